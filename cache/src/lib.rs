@@ -212,6 +212,53 @@ impl Cache {
         guild_roles
     }
 
+    /// Get the roles of a guild that the bot is able to assign to members. This needs the `Manage Roles`
+    /// permission & only covers the roles below the highest role of the bot
+    pub fn manageable_roles(&self, guild_id: GuildId) -> HashSet<RoleId> {
+        let mut manageable = HashSet::new();
+        let (Some(user), Some(guild)) = (self.current_user(), self.guild(guild_id)) else {
+            return manageable;
+        };
+        let Some(member) = self.member(guild_id, UserId(user.id)) else {
+            return manageable;
+        };
+        let server_roles = self
+            .guild_roles(guild_id)
+            .into_iter()
+            .map(|r| (r.id, r))
+            .collect::<HashMap<RoleId, Arc<CachedRole>>>();
+        let permissions =
+            match guild_wide_permissions(&guild, &server_roles, UserId(user.id), &member.roles) {
+                Ok(p) => p,
+                Err(why) => {
+                    tracing::error!(guild = ?guild_id, reason = ?why);
+                    return manageable;
+                }
+            };
+        if !permissions.intersects(Permissions::ADMINISTRATOR | Permissions::MANAGE_ROLES) {
+            return manageable;
+        }
+
+        let is_owner = guild.owner_id == UserId(user.id);
+        let highest_position = member
+            .roles
+            .iter()
+            .filter_map(|r| server_roles.get(r))
+            .map(|r| r.position)
+            .max()
+            .unwrap_or_default();
+        for role in server_roles.values() {
+            // The `@everyone` role shares its id with the guild & is held by every member already
+            if role.id.get() == guild_id.get() || role.managed {
+                continue;
+            }
+            if is_owner || role.position < highest_position {
+                manageable.insert(role.id);
+            }
+        }
+        manageable
+    }
+
     /// Get an immutable reference to a certain user
     pub fn user(&self, user_id: UserId) -> Option<Arc<User>> {
         self.0.users.get(&user_id).map(|u| Arc::clone(u.value()))
@@ -318,6 +365,7 @@ impl Cache {
             name: role.name,
             position: role.position,
             permissions: role.permissions,
+            managed: role.managed,
         };
         upsert_guild_item(&self.0.guild_roles, guild, role.id);
         upsert_item(&self.0.roles, role.id, role)
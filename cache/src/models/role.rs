@@ -10,4 +10,6 @@ pub struct CachedRole {
     pub name: String,
     pub position: i64,
     pub permissions: Permissions,
+    /// Whether the role is held by an integration. These roles can't be assigned by anyone
+    pub managed: bool,
 }
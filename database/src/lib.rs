@@ -9,6 +9,7 @@ use itertools::Itertools;
 use rowifi_models::{
     guild::RoGuild,
     id::{GuildId, UserId},
+    rolang::{Macro, Macros},
     user::{RoGuildUser, RoUser},
     FromRow,
 };
@@ -131,6 +132,13 @@ impl Database {
         }
     }
 
+    pub async fn get_macros(&self, guild_id: GuildId) -> Result<Macros, DatabaseError> {
+        let definitions = self
+            .query::<Macro>("SELECT * FROM macros WHERE guild_id = $1", &[&guild_id])
+            .await?;
+        Ok(Macros::compile(&definitions))
+    }
+
     pub async fn get_user(&self, user_id: i64) -> Result<Option<RoUser>, DatabaseError> {
         let client = self.get().await?;
         let statement = client
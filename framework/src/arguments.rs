@@ -13,6 +13,9 @@ use crate::utils::{parse_channel, parse_role, parse_username};
 #[derive(Debug, Clone)]
 pub struct Arguments {
     buf: Vec<String>,
+    /// The byte offset in `raw` that each argument starts at
+    offsets: Vec<usize>,
+    raw: String,
     idx: usize,
 }
 
@@ -58,6 +61,7 @@ pub trait FromArg {
 impl Arguments {
     pub fn new(buf: &str) -> Self {
         let mut args = Vec::new();
+        let mut offsets = Vec::new();
         let mut start_idx = 0;
         let mut quoted = false;
         let mut started = false;
@@ -73,6 +77,7 @@ impl Arguments {
                     let v = buf[start_idx..i].trim_matches(|c: char| c.is_whitespace() || c == '"');
                     if !v.is_empty() {
                         args.push(v.to_string());
+                        offsets.push(start_idx);
                     }
                     start_idx = i + 1;
                     started = false;
@@ -89,13 +94,21 @@ impl Arguments {
 
         match buf.get(start_idx..) {
             Some("") | None => {}
-            Some(s) => args.push(
-                s.trim_matches(|c: char| c.is_whitespace() || c == '"')
-                    .to_string(),
-            ),
+            Some(s) => {
+                args.push(
+                    s.trim_matches(|c: char| c.is_whitespace() || c == '"')
+                        .to_string(),
+                );
+                offsets.push(start_idx);
+            }
         }
 
-        Self { buf: args, idx: 0 }
+        Self {
+            buf: args,
+            offsets,
+            raw: buf.to_string(),
+            idx: 0,
+        }
     }
 
     #[allow(clippy::should_implement_trait)]
@@ -109,9 +122,16 @@ impl Arguments {
         self.idx -= 1;
     }
 
+    /// The remaining arguments as they were written, so line breaks are kept
     pub fn rest(&self) -> Option<String> {
-        let res = self.buf.get(self.idx..);
-        res.map(|s| s.join(" "))
+        match self.offsets.get(self.idx) {
+            Some(offset) => Some(
+                self.raw[*offset..]
+                    .trim_matches(|c: char| c.is_whitespace() || c == '"')
+                    .to_string(),
+            ),
+            None => self.buf.get(self.idx..).map(|s| s.join(" ")),
+        }
     }
 }
 
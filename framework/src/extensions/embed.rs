@@ -1,13 +1,21 @@
-use rowifi_models::{discord::datetime::Timestamp, id::RoleId};
+use rowifi_models::{bind::NicknameFallback, discord::datetime::Timestamp, id::RoleId};
 use twilight_embed_builder::{EmbedBuilder, EmbedFieldBuilder, EmbedFooterBuilder};
 
-use crate::utils::Color;
+use crate::{constants::EMBED_FIELD_DESCRIPTION_LIMIT, utils::Color};
 
 pub trait EmbedExtensions {
     #[must_use]
     fn default_data(self) -> Self;
     #[must_use]
-    fn update_log(self, added_roles: &[RoleId], removed_roles: &[RoleId], disc_nick: &str) -> Self;
+    fn update_log(
+        self,
+        added_roles: &[RoleId],
+        removed_roles: &[RoleId],
+        denied_roles: &[RoleId],
+        unmanageable_roles: &[RoleId],
+        disc_nick: &str,
+        nick_fallback: Option<&NicknameFallback>,
+    ) -> Self;
 }
 
 impl EmbedExtensions for EmbedBuilder {
@@ -17,15 +25,17 @@ impl EmbedExtensions for EmbedBuilder {
             .footer(EmbedFooterBuilder::new("RoWifi"))
     }
 
-    fn update_log(self, added_roles: &[RoleId], removed_roles: &[RoleId], disc_nick: &str) -> Self {
-        let mut added_str = added_roles
-            .iter()
-            .map(|a| format!("- <@&{}>\n", a.0))
-            .collect::<String>();
-        let mut removed_str = removed_roles
-            .iter()
-            .map(|r| format!("- <@&{}>\n", r.0))
-            .collect::<String>();
+    fn update_log(
+        self,
+        added_roles: &[RoleId],
+        removed_roles: &[RoleId],
+        denied_roles: &[RoleId],
+        unmanageable_roles: &[RoleId],
+        disc_nick: &str,
+        nick_fallback: Option<&NicknameFallback>,
+    ) -> Self {
+        let mut added_str = role_list(added_roles);
+        let mut removed_str = role_list(removed_roles);
         if added_str.is_empty() {
             added_str = "None".into();
         }
@@ -33,8 +43,40 @@ impl EmbedExtensions for EmbedBuilder {
             removed_str = "None".into();
         }
 
-        self.field(EmbedFieldBuilder::new("Nickname", disc_nick))
+        let nickname = match nick_fallback {
+            Some(fallback) => format!("{disc_nick}\n*{fallback}*"),
+            None => disc_nick.to_string(),
+        };
+        let mut embed = self
+            .field(EmbedFieldBuilder::new("Nickname", nickname))
             .field(EmbedFieldBuilder::new("Added Roles", added_str))
-            .field(EmbedFieldBuilder::new("Removed Roles", removed_str))
+            .field(EmbedFieldBuilder::new("Removed Roles", removed_str));
+        if !denied_roles.is_empty() {
+            embed = embed.field(EmbedFieldBuilder::new(
+                "Denied Roles",
+                role_list(denied_roles),
+            ));
+        }
+        if !unmanageable_roles.is_empty() {
+            embed = embed.field(EmbedFieldBuilder::new(
+                "Skipped Roles (above my highest role)",
+                role_list(unmanageable_roles),
+            ));
+        }
+        embed
+    }
+}
+
+/// Lists the roles one per line. Once the list would no longer fit in a field the rest of the
+/// roles are only counted
+fn role_list(roles: &[RoleId]) -> String {
+    let mut list = String::new();
+    for (index, role) in roles.iter().enumerate() {
+        let line = format!("- <@&{}>\n", role.0);
+        if list.len() + line.len() > EMBED_FIELD_DESCRIPTION_LIMIT - 32 {
+            return format!("{list}...and {} more", roles.len() - index);
+        }
+        list.push_str(&line);
     }
+    list
 }
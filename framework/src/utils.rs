@@ -268,6 +268,15 @@ pub async fn await_template_reply(
                     .await;
             }
         } else if let Event::MessageCreate(msg) = &event {
+            if let Some(slug) = Template::invalid_slug(&msg.content) {
+                ctx.respond()
+                    .content(&format!(
+                        "`{slug}` is not a valid slug. Please enter the template again"
+                    ))?
+                    .exec()
+                    .await?;
+                continue;
+            }
             ctx.bot.ignore_message_components.remove(&message_id);
             return Ok(Template(msg.content.clone()));
         }
@@ -1,4 +1,5 @@
 use bytes::BytesMut;
+use chrono::{DateTime, Utc};
 use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
@@ -26,10 +27,22 @@ pub struct Assetbind {
     pub asset_type: AssetType,
     /// The discord roles bounded to the asset
     pub discord_roles: Vec<RoleId>,
+    /// The discord roles removed from members matching the bind, even if another bind grants them
+    #[serde(default)]
+    pub removed_roles: Vec<RoleId>,
     /// The number that decides whether this bind is chosen for the nickname
     pub priority: i32,
     /// The format of the nickname if this bind is chosen
     pub template: Template,
+    /// The templates tried in order when the nickname of `template` is too long
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    /// The time from which the bind applies. Applies from creation if not set
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    /// The time after which the bind no longer applies. Never expires if not set
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -37,11 +50,21 @@ pub struct AssetbindBackup {
     pub asset_id: i64,
     pub asset_type: AssetType,
     pub discord_roles: Vec<String>,
+    #[serde(default)]
+    pub removed_roles: Vec<String>,
     pub priority: i32,
     pub template: Template,
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
 }
 
-#[derive(Clone, Copy, Debug, Deserialize_repr, Eq, Ord, PartialEq, PartialOrd, Serialize_repr)]
+#[derive(
+    Clone, Copy, Debug, Deserialize_repr, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize_repr,
+)]
 #[repr(u8)]
 pub enum AssetType {
     Asset = 0,
@@ -55,16 +78,24 @@ impl FromRow for Assetbind {
         let asset_id = row.try_get("asset_id")?;
         let asset_type = row.try_get("asset_type")?;
         let discord_roles = row.try_get("discord_roles")?;
+        let removed_roles = row.try_get("removed_roles")?;
         let priority = row.try_get("priority")?;
         let template = row.try_get("template")?;
+        let fallback_templates = row.try_get("fallback_templates")?;
+        let active_from = row.try_get("active_from")?;
+        let active_until = row.try_get("active_until")?;
 
         Ok(Self {
             bind_id,
             asset_id,
             asset_type,
             discord_roles,
+            removed_roles,
             priority,
             template,
+            fallback_templates,
+            active_from,
+            active_until,
         })
     }
 }
@@ -115,7 +146,7 @@ impl<'a> FromSql<'a> for AssetType {
             0 => Ok(AssetType::Asset),
             1 => Ok(AssetType::Badge),
             2 => Ok(AssetType::Gamepass),
-            _ => unreachable!(),
+            _ => Err(format!("Unknown asset type {bind_type}").into()),
         }
     }
 
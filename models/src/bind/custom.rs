@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{
     de::{Error as DeError, IgnoredAny, MapAccess, Visitor},
     Deserialize, Deserializer, Serialize,
@@ -20,12 +21,24 @@ pub struct Custombind {
     pub custom_bind_id: i32,
     /// The discord roles bound to the custombind
     pub discord_roles: Vec<RoleId>,
+    /// The discord roles removed from members matching the bind, even if another bind grants them
+    #[serde(default)]
+    pub removed_roles: Vec<RoleId>,
     /// The code of the bind
     pub code: String,
     /// The number that decides whether this bind is chosen for the nickname
     pub priority: i32,
     /// The format of the nickname if this bind is chosen
     pub template: Template,
+    /// The templates tried in order when the nickname of `template` is too long
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    /// The time from which the bind applies. Applies from creation if not set
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    /// The time after which the bind no longer applies. Never expires if not set
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
     #[serde(skip_serializing)]
     pub command: RoCommand,
 }
@@ -34,9 +47,21 @@ pub struct Custombind {
 pub struct CustombindBackup {
     pub custom_bind_id: i32,
     pub discord_roles: Vec<String>,
+    #[serde(default)]
+    pub removed_roles: Vec<String>,
     pub code: String,
     pub priority: i32,
     pub template: Template,
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
+    /// The grammar version the code was written under. Backups made before this was recorded
+    /// default to the version from before the precedence update
+    #[serde(default)]
+    pub grammar_version: i32,
 }
 
 impl FromRow for Custombind {
@@ -44,18 +69,27 @@ impl FromRow for Custombind {
         let bind_id = row.try_get("bind_id")?;
         let custom_bind_id = row.try_get("custom_bind_id")?;
         let discord_roles = row.try_get("discord_roles")?;
-        let code: String = row.try_get("code")?;
+        let removed_roles = row.try_get("removed_roles")?;
+        let code = row.try_get("code")?;
         let priority = row.try_get("priority")?;
         let template = row.try_get("template")?;
-        let command = RoCommand::new(&code).unwrap();
+        let fallback_templates = row.try_get("fallback_templates")?;
+        let active_from = row.try_get("active_from")?;
+        let active_until = row.try_get("active_until")?;
+        let mut command: RoCommand = row.try_get("code")?;
+        command.grammar_version = row.try_get("grammar_version")?;
 
         Ok(Self {
             bind_id,
             custom_bind_id,
             discord_roles,
+            removed_roles,
             code,
             priority,
             template,
+            fallback_templates,
+            active_from,
+            active_until,
             command,
         })
     }
@@ -69,9 +103,13 @@ impl<'de> Deserialize<'de> for Custombind {
             BindId,
             CustomBindId,
             DiscordRoles,
+            RemovedRoles,
             Code,
             Priority,
             Template,
+            FallbackTemplates,
+            ActiveFrom,
+            ActiveUntil,
         }
 
         struct CustomBindVisitor;
@@ -87,9 +125,13 @@ impl<'de> Deserialize<'de> for Custombind {
                 let mut bind_id = None;
                 let mut custom_bind_id = None;
                 let mut discord_roles = None;
+                let mut removed_roles = None;
                 let mut code = None::<String>;
                 let mut priority = None;
                 let mut template = None;
+                let mut fallback_templates = None;
+                let mut active_from = None;
+                let mut active_until = None;
 
                 loop {
                     let key = match map.next_key() {
@@ -120,6 +162,12 @@ impl<'de> Deserialize<'de> for Custombind {
                             }
                             discord_roles = Some(map.next_value()?);
                         }
+                        Field::RemovedRoles => {
+                            if removed_roles.is_some() {
+                                return Err(DeError::duplicate_field("removed_roles"));
+                            }
+                            removed_roles = Some(map.next_value()?);
+                        }
                         Field::Code => {
                             if code.is_some() {
                                 return Err(DeError::duplicate_field("code"));
@@ -138,6 +186,24 @@ impl<'de> Deserialize<'de> for Custombind {
                             }
                             template = Some(map.next_value()?);
                         }
+                        Field::FallbackTemplates => {
+                            if fallback_templates.is_some() {
+                                return Err(DeError::duplicate_field("fallback_templates"));
+                            }
+                            fallback_templates = Some(map.next_value()?);
+                        }
+                        Field::ActiveFrom => {
+                            if active_from.is_some() {
+                                return Err(DeError::duplicate_field("active_from"));
+                            }
+                            active_from = Some(map.next_value()?);
+                        }
+                        Field::ActiveUntil => {
+                            if active_until.is_some() {
+                                return Err(DeError::duplicate_field("active_until"));
+                            }
+                            active_until = Some(map.next_value()?);
+                        }
                     }
                 }
 
@@ -146,18 +212,26 @@ impl<'de> Deserialize<'de> for Custombind {
                     custom_bind_id.ok_or_else(|| DeError::missing_field("custom_bind_id"))?;
                 let discord_roles =
                     discord_roles.ok_or_else(|| DeError::missing_field("discord_roles"))?;
+                let removed_roles = removed_roles.unwrap_or_default();
                 let priority = priority.ok_or_else(|| DeError::missing_field("priority"))?;
                 let code = code.ok_or_else(|| DeError::missing_field("code"))?;
                 let template = template.ok_or_else(|| DeError::missing_field("template"))?;
-                let command = RoCommand::new(&code).unwrap();
+                let fallback_templates = fallback_templates.unwrap_or_default();
+                let active_from = active_from.unwrap_or_default();
+                let active_until = active_until.unwrap_or_default();
+                let command = RoCommand::parse(&code).map_err(DeError::custom)?;
 
                 Ok(Custombind {
                     bind_id,
                     custom_bind_id,
                     discord_roles,
+                    removed_roles,
                     code,
                     priority,
                     template,
+                    fallback_templates,
+                    active_from,
+                    active_until,
                     command,
                 })
             }
@@ -167,9 +241,13 @@ impl<'de> Deserialize<'de> for Custombind {
             "bind_id",
             "custom_bind_id",
             "discord_roles",
+            "removed_roles",
             "code",
             "priority",
             "template",
+            "fallback_templates",
+            "active_from",
+            "active_until",
         ];
 
         deserializer.deserialize_struct("Custombind", FIELDS, CustomBindVisitor)
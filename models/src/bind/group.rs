@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use super::Template;
@@ -16,18 +17,38 @@ pub struct Groupbind {
     pub group_id: i64,
     /// The discord roles bound to the group
     pub discord_roles: Vec<RoleId>,
+    /// The discord roles removed from members matching the bind, even if another bind grants them
+    #[serde(default)]
+    pub removed_roles: Vec<RoleId>,
     /// The number that decides whether this bind is chosen for the nickname
     pub priority: i32,
     /// The format of the nickname if this bind is chosen
     pub template: Template,
+    /// The templates tried in order when the nickname of `template` is too long
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    /// The time from which the bind applies. Applies from creation if not set
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    /// The time after which the bind no longer applies. Never expires if not set
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct GroupbindBackup {
     pub group_id: i64,
     pub discord_roles: Vec<String>,
+    #[serde(default)]
+    pub removed_roles: Vec<String>,
     pub priority: i32,
     pub template: Template,
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
 }
 
 impl FromRow for Groupbind {
@@ -35,15 +56,23 @@ impl FromRow for Groupbind {
         let bind_id = row.try_get("bind_id")?;
         let group_id = row.try_get("group_id")?;
         let discord_roles = row.try_get("discord_roles")?;
+        let removed_roles = row.try_get("removed_roles")?;
         let priority = row.try_get("priority")?;
         let template = row.try_get("template")?;
+        let fallback_templates = row.try_get("fallback_templates")?;
+        let active_from = row.try_get("active_from")?;
+        let active_until = row.try_get("active_until")?;
 
         Ok(Self {
             bind_id,
             group_id,
             discord_roles,
+            removed_roles,
             priority,
             template,
+            fallback_templates,
+            active_from,
+            active_until,
         })
     }
 }
@@ -0,0 +1,290 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use super::Bind;
+use crate::{
+    id::RoleId,
+    rolang::{Diagnostic, Macros, RoCommand},
+};
+
+/// The highest rank in a group. Rank 0 is used for users outside the group
+const MAX_RANK: i64 = 255;
+
+/// Everything the binds of a guild are checked against
+pub struct LintInput<'a> {
+    pub binds: &'a [Bind],
+    /// The roles that currently exist in the server
+    pub guild_roles: &'a HashSet<RoleId>,
+    /// The ranks of the groups of the rankbinds & range binds. Groups missing from the map aren't
+    /// checked, while a group that no longer exists should map to no ranks
+    pub group_ranks: &'a HashMap<i64, Vec<i64>>,
+    /// The macros of the server, which the code of custombinds is checked with
+    pub macros: &'a Macros,
+}
+
+/// A mistake in the binds of a guild that doesn't stop them from being used
+#[derive(Debug)]
+pub enum BindLint<'a> {
+    /// A custombind whose code no longer compiles or type checks. It never matches
+    InvalidCode {
+        bind: &'a Bind,
+        diagnostic: Diagnostic,
+    },
+    /// A role of a bind that has been deleted from the server
+    DeletedRole { bind: &'a Bind, role: RoleId },
+    /// A rankbind for a rank the group doesn't have, or a range bind spanning none of its ranks
+    MissingRank(&'a Bind),
+    /// Two binds that match the exact same members
+    Duplicate(&'a Bind, &'a Bind),
+    /// A role given by one bind & removed by another that can match the same member. The removal
+    /// always wins
+    ContradictoryRole {
+        role: RoleId,
+        giver: &'a Bind,
+        remover: &'a Bind,
+    },
+    /// Binds of the same priority with different templates that can match the same member, so
+    /// which template is used is down to the order the binds are loaded in
+    DuplicatePriority { priority: i32, binds: Vec<&'a Bind> },
+    /// A bind whose template is never used as a bind of a higher priority always matches with it
+    UnreachableNickname {
+        bind: &'a Bind,
+        shadowed_by: &'a Bind,
+    },
+}
+
+impl BindLint<'_> {
+    #[must_use]
+    pub const fn title(&self) -> &'static str {
+        match self {
+            BindLint::InvalidCode { .. } => "Invalid Code",
+            BindLint::DeletedRole { .. } => "Deleted Role",
+            BindLint::MissingRank(_) => "Missing Rank",
+            BindLint::Duplicate(..) => "Duplicate Binds",
+            BindLint::ContradictoryRole { .. } => "Contradictory Role",
+            BindLint::DuplicatePriority { .. } => "Duplicate Priority",
+            BindLint::UnreachableNickname { .. } => "Unreachable Nickname",
+        }
+    }
+}
+
+/// Checks the binds of a guild for broken code, deleted roles, missing ranks, duplicates,
+/// contradicting roles & nicknames that are arbitrary or never used
+#[must_use]
+pub fn lint_binds<'a>(input: &LintInput<'a>) -> Vec<BindLint<'a>> {
+    let binds = input.binds;
+    let mut lints = Vec::new();
+
+    for bind in binds {
+        if let Bind::Custom(c) = bind {
+            if let Err(diagnostic) = RoCommand::new(&c.code, input.macros) {
+                lints.push(BindLint::InvalidCode { bind, diagnostic });
+            }
+        }
+    }
+
+    for bind in binds {
+        for role in bind.discord_roles().iter().chain(bind.removed_roles()) {
+            if !input.guild_roles.contains(role) {
+                lints.push(BindLint::DeletedRole { bind, role: *role });
+            }
+        }
+    }
+
+    for bind in binds {
+        let missing = match bind {
+            Bind::Rank(r) => match input.group_ranks.get(&r.group_id) {
+                Some(ranks) => r.group_rank_id != 0 && !ranks.contains(&r.group_rank_id),
+                None => false,
+            },
+            Bind::Range(r) => match input.group_ranks.get(&r.group_id) {
+                Some(ranks) => r.min_rank > 0 && !ranks.iter().any(|rank| r.contains(*rank)),
+                None => false,
+            },
+            _ => false,
+        };
+        if missing {
+            lints.push(BindLint::MissingRank(bind));
+        }
+    }
+
+    for (index, first) in binds.iter().enumerate() {
+        for second in &binds[index + 1..] {
+            if same_condition(first, second) {
+                lints.push(BindLint::Duplicate(first, second));
+            }
+        }
+    }
+
+    for giver in binds {
+        for remover in binds {
+            if !std::ptr::eq(giver, remover) && exclusive(giver, remover) {
+                continue;
+            }
+            for role in giver.discord_roles() {
+                if remover.removed_roles().contains(role) {
+                    lints.push(BindLint::ContradictoryRole {
+                        role: *role,
+                        giver,
+                        remover,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut by_priority = HashMap::<i32, Vec<&Bind>>::new();
+    for bind in binds {
+        by_priority.entry(bind.priority()).or_default().push(bind);
+    }
+    let mut priorities = by_priority.into_iter().collect::<Vec<_>>();
+    priorities.sort_unstable_by_key(|(priority, _)| *priority);
+    for (priority, tied) in priorities {
+        let conflicting = tied
+            .iter()
+            .filter(|a| {
+                tied.iter()
+                    .any(|b| !std::ptr::eq(**a, *b) && !same_templates(a, b) && !exclusive(a, b))
+            })
+            .copied()
+            .collect::<Vec<_>>();
+        if !conflicting.is_empty() {
+            lints.push(BindLint::DuplicatePriority {
+                priority,
+                binds: conflicting,
+            });
+        }
+    }
+
+    for bind in binds {
+        let shadowed_by = binds.iter().find(|other| {
+            other.priority() > bind.priority()
+                && !same_templates(bind, other)
+                && !same_condition(bind, other)
+                && always_matches_with(other, bind)
+        });
+        if let Some(shadowed_by) = shadowed_by {
+            lints.push(BindLint::UnreachableNickname { bind, shadowed_by });
+        }
+    }
+
+    lints
+}
+
+/// The group & the lowest & highest rank matched by binds decided by group rank alone
+fn rank_condition(bind: &Bind) -> Option<(i64, i64, i64)> {
+    match bind {
+        Bind::Rank(r) => Some((r.group_id, r.group_rank_id, r.group_rank_id)),
+        Bind::Range(r) => Some((r.group_id, r.min_rank, r.max_rank)),
+        Bind::Group(g) => Some((g.group_id, 1, MAX_RANK)),
+        _ => None,
+    }
+}
+
+/// Whether both binds match the exact same members over the same time
+fn same_condition(a: &Bind, b: &Bind) -> bool {
+    if a.active_from() != b.active_from() || a.active_until() != b.active_until() {
+        return false;
+    }
+    match (a, b) {
+        (Bind::Custom(a), Bind::Custom(b)) => a.command.formatted() == b.command.formatted(),
+        (Bind::Asset(a), Bind::Asset(b)) => {
+            a.asset_type == b.asset_type && a.asset_id == b.asset_id
+        }
+        _ => match (rank_condition(a), rank_condition(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+    }
+}
+
+/// Whether the binds can never match the same member at once
+fn exclusive(a: &Bind, b: &Bind) -> bool {
+    if ends_before(a, b) || ends_before(b, a) {
+        return true;
+    }
+    match (rank_condition(a), rank_condition(b)) {
+        (Some((a_group, a_min, a_max)), Some((b_group, b_min, b_max))) => {
+            a_group == b_group && (a_max < b_min || b_max < a_min)
+        }
+        _ => false,
+    }
+}
+
+/// Whether `a` stops applying before `b` starts
+fn ends_before(a: &Bind, b: &Bind) -> bool {
+    match (a.active_until(), b.active_from()) {
+        (Some(until), Some(from)) => until <= from,
+        _ => false,
+    }
+}
+
+/// Whether `shadow` is always active & matches every member `bind` matches
+fn always_matches_with(shadow: &Bind, bind: &Bind) -> bool {
+    if shadow.active_from().is_some() || shadow.active_until().is_some() {
+        return false;
+    }
+    match (rank_condition(shadow), rank_condition(bind)) {
+        (Some((s_group, s_min, s_max)), Some((b_group, b_min, b_max))) => {
+            s_group == b_group && s_min <= b_min && b_max <= s_max
+        }
+        _ => false,
+    }
+}
+
+/// Whether both binds give the same nickname
+fn same_templates(a: &Bind, b: &Bind) -> bool {
+    a.template() == b.template() && a.fallback_templates() == b.fallback_templates()
+}
+
+impl Display for BindLint<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            BindLint::InvalidCode { bind, diagnostic } => write!(
+                f,
+                "{bind} never matches since its code is invalid: {diagnostic}"
+            ),
+            BindLint::DeletedRole { bind, role } => write!(
+                f,
+                "The role `{role}` of {bind} no longer exists in the server"
+            ),
+            BindLint::MissingRank(bind) => {
+                write!(f, "{bind} is for a rank that does not exist in the group")
+            }
+            BindLint::Duplicate(first, second) => {
+                write!(f, "{first} and {second} match the same members")
+            }
+            BindLint::ContradictoryRole {
+                role,
+                giver,
+                remover,
+            } => {
+                if std::ptr::eq(*giver, *remover) {
+                    write!(f, "{giver} both gives and removes <@&{role}>")
+                } else {
+                    write!(
+                        f,
+                        "<@&{role}> is given by {giver} but removed by {remover}, which can match the same members"
+                    )
+                }
+            }
+            BindLint::DuplicatePriority { priority, binds } => {
+                let binds = binds
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(
+                    f,
+                    "{binds} share the priority {priority} and can match the same members, so which template is used is arbitrary"
+                )
+            }
+            BindLint::UnreachableNickname { bind, shadowed_by } => write!(
+                f,
+                "The template of {bind} is never used since {shadowed_by} has a higher priority and always matches along with it"
+            ),
+        }
+    }
+}
@@ -1,25 +1,37 @@
 mod asset;
 mod custom;
 mod group;
+mod lint;
+mod range;
 mod rank;
 mod template;
 
 pub use asset::{AssetType, Assetbind, AssetbindBackup};
 pub use custom::{Custombind, CustombindBackup};
 pub use group::{Groupbind, GroupbindBackup};
+pub use lint::{lint_binds, BindLint, LintInput};
+pub use range::{Rangebind, RangebindBackup};
 pub use rank::{Rankbind, RankbindBackup};
-pub use template::Template;
+pub use template::{NicknameFallback, Template, NICKNAME_LIMIT};
 
 use bytes::BytesMut;
+use chrono::{DateTime, Utc};
 use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
 use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter, Result as FmtResult};
 
-use crate::{id::RoleId, roblox::user::PartialUser as RobloxUser, user::RoGuildUser, FromRow};
+use crate::{
+    id::{BindId, RoleId},
+    roblox::{group::GroupUserRole, user::PartialUser as RobloxUser},
+    user::RoGuildUser,
+    FromRow,
+};
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 #[serde(untagged)]
 pub enum Bind {
     Rank(Rankbind),
+    Range(Rangebind),
     Group(Groupbind),
     Custom(Custombind),
     Asset(Assetbind),
@@ -29,6 +41,8 @@ pub enum Bind {
 #[serde(untagged)]
 pub enum BindBackup {
     Rank(RankbindBackup),
+    // Has to be tried before groupbinds since a range backup also has every field of a groupbind
+    Range(RangebindBackup),
     Group(GroupbindBackup),
     Custom(CustombindBackup),
     Asset(AssetbindBackup),
@@ -41,13 +55,37 @@ pub enum BindType {
     Group = 1,
     Custom = 2,
     Asset = 3,
+    Range = 4,
 }
 
 impl Bind {
+    #[must_use]
+    pub const fn bind_id(&self) -> BindId {
+        match self {
+            Bind::Rank(r) => r.bind_id,
+            Bind::Range(r) => r.bind_id,
+            Bind::Group(g) => g.bind_id,
+            Bind::Custom(c) => c.bind_id,
+            Bind::Asset(a) => a.bind_id,
+        }
+    }
+
+    #[must_use]
+    pub const fn template(&self) -> &Template {
+        match self {
+            Bind::Rank(r) => &r.template,
+            Bind::Range(r) => &r.template,
+            Bind::Group(g) => &g.template,
+            Bind::Custom(c) => &c.template,
+            Bind::Asset(a) => &a.template,
+        }
+    }
+
     #[must_use]
     pub fn priority(&self) -> i32 {
         match self {
             Bind::Rank(r) => r.priority,
+            Bind::Range(r) => r.priority,
             Bind::Group(g) => g.priority,
             Bind::Custom(c) => c.priority,
             Bind::Asset(a) => a.priority,
@@ -55,34 +93,101 @@ impl Bind {
     }
 
     #[must_use]
-    pub fn nickname(
+    pub fn fallback_templates(&self) -> &[Template] {
+        match self {
+            Bind::Rank(r) => &r.fallback_templates,
+            Bind::Range(r) => &r.fallback_templates,
+            Bind::Group(g) => &g.fallback_templates,
+            Bind::Custom(c) => &c.fallback_templates,
+            Bind::Asset(a) => &a.fallback_templates,
+        }
+    }
+
+    /// The nickname of a member matching the bind, trying its fallback templates when the
+    /// template makes it too long. See [`Template::fitting_nickname`]
+    #[must_use]
+    pub fn fitting_nickname(
         &self,
         roblox_user: &RobloxUser,
         user: &RoGuildUser,
         discord_username: &str,
-    ) -> String {
-        match self {
-            Bind::Rank(r) => r.template.nickname(roblox_user, user, discord_username),
-            Bind::Group(g) => g.template.nickname(roblox_user, user, discord_username),
-            Bind::Custom(c) => c.template.nickname(roblox_user, user, discord_username),
-            Bind::Asset(a) => a.template.nickname(roblox_user, user, discord_username),
-        }
+        group_roles: &[GroupUserRole],
+    ) -> (String, Option<NicknameFallback>) {
+        self.template().fitting_nickname(
+            self.fallback_templates(),
+            roblox_user,
+            user,
+            discord_username,
+            group_roles,
+        )
     }
 
     #[must_use]
     pub fn discord_roles(&self) -> &[RoleId] {
         match self {
             Bind::Rank(r) => &r.discord_roles,
+            Bind::Range(r) => &r.discord_roles,
             Bind::Group(g) => &g.discord_roles,
             Bind::Custom(c) => &c.discord_roles,
             Bind::Asset(a) => &a.discord_roles,
         }
     }
 
+    /// The roles removed from members matching the bind. These take precedence over the roles
+    /// granted by any bind
+    #[must_use]
+    pub fn removed_roles(&self) -> &[RoleId] {
+        match self {
+            Bind::Rank(r) => &r.removed_roles,
+            Bind::Range(r) => &r.removed_roles,
+            Bind::Group(g) => &g.removed_roles,
+            Bind::Custom(c) => &c.removed_roles,
+            Bind::Asset(a) => &a.removed_roles,
+        }
+    }
+
+    #[must_use]
+    pub const fn active_from(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Bind::Rank(r) => r.active_from,
+            Bind::Range(r) => r.active_from,
+            Bind::Group(g) => g.active_from,
+            Bind::Custom(c) => c.active_from,
+            Bind::Asset(a) => a.active_from,
+        }
+    }
+
+    #[must_use]
+    pub const fn active_until(&self) -> Option<DateTime<Utc>> {
+        match self {
+            Bind::Rank(r) => r.active_until,
+            Bind::Range(r) => r.active_until,
+            Bind::Group(g) => g.active_until,
+            Bind::Custom(c) => c.active_until,
+            Bind::Asset(a) => a.active_until,
+        }
+    }
+
+    /// Whether the bind applies at the given time
+    #[must_use]
+    pub fn is_active(&self, now: DateTime<Utc>) -> bool {
+        match self.active_from() {
+            Some(from) if from > now => false,
+            _ => !self.is_expired(now),
+        }
+    }
+
+    /// Whether the bind stopped applying at or before the given time
+    #[must_use]
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        matches!(self.active_until(), Some(until) if until <= now)
+    }
+
     #[must_use]
     pub const fn kind(&self) -> BindType {
         match self {
             Self::Rank(_) => BindType::Rank,
+            Self::Range(_) => BindType::Range,
             Self::Group(_) => BindType::Group,
             Self::Custom(_) => BindType::Custom,
             Self::Asset(_) => BindType::Asset,
@@ -95,6 +200,7 @@ impl BindBackup {
     pub const fn kind(&self) -> BindType {
         match self {
             Self::Rank(_) => BindType::Rank,
+            Self::Range(_) => BindType::Range,
             Self::Group(_) => BindType::Group,
             Self::Custom(_) => BindType::Custom,
             Self::Asset(_) => BindType::Asset,
@@ -105,11 +211,39 @@ impl BindBackup {
     pub fn discord_roles(&self) -> &[String] {
         match self {
             Self::Rank(r) => &r.discord_roles,
+            Self::Range(r) => &r.discord_roles,
             Self::Group(g) => &g.discord_roles,
             Self::Custom(c) => &c.discord_roles,
             Self::Asset(a) => &a.discord_roles,
         }
     }
+
+    #[must_use]
+    pub fn removed_roles(&self) -> &[String] {
+        match self {
+            Self::Rank(r) => &r.removed_roles,
+            Self::Range(r) => &r.removed_roles,
+            Self::Group(g) => &g.removed_roles,
+            Self::Custom(c) => &c.removed_roles,
+            Self::Asset(a) => &a.removed_roles,
+        }
+    }
+}
+
+impl Display for Bind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Bind::Rank(r) => write!(f, "Rankbind {} (Rank {})", r.group_id, r.group_rank_id),
+            Bind::Range(r) => write!(
+                f,
+                "Rankbind {} (Ranks {}-{})",
+                r.group_id, r.min_rank, r.max_rank
+            ),
+            Bind::Group(g) => write!(f, "Groupbind {}", g.group_id),
+            Bind::Custom(c) => write!(f, "Custombind {}", c.custom_bind_id),
+            Bind::Asset(a) => write!(f, "Assetbind {} ({})", a.asset_id, a.asset_type),
+        }
+    }
 }
 
 impl FromRow for Bind {
@@ -120,6 +254,7 @@ impl FromRow for Bind {
             BindType::Group => Ok(Bind::Group(Groupbind::from_row(row)?)),
             BindType::Custom => Ok(Bind::Custom(Custombind::from_row(row)?)),
             BindType::Asset => Ok(Bind::Asset(Assetbind::from_row(row)?)),
+            BindType::Range => Ok(Bind::Range(Rangebind::from_row(row)?)),
         }
     }
 }
@@ -151,7 +286,8 @@ impl<'a> FromSql<'a> for BindType {
             1 => Ok(BindType::Group),
             2 => Ok(BindType::Custom),
             3 => Ok(BindType::Asset),
-            _ => unreachable!(),
+            4 => Ok(BindType::Range),
+            _ => Err(format!("Unknown bind type {bind_type}").into()),
         }
     }
 
@@ -0,0 +1,181 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    id::{BindId, RoleId},
+    serialize_i64_as_string, FromRow,
+};
+
+use super::{rank::Rankbind, template::Template};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Rangebind {
+    /// The global id of the bind
+    pub bind_id: BindId,
+    /// The Id of the Group
+    #[serde(serialize_with = "serialize_i64_as_string")]
+    pub group_id: i64,
+    /// The discord roles bound to the ranks
+    pub discord_roles: Vec<RoleId>,
+    /// The discord roles removed from members matching the bind, even if another bind grants them
+    #[serde(default)]
+    pub removed_roles: Vec<RoleId>,
+    /// The lowest rank id in the group (0-255) the bind applies to
+    #[serde(serialize_with = "serialize_i64_as_string")]
+    pub min_rank: i64,
+    /// The highest rank id in the group (0-255) the bind applies to
+    #[serde(serialize_with = "serialize_i64_as_string")]
+    pub max_rank: i64,
+    /// The number that decides whether this bind is chosen for the nickname
+    pub priority: i32,
+    /// The format of the nickname if this bind is chosen
+    pub template: Template,
+    /// The templates tried in order when the nickname of `template` is too long
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    /// The time from which the bind applies. Applies from creation if not set
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    /// The time after which the bind no longer applies. Never expires if not set
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RangebindBackup {
+    pub group_id: i64,
+    pub discord_roles: Vec<String>,
+    #[serde(default)]
+    pub removed_roles: Vec<String>,
+    pub min_rank: i64,
+    pub max_rank: i64,
+    pub priority: i32,
+    pub template: Template,
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
+}
+
+impl Rangebind {
+    /// Whether the bind applies to a member with the given rank in the group. Members who are not
+    /// in the group are treated as having rank 0
+    #[must_use]
+    pub fn contains(&self, rank: i64) -> bool {
+        self.min_rank <= rank && rank <= self.max_rank
+    }
+
+    /// Whether the two binds are for the same group & share at least one rank
+    #[must_use]
+    pub fn overlaps(&self, other: &Rangebind) -> bool {
+        self.group_id == other.group_id
+            && self.min_rank <= other.max_rank
+            && other.min_rank <= self.max_rank
+    }
+
+    /// Finds runs of rankbinds with the same roles, template & priority that cover consecutive
+    /// ranks of a group. Every run of two or more binds is returned as a range bind along with the
+    /// ids of the rankbinds it replaces. `group_ranks` are the rank ids of every rank in the group
+    #[must_use]
+    pub fn merge(
+        group_id: i64,
+        rankbinds: &[Rankbind],
+        group_ranks: &[i64],
+    ) -> Vec<(Rangebind, Vec<BindId>)> {
+        let mut group_ranks = group_ranks.to_vec();
+        group_ranks.sort_unstable();
+        group_ranks.dedup();
+
+        let mut merged = Vec::new();
+        let mut run: Vec<&Rankbind> = Vec::new();
+        for rank in group_ranks {
+            let bind = rankbinds
+                .iter()
+                .find(|r| r.group_id == group_id && r.group_rank_id == rank);
+            match (bind, run.last()) {
+                (Some(bind), Some(last)) if same_effect(bind, last) => run.push(bind),
+                (Some(bind), _) => {
+                    push_run(&mut merged, &run);
+                    run = vec![bind];
+                }
+                (None, _) => {
+                    push_run(&mut merged, &run);
+                    run.clear();
+                }
+            }
+        }
+        push_run(&mut merged, &run);
+        merged
+    }
+}
+
+fn same_effect(a: &Rankbind, b: &Rankbind) -> bool {
+    a.priority == b.priority
+        && a.template == b.template
+        && a.fallback_templates == b.fallback_templates
+        && a.active_from == b.active_from
+        && a.active_until == b.active_until
+        && same_roles(&a.discord_roles, &b.discord_roles)
+        && same_roles(&a.removed_roles, &b.removed_roles)
+}
+
+fn same_roles(a: &[RoleId], b: &[RoleId]) -> bool {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    a.sort_unstable();
+    a.dedup();
+    b.sort_unstable();
+    b.dedup();
+    a == b
+}
+
+fn push_run(merged: &mut Vec<(Rangebind, Vec<BindId>)>, run: &[&Rankbind]) {
+    if let (Some(first), Some(last), true) = (run.first(), run.last(), run.len() > 1) {
+        let bind = Rangebind {
+            bind_id: BindId::default(),
+            group_id: first.group_id,
+            discord_roles: first.discord_roles.clone(),
+            removed_roles: first.removed_roles.clone(),
+            min_rank: first.group_rank_id,
+            max_rank: last.group_rank_id,
+            priority: first.priority,
+            template: first.template.clone(),
+            fallback_templates: first.fallback_templates.clone(),
+            active_from: first.active_from,
+            active_until: first.active_until,
+        };
+        merged.push((bind, run.iter().map(|r| r.bind_id).collect()));
+    }
+}
+
+impl FromRow for Rangebind {
+    fn from_row(row: tokio_postgres::Row) -> Result<Self, tokio_postgres::Error> {
+        let bind_id = row.try_get("bind_id")?;
+        let group_id = row.try_get("group_id")?;
+        let discord_roles = row.try_get("discord_roles")?;
+        let removed_roles = row.try_get("removed_roles")?;
+        let min_rank = row.try_get("min_rank")?;
+        let max_rank = row.try_get("max_rank")?;
+        let priority = row.try_get("priority")?;
+        let template = row.try_get("template")?;
+        let fallback_templates = row.try_get("fallback_templates")?;
+        let active_from = row.try_get("active_from")?;
+        let active_until = row.try_get("active_until")?;
+
+        Ok(Self {
+            bind_id,
+            group_id,
+            discord_roles,
+            removed_roles,
+            min_rank,
+            max_rank,
+            priority,
+            template,
+            fallback_templates,
+            active_from,
+            active_until,
+        })
+    }
+}
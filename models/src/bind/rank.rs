@@ -1,3 +1,4 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
 use crate::{
@@ -16,6 +17,9 @@ pub struct Rankbind {
     pub group_id: i64,
     /// The discord roles bound to the rank
     pub discord_roles: Vec<RoleId>,
+    /// The discord roles removed from members matching the bind, even if another bind grants them
+    #[serde(default)]
+    pub removed_roles: Vec<RoleId>,
     /// The Id of the rank in the group (0-255)
     #[serde(serialize_with = "serialize_i64_as_string")]
     pub group_rank_id: i64,
@@ -26,16 +30,33 @@ pub struct Rankbind {
     pub priority: i32,
     /// The format of the nickname if this bind is chosen
     pub template: Template,
+    /// The templates tried in order when the nickname of `template` is too long
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    /// The time from which the bind applies. Applies from creation if not set
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    /// The time after which the bind no longer applies. Never expires if not set
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct RankbindBackup {
     pub group_id: i64,
     pub discord_roles: Vec<String>,
+    #[serde(default)]
+    pub removed_roles: Vec<String>,
     pub group_rank_id: i64,
     pub roblox_rank_id: i64,
     pub priority: i32,
     pub template: Template,
+    #[serde(default)]
+    pub fallback_templates: Vec<Template>,
+    #[serde(default)]
+    pub active_from: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub active_until: Option<DateTime<Utc>>,
 }
 
 impl FromRow for Rankbind {
@@ -43,19 +64,27 @@ impl FromRow for Rankbind {
         let bind_id = row.try_get("bind_id")?;
         let group_id = row.try_get("group_id")?;
         let discord_roles = row.try_get("discord_roles")?;
+        let removed_roles = row.try_get("removed_roles")?;
         let group_rank_id = row.try_get("group_rank_id")?;
         let roblox_rank_id = row.try_get("roblox_rank_id")?;
         let priority = row.try_get("priority")?;
         let template = row.try_get("template")?;
+        let fallback_templates = row.try_get("fallback_templates")?;
+        let active_from = row.try_get("active_from")?;
+        let active_until = row.try_get("active_until")?;
 
         Ok(Self {
             bind_id,
             group_id,
             discord_roles,
+            removed_roles,
             group_rank_id,
             roblox_rank_id,
             priority,
             template,
+            fallback_templates,
+            active_from,
+            active_until,
         })
     }
 }
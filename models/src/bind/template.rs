@@ -5,84 +5,288 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
-use crate::roblox::user::PartialUser as RobloxUser;
+use crate::id::UserId;
+use crate::roblox::{group::GroupUserRole, user::PartialUser as RobloxUser};
 use crate::user::RoGuildUser;
 
 lazy_static! {
     static ref TEMPLATE_REGEX: Regex = Regex::new(r"\{(.*?)\}").unwrap();
 }
 
+/// The longest nickname Discord allows, in characters
+pub const NICKNAME_LIMIT: usize = 32;
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Template(pub String);
 
+/// How the nickname of a member was picked when the template of their bind made it too long
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum NicknameFallback {
+    /// The fallback template at this index of the list of the bind
+    Template(usize),
+    /// The usernames in the template were shortened
+    Truncated,
+    /// Nothing fit, so the nickname is left as is. Holds the nickname of the template
+    TooLong(String),
+}
+
 impl Template {
+    /// The nickname of a member from this template. When the template makes it too long, the
+    /// fallback templates are tried in order, ending with the last of them with its usernames cut
+    /// short. The nickname of the template is returned along with [`NicknameFallback::TooLong`]
+    /// if nothing fits
     #[must_use]
-    pub fn nickname(
+    pub fn fitting_nickname(
         &self,
+        fallbacks: &[Template],
         roblox_user: &RobloxUser,
         user: &RoGuildUser,
         discord_username: &str,
-    ) -> String {
-        let roblox_id = user.roblox_id.to_string();
-        let discord_id = user.discord_id.to_string();
-        let display_name = roblox_user.display_name.clone().unwrap_or_default();
-
-        let template_str = &self.0;
-        let mut parts = vec![];
-
-        let mut matches = TEMPLATE_REGEX
-            .find_iter(template_str)
-            .map(|m| (m.start(), m.end()))
-            .peekable();
-        let first = match matches.peek() {
-            Some((start, _)) => *start,
-            None => return template_str.clone(),
-        };
-
-        if first > 0 {
-            parts.push(&template_str[0..first]);
+        group_roles: &[GroupUserRole],
+    ) -> (String, Option<NicknameFallback>) {
+        let member = Member::verified(roblox_user, user, discord_username, group_roles);
+        let nickname = self.render(&member, usize::MAX);
+        if nickname.chars().count() <= NICKNAME_LIMIT {
+            return (nickname, None);
         }
 
-        let mut previous_end = first;
-        for (start, end) in matches {
-            if previous_end != start {
-                parts.push(&template_str[previous_end..start]);
+        for (index, template) in fallbacks.iter().enumerate() {
+            let fallback = template.render(&member, usize::MAX);
+            if fallback.chars().count() <= NICKNAME_LIMIT {
+                return (fallback, Some(NicknameFallback::Template(index)));
             }
+        }
 
-            let arg = &template_str[start..end];
-            let arg_name = &arg[1..arg.len() - 1];
-            match arg_name {
-                "roblox-username" => parts.push(&roblox_user.name),
-                "roblox-id" => parts.push(&roblox_id),
-                "discord-id" => parts.push(&discord_id),
-                "discord-name" => parts.push(discord_username),
-                "display-name" => parts.push(&display_name),
-                _ => parts.push(arg),
-            }
+        let last = fallbacks.last().unwrap_or(self);
+        match last.truncated(&member, NICKNAME_LIMIT) {
+            Some(truncated) => (truncated, Some(NicknameFallback::Truncated)),
+            None => (nickname.clone(), Some(NicknameFallback::TooLong(nickname))),
+        }
+    }
 
-            previous_end = end;
+    /// The nickname of a member who hasn't verified. Only the Discord slugs are filled in, the
+    /// Roblox ones are left empty. The nickname is cut short if it doesn't fit
+    #[must_use]
+    pub fn unverified_nickname(&self, discord_id: UserId, discord_username: &str) -> String {
+        let member = Member {
+            discord_id,
+            discord_username,
+            roblox: None,
+        };
+        let nickname = self.render(&member, usize::MAX);
+        if nickname.chars().count() <= NICKNAME_LIMIT {
+            return nickname;
         }
+        self.truncated(&member, NICKNAME_LIMIT)
+            .unwrap_or_else(|| truncate(&nickname, NICKNAME_LIMIT).to_string())
+    }
 
-        if previous_end < template_str.len() {
-            parts.push(&template_str[previous_end..]);
+    fn truncated(&self, member: &Member, limit: usize) -> Option<String> {
+        let names = TEMPLATE_REGEX
+            .captures_iter(&self.0)
+            .filter_map(|c| Slug::parse(c.get(1).unwrap().as_str()))
+            .filter(|s| s.kind.is_name())
+            .count();
+        if names == 0 {
+            return None;
+        }
+        let rest = self.render(member, 0);
+        let room = limit.checked_sub(rest.chars().count())? / names;
+        if room == 0 {
+            return None;
         }
+        Some(self.render(member, room))
+    }
 
-        parts.join("")
+    /// Fills in the slugs of the template. The usernames are cut to `name_limit` characters.
+    /// Anything in braces that isn't a valid slug is left as it is
+    fn render(&self, member: &Member, name_limit: usize) -> String {
+        let template_str = &self.0;
+        let mut nickname = String::new();
+        let mut previous_end = 0;
+        for captures in TEMPLATE_REGEX.captures_iter(template_str) {
+            let arg = captures.get(0).unwrap();
+            nickname.push_str(&template_str[previous_end..arg.start()]);
+            match Slug::parse(captures.get(1).unwrap().as_str()) {
+                Some(slug) => nickname.push_str(&slug.render(member, name_limit)),
+                None => nickname.push_str(arg.as_str()),
+            }
+            previous_end = arg.end();
+        }
+        nickname.push_str(&template_str[previous_end..]);
+        nickname
     }
 
     #[must_use]
     pub fn has_slug(template_str: &str) -> bool {
-        let matches = TEMPLATE_REGEX.find_iter(template_str);
-        for m in matches {
-            let match_str = m.as_str();
-            let slug = &match_str[1..match_str.len() - 1];
-            match slug {
-                "roblox-username" | "roblox-id" | "discord-id" | "discord-name"
-                | "display-name" => return true,
-                _ => {}
+        TEMPLATE_REGEX
+            .captures_iter(template_str)
+            .any(|c| Slug::parse(c.get(1).unwrap().as_str()).is_some())
+    }
+
+    /// Whether the template uses any slug that needs the member to be verified
+    #[must_use]
+    pub fn has_roblox_slug(template_str: &str) -> bool {
+        TEMPLATE_REGEX
+            .captures_iter(template_str)
+            .filter_map(|c| Slug::parse(c.get(1).unwrap().as_str()))
+            .any(|s| s.kind.is_roblox())
+    }
+
+    /// Returns the first part of the template in braces that isn't a valid slug, modifiers included
+    #[must_use]
+    pub fn invalid_slug(template_str: &str) -> Option<&str> {
+        TEMPLATE_REGEX
+            .captures_iter(template_str)
+            .find(|c| Slug::parse(c.get(1).unwrap().as_str()).is_none())
+            .map(|c| c.get(0).unwrap().as_str())
+    }
+}
+
+/// The member the slugs of a template are filled in for
+struct Member<'a> {
+    discord_id: UserId,
+    discord_username: &'a str,
+    /// The Roblox account & group ranks of the member. `None` if they haven't verified
+    roblox: Option<(&'a RobloxUser, i64, &'a [GroupUserRole])>,
+}
+
+impl<'a> Member<'a> {
+    fn verified(
+        roblox_user: &'a RobloxUser,
+        user: &RoGuildUser,
+        discord_username: &'a str,
+        group_roles: &'a [GroupUserRole],
+    ) -> Self {
+        Self {
+            discord_id: user.discord_id,
+            discord_username,
+            roblox: Some((roblox_user, user.roblox_id, group_roles)),
+        }
+    }
+}
+
+/// A slug of a template along with its modifiers, like `{display-name|max:10}`
+struct Slug {
+    kind: SlugKind,
+    modifiers: Vec<Modifier>,
+}
+
+#[derive(Clone, Copy)]
+enum SlugKind {
+    RobloxUsername,
+    RobloxId,
+    DiscordId,
+    DiscordName,
+    DisplayName,
+    /// The display name, or the username if there is none
+    SmartName,
+    /// The name of the rank in the group of this id. `Guest` if the user isn't in the group
+    GroupRankName(u64),
+    /// The rank in the group of this id. `0` if the user isn't in the group
+    GroupRankId(u64),
+}
+
+enum Modifier {
+    Upper,
+    Lower,
+    /// Cuts the value down to this many characters
+    Max(usize),
+}
+
+impl Slug {
+    fn parse(slug: &str) -> Option<Self> {
+        let mut parts = slug.split('|');
+        let kind_str = parts.next()?;
+        let kind = match kind_str.split_once(':') {
+            Some(("group-rank-name", group_id)) => SlugKind::GroupRankName(group_id.parse().ok()?),
+            Some(("group-rank-id", group_id)) => SlugKind::GroupRankId(group_id.parse().ok()?),
+            Some(_) => return None,
+            None => match kind_str {
+                "roblox-username" => SlugKind::RobloxUsername,
+                "roblox-id" => SlugKind::RobloxId,
+                "discord-id" => SlugKind::DiscordId,
+                "discord-name" => SlugKind::DiscordName,
+                "display-name" => SlugKind::DisplayName,
+                "smart-name" => SlugKind::SmartName,
+                _ => return None,
+            },
+        };
+        let modifiers = parts
+            .map(|m| match m.split_once(':') {
+                Some(("max", limit)) => limit.parse().ok().map(Modifier::Max),
+                Some(_) => None,
+                None => match m {
+                    "upper" => Some(Modifier::Upper),
+                    "lower" => Some(Modifier::Lower),
+                    _ => None,
+                },
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(Self { kind, modifiers })
+    }
+
+    fn render(&self, member: &Member, name_limit: usize) -> String {
+        let mut value = match (self.kind, member.roblox) {
+            (SlugKind::DiscordId, _) => member.discord_id.to_string(),
+            (SlugKind::DiscordName, _) => member.discord_username.to_string(),
+            (_, None) => String::new(),
+            (SlugKind::RobloxUsername, Some((roblox_user, _, _))) => roblox_user.name.clone(),
+            (SlugKind::RobloxId, Some((_, roblox_id, _))) => roblox_id.to_string(),
+            (SlugKind::DisplayName, Some((roblox_user, _, _))) => {
+                roblox_user.display_name.clone().unwrap_or_default()
             }
+            (SlugKind::SmartName, Some((roblox_user, _, _))) => match &roblox_user.display_name {
+                Some(display_name) if !display_name.is_empty() => display_name.clone(),
+                _ => roblox_user.name.clone(),
+            },
+            (SlugKind::GroupRankName(group_id), Some((_, _, group_roles))) => group_roles
+                .iter()
+                .find(|r| r.group.id.0 == group_id)
+                .map_or_else(|| "Guest".into(), |r| r.role.name.clone()),
+            (SlugKind::GroupRankId(group_id), Some((_, _, group_roles))) => group_roles
+                .iter()
+                .find(|r| r.group.id.0 == group_id)
+                .map_or_else(|| "0".into(), |r| r.role.rank.to_string()),
+        };
+        for modifier in &self.modifiers {
+            value = match modifier {
+                Modifier::Upper => value.to_uppercase(),
+                Modifier::Lower => value.to_lowercase(),
+                Modifier::Max(limit) => truncate(&value, *limit).to_string(),
+            };
         }
-        false
+        if self.kind.is_name() {
+            truncate(&value, name_limit).to_string()
+        } else {
+            value
+        }
+    }
+}
+
+impl SlugKind {
+    /// Whether the slug is a username, which gets cut short to make a nickname fit
+    const fn is_name(self) -> bool {
+        matches!(
+            self,
+            SlugKind::RobloxUsername
+                | SlugKind::DiscordName
+                | SlugKind::DisplayName
+                | SlugKind::SmartName
+        )
+    }
+
+    /// Whether the slug needs the Roblox account of the member
+    const fn is_roblox(self) -> bool {
+        !matches!(self, SlugKind::DiscordId | SlugKind::DiscordName)
+    }
+}
+
+/// Cuts the string down to at most `limit` characters
+fn truncate(s: &str, limit: usize) -> &str {
+    match s.char_indices().nth(limit) {
+        Some((end, _)) => &s[..end],
+        None => s,
     }
 }
 
@@ -98,6 +302,21 @@ impl Display for Template {
     }
 }
 
+impl Display for NicknameFallback {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            NicknameFallback::Template(index) => {
+                write!(f, "Fallback template {} was used", index + 1)
+            }
+            NicknameFallback::Truncated => f.write_str("The usernames were shortened to fit"),
+            NicknameFallback::TooLong(nickname) => write!(
+                f,
+                "`{nickname}` is longer than {NICKNAME_LIMIT} characters, so it was left unchanged"
+            ),
+        }
+    }
+}
+
 impl ToSql for Template {
     fn to_sql(
         &self,
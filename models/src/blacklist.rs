@@ -1,6 +1,6 @@
 use bytes::BytesMut;
 use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
-use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde::{de::Error as DeError, Deserialize, Deserializer, Serialize, Serializer};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 use std::fmt::{Display, Formatter, Result as FmtResult};
 
@@ -38,6 +38,9 @@ struct BlacklistIntermediary {
     pub user_id: Option<i64>,
     pub group_id: Option<i64>,
     pub code: Option<String>,
+    /// The grammar version the code was written under. This is null for blacklists made before it
+    /// was recorded, which were written under version 0
+    pub grammar_version: Option<i32>,
 }
 
 impl Blacklist {
@@ -65,10 +68,10 @@ impl ToSql for Blacklist {
         ty: &Type,
         out: &mut BytesMut,
     ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
-        let (user_id, group_id, code) = match &self.data {
-            BlacklistData::User(u) => (Some(*u), None, None),
-            BlacklistData::Group(g) => (None, Some(*g), None),
-            BlacklistData::Custom(c) => (None, None, Some(c.code.clone())),
+        let (user_id, group_id, code, grammar_version) = match &self.data {
+            BlacklistData::User(u) => (Some(*u), None, None, None),
+            BlacklistData::Group(g) => (None, Some(*g), None, None),
+            BlacklistData::Custom(c) => (None, None, Some(c.code.clone()), Some(c.grammar_version)),
         };
         let intermediary = BlacklistIntermediary {
             blacklist_id: self.blacklist_id,
@@ -77,6 +80,7 @@ impl ToSql for Blacklist {
             user_id,
             group_id,
             code,
+            grammar_version,
         };
         BlacklistIntermediary::to_sql(&intermediary, ty, out)
     }
@@ -95,11 +99,25 @@ impl<'a> FromSql<'a> for Blacklist {
     ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
         let blacklist_intermediary = BlacklistIntermediary::from_sql(ty, raw)?;
         let data = match blacklist_intermediary.kind {
-            BlacklistType::User => BlacklistData::User(blacklist_intermediary.user_id.unwrap()),
-            BlacklistType::Group => BlacklistData::Group(blacklist_intermediary.group_id.unwrap()),
-            BlacklistType::Custom => BlacklistData::Custom(
-                RoCommand::new(&blacklist_intermediary.code.unwrap()).unwrap(),
+            BlacklistType::User => BlacklistData::User(
+                blacklist_intermediary
+                    .user_id
+                    .ok_or("User blacklist is missing the user id")?,
             ),
+            BlacklistType::Group => BlacklistData::Group(
+                blacklist_intermediary
+                    .group_id
+                    .ok_or("Group blacklist is missing the group id")?,
+            ),
+            BlacklistType::Custom => {
+                let code = blacklist_intermediary
+                    .code
+                    .ok_or("Custom blacklist is missing the code")?;
+                let mut command = RoCommand::parse(&code)?;
+                command.grammar_version =
+                    blacklist_intermediary.grammar_version.unwrap_or_default();
+                BlacklistData::Custom(command)
+            }
         };
         Ok(Blacklist {
             blacklist_id: blacklist_intermediary.blacklist_id,
@@ -149,7 +167,7 @@ impl<'a> FromSql<'a> for BlacklistType {
             0 => Ok(BlacklistType::User),
             1 => Ok(BlacklistType::Group),
             2 => Ok(BlacklistType::Custom),
-            _ => unreachable!(),
+            _ => Err(format!("Unknown blacklist type {blacklist_type}").into()),
         }
     }
 
@@ -162,10 +180,23 @@ impl<'de> Deserialize<'de> for Blacklist {
     fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
         let intermediary = BlacklistIntermediary::deserialize(deserializer)?;
         let data = match intermediary.kind {
-            BlacklistType::User => BlacklistData::User(intermediary.user_id.unwrap()),
-            BlacklistType::Group => BlacklistData::Group(intermediary.group_id.unwrap()),
+            BlacklistType::User => BlacklistData::User(
+                intermediary
+                    .user_id
+                    .ok_or_else(|| DeError::missing_field("user_id"))?,
+            ),
+            BlacklistType::Group => BlacklistData::Group(
+                intermediary
+                    .group_id
+                    .ok_or_else(|| DeError::missing_field("group_id"))?,
+            ),
             BlacklistType::Custom => {
-                BlacklistData::Custom(RoCommand::new(&intermediary.code.unwrap()).unwrap())
+                let code = intermediary
+                    .code
+                    .ok_or_else(|| DeError::missing_field("code"))?;
+                let mut command = RoCommand::parse(&code).map_err(DeError::custom)?;
+                command.grammar_version = intermediary.grammar_version.unwrap_or_default();
+                BlacklistData::Custom(command)
             }
         };
         Ok(Blacklist {
@@ -178,10 +209,10 @@ impl<'de> Deserialize<'de> for Blacklist {
 
 impl Serialize for Blacklist {
     fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
-        let (user_id, group_id, code) = match &self.data {
-            BlacklistData::User(u) => (Some(*u), None, None),
-            BlacklistData::Group(g) => (None, Some(*g), None),
-            BlacklistData::Custom(c) => (None, None, Some(c.code.clone())),
+        let (user_id, group_id, code, grammar_version) = match &self.data {
+            BlacklistData::User(u) => (Some(*u), None, None, None),
+            BlacklistData::Group(g) => (None, Some(*g), None, None),
+            BlacklistData::Custom(c) => (None, None, Some(c.source()), Some(c.grammar_version)),
         };
         let intermediary = BlacklistIntermediary {
             blacklist_id: self.blacklist_id,
@@ -190,6 +221,7 @@ impl Serialize for Blacklist {
             user_id,
             group_id,
             code,
+            grammar_version,
         };
         intermediary.serialize(serializer)
     }
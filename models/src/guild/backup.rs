@@ -2,7 +2,7 @@ use postgres_types::Json;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    bind::BindBackup,
+    bind::{BindBackup, Template},
     blacklist::Blacklist,
     id::{BackupId, UserId},
     FromRow,
@@ -27,6 +27,10 @@ pub struct GuildBackupData {
     pub blacklist_action: BlacklistActionType,
     pub update_on_join: bool,
     pub binds: Vec<BindBackup>,
+    #[serde(default)]
+    pub default_template: Option<Template>,
+    #[serde(default)]
+    pub unverified_template: Option<Template>,
 }
 
 impl FromRow for GuildBackup {
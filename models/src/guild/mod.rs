@@ -4,6 +4,7 @@ mod types;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    bind::Template,
     blacklist::Blacklist,
     id::{ChannelId, GuildId, RoleId, UserId},
     serialize_vec_as_string, FromRow,
@@ -54,6 +55,12 @@ pub struct RoGuild {
     pub nickname_bypass_roles: Vec<RoleId>,
 
     pub log_channel: Option<ChannelId>,
+
+    /// The template used for members no bind gives a nickname to. `None` uses the Roblox username
+    pub default_template: Option<Template>,
+
+    /// The template used for members who haven't verified. `None` leaves their nickname as is
+    pub unverified_template: Option<Template>,
 }
 
 impl RoGuild {
@@ -77,6 +84,8 @@ impl RoGuild {
             bypass_roles: Vec::new(),
             nickname_bypass_roles: Vec::new(),
             log_channel: None,
+            default_template: None,
+            unverified_template: None,
         }
     }
 }
@@ -100,6 +109,8 @@ impl FromRow for RoGuild {
         let bypass_roles = row.try_get("bypass_roles")?;
         let nickname_bypass_roles = row.try_get("nickname_bypass_roles")?;
         let log_channel = row.try_get("log_channel").ok();
+        let default_template = row.try_get("default_template")?;
+        let unverified_template = row.try_get("unverified_template")?;
 
         Ok(Self {
             guild_id,
@@ -119,6 +130,8 @@ impl FromRow for RoGuild {
             bypass_roles,
             nickname_bypass_roles,
             log_channel,
+            default_template,
+            unverified_template,
         })
     }
 }
@@ -93,7 +93,7 @@ impl<'a> FromSql<'a> for GuildType {
             0 => Ok(GuildType::Free),
             1 => Ok(GuildType::Alpha),
             2 => Ok(GuildType::Beta),
-            _ => unreachable!(),
+            _ => Err(format!("Unknown guild type {guild_type}").into()),
         }
     }
 
@@ -128,7 +128,7 @@ impl<'a> FromSql<'a> for BlacklistActionType {
             0 => Ok(BlacklistActionType::None),
             1 => Ok(BlacklistActionType::Kick),
             2 => Ok(BlacklistActionType::Ban),
-            _ => unreachable!(),
+            _ => Err(format!("Unknown blacklist action {bl_type}").into()),
         }
     }
 
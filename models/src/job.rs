@@ -0,0 +1,121 @@
+use bytes::BytesMut;
+use chrono::{DateTime, Utc};
+use postgres_types::{to_sql_checked, FromSql, IsNull, ToSql, Type};
+use serde_repr::{Deserialize_repr, Serialize_repr};
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::{
+    id::{ChannelId, GuildId, RoleId, UserId},
+    FromRow,
+};
+
+#[derive(Clone, Copy, Debug, Deserialize_repr, Eq, PartialEq, Serialize_repr)]
+#[repr(u8)]
+pub enum JobStatus {
+    Running = 0,
+    Completed = 1,
+    Cancelled = 2,
+    /// The job stopped because of an error. The reason is kept in [`UpdateJob::failure`]
+    Failed = 3,
+}
+
+/// A mass update (`update-all` or `update-role`) of a server
+#[derive(Clone, Debug)]
+pub struct UpdateJob {
+    pub job_id: i64,
+    pub guild_id: GuildId,
+    /// The channel the job was started from. The completion message is sent here
+    pub channel_id: ChannelId,
+    pub author_id: UserId,
+    /// Only members with this role are updated
+    pub role_filter: Option<RoleId>,
+    /// The Discord id of the last member processed. Members are processed in ascending order of
+    /// their ids, so a resumed job picks up right after this member
+    pub last_member_id: i64,
+    pub processed: i64,
+    pub total: i64,
+    pub status: JobStatus,
+    /// Why the job failed, if it did
+    pub failure: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Display for JobStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            JobStatus::Running => f.write_str("Running"),
+            JobStatus::Completed => f.write_str("Completed"),
+            JobStatus::Cancelled => f.write_str("Cancelled"),
+            JobStatus::Failed => f.write_str("Failed"),
+        }
+    }
+}
+
+impl ToSql for JobStatus {
+    fn to_sql(
+        &self,
+        ty: &Type,
+        out: &mut BytesMut,
+    ) -> Result<IsNull, Box<dyn std::error::Error + Sync + Send>> {
+        i32::to_sql(&(*self as i32), ty, out)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <i32 as ToSql>::accepts(ty)
+    }
+
+    to_sql_checked!();
+}
+
+impl<'a> FromSql<'a> for JobStatus {
+    fn from_sql(
+        ty: &Type,
+        raw: &'a [u8],
+    ) -> Result<Self, Box<dyn std::error::Error + Sync + Send>> {
+        let status = i32::from_sql(ty, raw)?;
+        match status {
+            0 => Ok(JobStatus::Running),
+            1 => Ok(JobStatus::Completed),
+            2 => Ok(JobStatus::Cancelled),
+            3 => Ok(JobStatus::Failed),
+            _ => Err(format!("Unknown job status {status}").into()),
+        }
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <i32 as FromSql>::accepts(ty)
+    }
+}
+
+impl FromRow for UpdateJob {
+    fn from_row(row: tokio_postgres::Row) -> Result<Self, tokio_postgres::Error> {
+        let job_id = row.try_get("job_id")?;
+        let guild_id = row.try_get("guild_id")?;
+        let channel_id = row.try_get("channel_id")?;
+        let author_id = row.try_get("author_id")?;
+        let role_filter = row.try_get("role_filter")?;
+        let last_member_id = row.try_get("last_member_id")?;
+        let processed = row.try_get("processed")?;
+        let total = row.try_get("total")?;
+        let status = row.try_get("status")?;
+        let failure = row.try_get("failure")?;
+        let started_at = row.try_get("started_at")?;
+        let updated_at = row.try_get("updated_at")?;
+
+        Ok(Self {
+            job_id,
+            guild_id,
+            channel_id,
+            author_id,
+            role_filter,
+            last_member_id,
+            processed,
+            total,
+            status,
+            failure,
+            started_at,
+            updated_at,
+        })
+    }
+}
@@ -20,6 +20,7 @@ pub mod blacklist;
 pub mod events;
 pub mod guild;
 pub mod id;
+pub mod job;
 pub mod roblox;
 pub mod rolang;
 pub mod stats;
@@ -0,0 +1,187 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use super::{
+    diagnostic::Diagnostic,
+    expression::Expression,
+    token::{Literal, Token, TokenType},
+};
+
+/// The type of a value produced by an expression
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ValueType {
+    Bool,
+    Number,
+    String,
+    List,
+}
+
+impl Literal {
+    #[must_use]
+    pub const fn value_type(&self) -> ValueType {
+        match self {
+            Literal::Bool(_) => ValueType::Bool,
+            Literal::Number(_) => ValueType::Number,
+            Literal::String(_) => ValueType::String,
+            Literal::List(_) => ValueType::List,
+        }
+    }
+}
+
+impl Expression {
+    /// Infers the type of the expression, rejecting any operator that is applied to operands of the wrong type
+    pub fn check(&self) -> Result<ValueType, Diagnostic> {
+        match self {
+            Expression::Literal(l) => Ok(l.value_type()),
+            Expression::Grouping(e) | Expression::Macro(_, Some(e)) => e.check(),
+            Expression::Macro(token, None) => Err(Diagnostic::new(
+                format!("Unknown function `{}`", token.lexeme),
+                token.span,
+            )),
+            Expression::Quantifier(quantifier, e) => {
+                expect(e, ValueType::Bool, quantifier, "argument")?;
+                Ok(ValueType::Bool)
+            }
+            Expression::Unary(operator, right) => {
+                let expected = if operator.token_type == TokenType::Minus {
+                    ValueType::Number
+                } else {
+                    ValueType::Bool
+                };
+                expect(right, expected, operator, "operand")?;
+                Ok(expected)
+            }
+            Expression::Binary(left, operator, right) => match operator.token_type {
+                TokenType::And | TokenType::Or => {
+                    expect(left, ValueType::Bool, operator, "left side")?;
+                    expect(right, ValueType::Bool, operator, "right side")?;
+                    Ok(ValueType::Bool)
+                }
+                TokenType::Greater
+                | TokenType::GreaterEqual
+                | TokenType::Less
+                | TokenType::LessEqual => {
+                    expect(left, ValueType::Number, operator, "left side")?;
+                    expect(right, ValueType::Number, operator, "right side")?;
+                    Ok(ValueType::Bool)
+                }
+                TokenType::In => {
+                    let item = left.check()?;
+                    expect(right, ValueType::List, operator, "right side")?;
+                    let mut inner = right.as_ref();
+                    while let Expression::Grouping(e) = inner {
+                        inner = e;
+                    }
+                    if let Expression::Literal(Literal::List(items)) = inner {
+                        if let Some(other) = items.iter().find(|i| i.value_type() != item) {
+                            return Err(Diagnostic::new(
+                                format!(
+                                    "Cannot look for a {item} in a list of {}s",
+                                    other.value_type()
+                                ),
+                                operator.span,
+                            ));
+                        }
+                    }
+                    Ok(ValueType::Bool)
+                }
+                TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Star
+                | TokenType::Slash
+                | TokenType::Percent => {
+                    expect(left, ValueType::Number, operator, "left side")?;
+                    expect(right, ValueType::Number, operator, "right side")?;
+                    Ok(ValueType::Number)
+                }
+                TokenType::EqualEqual | TokenType::BangEqual => {
+                    let left = left.check()?;
+                    let right = right.check()?;
+                    if left != right {
+                        return Err(Diagnostic::new(
+                            format!(
+                                "Cannot compare a {left} with a {right} using `{}`",
+                                operator.lexeme
+                            ),
+                            operator.span,
+                        ));
+                    }
+                    Ok(ValueType::Bool)
+                }
+                _ => Err(Diagnostic::new(
+                    format!("Unknown operator `{}`", operator.lexeme),
+                    operator.span,
+                )),
+            },
+            Expression::Function(token, _) => match token.token_type {
+                TokenType::GetRank | TokenType::AccountAgeDays => Ok(ValueType::Number),
+                TokenType::DisplayName => Ok(ValueType::String),
+                TokenType::HasRank
+                | TokenType::IsInGroup
+                | TokenType::HasRole
+                | TokenType::WithString
+                | TokenType::HasAsset
+                | TokenType::HasBadge
+                | TokenType::HasGamepass
+                | TokenType::IsBanned
+                | TokenType::IsInAnyGroup
+                | TokenType::HasAnyRole
+                | TokenType::HasAllRoles
+                | TokenType::UsernameEquals
+                | TokenType::UsernameStartsWith
+                | TokenType::UsernameMatches
+                | TokenType::NameEquals
+                | TokenType::NameStartsWith
+                | TokenType::NameMatches => Ok(ValueType::Bool),
+                _ => Err(Diagnostic::new(
+                    format!("Unknown function `{}`", token.lexeme),
+                    token.span,
+                )),
+            },
+        }
+    }
+}
+
+fn expect(
+    operand: &Expression,
+    expected: ValueType,
+    operator: &Token,
+    side: &str,
+) -> Result<(), Diagnostic> {
+    let found = operand.check()?;
+    if found == expected {
+        return Ok(());
+    }
+
+    let mut inner = operand;
+    while let Expression::Grouping(e) = inner {
+        inner = e;
+    }
+    let diagnostic = match inner {
+        Expression::Function(function, _) => Diagnostic::new(
+            format!(
+                "Expected the {side} of `{}` to be a {expected} but `{}` returns a {found}",
+                operator.lexeme, function.lexeme
+            ),
+            function.span,
+        ),
+        _ => Diagnostic::new(
+            format!(
+                "Expected the {side} of `{}` to be a {expected} but found a {found}",
+                operator.lexeme
+            ),
+            operator.span,
+        ),
+    };
+    Err(diagnostic)
+}
+
+impl Display for ValueType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            ValueType::Bool => f.write_str("Bool"),
+            ValueType::Number => f.write_str("Number"),
+            ValueType::String => f.write_str("String"),
+            ValueType::List => f.write_str("List"),
+        }
+    }
+}
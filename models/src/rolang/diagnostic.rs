@@ -0,0 +1,87 @@
+use std::{
+    error::Error as StdError,
+    fmt::{Display, Formatter, Result as FmtResult},
+};
+
+use super::{parser::ParseError, token::Span};
+
+/// The maximum number of characters of the offending line shown when rendering a diagnostic
+const RENDER_WIDTH: usize = 60;
+
+/// An error in a piece of `RoLang` code along with the location it occurred at
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+
+    /// Renders the line of `code` the diagnostic points to inside a code block with carets
+    /// under the offending span. Long lines are trimmed to a window around the span.
+    #[must_use]
+    pub fn render(&self, code: &str) -> String {
+        let line = code
+            .lines()
+            .nth(self.span.line.saturating_sub(1))
+            .unwrap_or_default()
+            .chars()
+            .map(|c| if c.is_whitespace() { ' ' } else { c })
+            .collect::<Vec<_>>();
+
+        let start = self.span.column.saturating_sub(1).min(line.len());
+        let window_start = if line.len() > RENDER_WIDTH {
+            start
+                .saturating_sub(RENDER_WIDTH / 2)
+                .min(line.len() - RENDER_WIDTH)
+        } else {
+            0
+        };
+        let window_end = (window_start + RENDER_WIDTH).min(line.len());
+
+        let prefix = if window_start > 0 { "..." } else { "" };
+        let suffix = if window_end < line.len() { "..." } else { "" };
+        let snippet = line[window_start..window_end].iter().collect::<String>();
+
+        let padding = " ".repeat(prefix.len() + start - window_start);
+        let width = self
+            .span
+            .end
+            .saturating_sub(self.span.start)
+            .min(window_end.saturating_sub(start))
+            .max(1);
+        let carets = "^".repeat(width);
+
+        format!(
+            "```\n{}{}{}\n{}{} {}\n```",
+            prefix, snippet, suffix, padding, carets, self.message
+        )
+    }
+}
+
+impl From<ParseError> for Diagnostic {
+    fn from(err: ParseError) -> Self {
+        Self {
+            message: err.1,
+            span: err.0.span,
+        }
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.span.line, self.span.column
+        )
+    }
+}
+
+impl StdError for Diagnostic {}
@@ -0,0 +1,198 @@
+use std::fmt::Write;
+
+use super::{
+    expression::{binary, unary, Expression},
+    token::{Literal, TokenType},
+    RoCommandUser,
+};
+
+/// The value an expression evaluated to along with the values of its sub-expressions
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Explanation {
+    /// The code of the expression
+    pub code: String,
+    /// The result of evaluating the expression. This is `None` if the expression was skipped
+    /// because the left side of an `and`/`or` had already decided the outcome
+    pub value: Option<Result<Literal, String>>,
+    pub children: Vec<Explanation>,
+}
+
+impl Expression {
+    /// Evaluates the expression & every sub-expression that is reached during evaluation. Each
+    /// node is evaluated once, with its value worked out from the values recorded for its children
+    pub fn explain(&self, user: &RoCommandUser) -> Explanation {
+        let (value, children) = match self {
+            Expression::Grouping(e) => return e.explain(user),
+            Expression::Binary(left, operator, right) => {
+                let left = left.explain(user);
+                let (value, right) = match (&left.value, operator.token_type) {
+                    (Some(Ok(value)), TokenType::And | TokenType::Or) => {
+                        let value = bool::from(value.clone());
+                        // The left side decides the outcome when it's false for `and` or true for `or`
+                        if value == (operator.token_type == TokenType::Or) {
+                            (Ok(Literal::Bool(value)), right.skipped())
+                        } else {
+                            let right = right.explain(user);
+                            let value = result(&right).map(|v| Literal::Bool(bool::from(v)));
+                            (value, right)
+                        }
+                    }
+                    (Some(Ok(value)), _) => {
+                        let right = right.explain(user);
+                        let value =
+                            result(&right).and_then(|r| binary(operator.token_type, value, &r));
+                        (value, right)
+                    }
+                    _ => (result(&left), right.skipped()),
+                };
+                (value, vec![left, right])
+            }
+            Expression::Unary(operator, e) => {
+                let child = e.explain(user);
+                let value = result(&child).and_then(|v| unary(operator.token_type, v));
+                (value, vec![child])
+            }
+            Expression::Macro(_, Some(e)) => {
+                let child = e.explain(user);
+                (result(&child), vec![child])
+            }
+            Expression::Quantifier(_, e) if user.accounts.is_empty() => {
+                let child = e.explain(user);
+                (result(&child), vec![child])
+            }
+            Expression::Quantifier(quantifier, e) => {
+                let mut accounts = user.accounts.iter().collect::<Vec<_>>();
+                accounts.sort_by_key(|(id, _)| **id);
+                let children = accounts
+                    .into_iter()
+                    .map(|(id, ranks)| {
+                        let mut explanation = e.explain(&RoCommandUser { ranks, ..*user });
+                        explanation.code = format!("Account {id}: {}", explanation.code);
+                        explanation
+                    })
+                    .collect::<Vec<_>>();
+                let mut results = children.iter().map(|c| result(c).map(bool::from));
+                let value = if quantifier.token_type == TokenType::AnyAccount {
+                    results.try_fold(false, |any, res| res.map(|r| any || r))
+                } else {
+                    results.try_fold(true, |all, res| res.map(|r| all && r))
+                };
+                (value.map(Literal::Bool), children)
+            }
+            Expression::Literal(_) | Expression::Function(_, _) | Expression::Macro(_, None) => {
+                (self.evaluate(user), Vec::new())
+            }
+        };
+
+        Explanation {
+            code: self.to_string(),
+            value: Some(value),
+            children,
+        }
+    }
+
+    fn skipped(&self) -> Explanation {
+        Explanation {
+            code: self.to_string(),
+            value: None,
+            children: Vec::new(),
+        }
+    }
+}
+
+/// The recorded result of an explanation that was evaluated
+fn result(explanation: &Explanation) -> Result<Literal, String> {
+    explanation
+        .value
+        .clone()
+        .unwrap_or_else(|| Err("Expression was skipped".into()))
+}
+
+impl Explanation {
+    /// Renders the explanation as a tree with the value of every node next to its code
+    #[must_use]
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        self.render_node(&mut out, "", "");
+        out
+    }
+
+    fn render_node(&self, out: &mut String, prefix: &str, child_prefix: &str) {
+        let value = match &self.value {
+            Some(Ok(value)) => value.to_string(),
+            Some(Err(err)) => format!("error: {err}"),
+            None => "skipped".into(),
+        };
+        if value == self.code {
+            let _ = writeln!(out, "{prefix}{}", self.code);
+        } else {
+            let _ = writeln!(out, "{prefix}{} => {value}", self.code);
+        }
+
+        for (i, child) in self.children.iter().enumerate() {
+            let (branch, indent) = if i + 1 == self.children.len() {
+                ("└─ ", "   ")
+            } else {
+                ("├─ ", "│  ")
+            };
+            child.render_node(
+                out,
+                &format!("{child_prefix}{branch}"),
+                &format!("{child_prefix}{indent}"),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{HashMap, HashSet};
+
+    use crate::{
+        id::{GuildId, RoleId, UserId},
+        rolang::{Macros, RoCommand, RoCommandUser},
+        user::RoGuildUser,
+    };
+
+    #[test]
+    fn explain_agrees_with_evaluate() {
+        let user = RoGuildUser {
+            guild_id: GuildId::new(1),
+            discord_id: UserId::new(2),
+            roblox_id: 3,
+        };
+        let ranks = HashMap::from([(1000, 10), (2000, 255)]);
+        let accounts = HashMap::from([(3, ranks.clone()), (4, HashMap::from([(1000, 50)]))]);
+        let roles = [RoleId::new(5)];
+        let owned_assets = HashSet::new();
+        let command_user = RoCommandUser {
+            user: &user,
+            roles: &roles,
+            ranks: &ranks,
+            username: "builderman",
+            owned_assets: &owned_assets,
+            profile: None,
+            accounts: &accounts,
+        };
+
+        let codes = [
+            "HasRank(1000, 10) and IsInGroup(2000)",
+            "IsInGroup(3000) and HasRole(5)",
+            "IsInGroup(1000) or 1 / 0 == 1",
+            "IsInGroup(3000) or 1 / 0 == 1",
+            "not (GetRank(1000) * 2 - 5 > GetRank(2000) % 100)",
+            "-GetRank(2000) < 0 and GetRank(1000) in [5, 10]",
+            "AnyAccount(GetRank(1000) >= 50) and not AllAccounts(GetRank(1000) >= 50)",
+            "AccountAgeDays() > 5 or true",
+        ];
+        for code in codes {
+            let command = RoCommand::new(code, &Macros::default()).unwrap();
+            let explanation = command.expr.explain(&command_user);
+            assert_eq!(
+                explanation.value,
+                Some(command.expr.evaluate(&command_user)),
+                "{code}"
+            );
+        }
+    }
+}
@@ -1,4 +1,11 @@
-use crate::id::RoleId;
+use chrono::{DateTime, Utc};
+use regex::{Error as RegexError, Regex, RegexBuilder};
+use std::{
+    collections::HashMap,
+    sync::{LazyLock, Mutex, PoisonError},
+};
+
+use crate::bind::AssetType;
 
 use super::{
     parser::ParseError,
@@ -6,6 +13,15 @@ use super::{
     RoCommandUser,
 };
 
+/// The maximum size in bytes of a compiled `UsernameMatches` or `NameMatches` pattern
+const MAX_PATTERN_SIZE: usize = 1 << 16;
+
+/// The maximum number of compiled patterns kept around between evaluations
+const PATTERN_CACHE_SIZE: usize = 256;
+
+static PATTERNS: LazyLock<Mutex<HashMap<(String, bool), Regex>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Expression {
     Binary(Box<Expression>, Token, Box<Expression>),
@@ -13,64 +29,90 @@ pub enum Expression {
     Literal(Literal),
     Grouping(Box<Expression>),
     Function(Token, Vec<Literal>),
+    /// A call to a guild macro. The body is filled in once the macro has been resolved
+    Macro(Token, Option<Box<Expression>>),
+    /// `AnyAccount` or `AllAccounts`, which evaluate the expression against the ranks of every
+    /// Roblox account linked to the user
+    Quantifier(Token, Box<Expression>),
 }
 
 impl Expression {
     pub fn evaluate(&self, user: &RoCommandUser) -> Result<Literal, String> {
         match self {
             Expression::Literal(l) => return Ok(l.clone()),
-            Expression::Unary(t, e) => {
-                let flip = t.token_type == TokenType::Not || t.token_type == TokenType::Bang;
-                let res = flip ^ e.evaluate(user)?;
-                return Ok(Literal::Bool(res));
-            }
+            Expression::Unary(t, e) => return unary(t.token_type, e.evaluate(user)?),
             Expression::Binary(left, oper, right) => {
-                let left = left.evaluate(user)?;
-                let right = right.evaluate(user)?;
                 match oper.token_type {
-                    TokenType::And => return Ok(Literal::Bool(left.into() && right.into())),
-                    TokenType::Or => return Ok(Literal::Bool(left.into() || right.into())),
-                    TokenType::Greater => return Ok(Literal::Bool(left > right)),
-                    TokenType::GreaterEqual => return Ok(Literal::Bool(left >= right)),
-                    TokenType::Less => return Ok(Literal::Bool(left < right)),
-                    TokenType::LessEqual => return Ok(Literal::Bool(left <= right)),
-                    TokenType::EqualEqual => return Ok(Literal::Bool(left == right)),
-                    TokenType::BangEqual => return Ok(Literal::Bool(left != right)),
-                    _ => return Err("Invalid Operator".to_string()),
+                    TokenType::And => {
+                        let success =
+                            bool::from(left.evaluate(user)?) && bool::from(right.evaluate(user)?);
+                        return Ok(Literal::Bool(success));
+                    }
+                    TokenType::Or => {
+                        let success =
+                            bool::from(left.evaluate(user)?) || bool::from(right.evaluate(user)?);
+                        return Ok(Literal::Bool(success));
+                    }
+                    _ => {}
                 }
+                let left = left.evaluate(user)?;
+                let right = right.evaluate(user)?;
+                return binary(oper.token_type, &left, &right);
             }
             Expression::Grouping(e) => return e.evaluate(user),
+            Expression::Macro(token, body) => {
+                return match body {
+                    Some(body) => body.evaluate(user),
+                    None => Err(format!("Unknown function `{}`", token.lexeme)),
+                }
+            }
+            Expression::Quantifier(token, e) => {
+                if user.accounts.is_empty() {
+                    return e.evaluate(user);
+                }
+                let mut results = user.accounts.values().map(|ranks| {
+                    let account = RoCommandUser { ranks, ..*user };
+                    e.evaluate(&account).map(bool::from)
+                });
+                let success = if token.token_type == TokenType::AnyAccount {
+                    results.try_fold(false, |any, res| res.map(|r| any || r))?
+                } else {
+                    results.try_fold(true, |all, res| res.map(|r| all && r))?
+                };
+                return Ok(Literal::Bool(success));
+            }
             Expression::Function(token, args) => match token.token_type {
                 TokenType::HasRank => {
-                    if let Literal::Number(num) = &args[0] {
+                    if let Some(Literal::Number(num)) = args.first() {
                         let success = match user.ranks.get(num) {
-                            Some(rank) => *rank == args[1],
+                            Some(rank) => {
+                                matches!(args.get(1), Some(Literal::Number(r)) if r == rank)
+                            }
                             None => false,
                         };
                         return Ok(Literal::Bool(success));
                     }
                 }
                 TokenType::IsInGroup => {
-                    if let Literal::Number(num) = &args[0] {
+                    if let Some(Literal::Number(num)) = args.first() {
                         let success = user.ranks.contains_key(num);
                         return Ok(Literal::Bool(success));
                     }
                 }
                 TokenType::HasRole => {
-                    if let Literal::Number(num) = &args[0] {
-                        let id = RoleId::new(*num as u64);
-                        let success = user.roles.contains(&id);
+                    if let Some(Literal::Number(num)) = args.first() {
+                        let success = has_role(user, *num);
                         return Ok(Literal::Bool(success));
                     }
                 }
                 TokenType::WithString => {
-                    if let Literal::String(name) = &args[0] {
+                    if let Some(Literal::String(name)) = args.first() {
                         let success = user.username.contains(name);
                         return Ok(Literal::Bool(success));
                     }
                 }
                 TokenType::GetRank => {
-                    if let Literal::Number(num) = &args[0] {
+                    if let Some(Literal::Number(num)) = args.first() {
                         let rank = match user.ranks.get(num) {
                             Some(rank) => rank.to_owned(),
                             None => 0,
@@ -78,6 +120,71 @@ impl Expression {
                         return Ok(Literal::Number(rank));
                     }
                 }
+                TokenType::HasAsset | TokenType::HasBadge | TokenType::HasGamepass => {
+                    if let (Some(asset_type), Some(Literal::Number(num))) =
+                        (asset_type(token.token_type), args.first())
+                    {
+                        let success = user.owned_assets.contains(&(asset_type, *num));
+                        return Ok(Literal::Bool(success));
+                    }
+                }
+                TokenType::IsInAnyGroup => {
+                    if let Some(Literal::List(groups)) = args.first() {
+                        let success = groups.iter().any(|group| match group {
+                            Literal::Number(num) => user.ranks.contains_key(num),
+                            _ => false,
+                        });
+                        return Ok(Literal::Bool(success));
+                    }
+                }
+                TokenType::HasAnyRole | TokenType::HasAllRoles => {
+                    if let Some(Literal::List(roles)) = args.first() {
+                        let mut has_roles = roles.iter().map(|role| match role {
+                            Literal::Number(num) => has_role(user, *num),
+                            _ => false,
+                        });
+                        let success = if token.token_type == TokenType::HasAnyRole {
+                            has_roles.any(|has_role| has_role)
+                        } else {
+                            has_roles.all(|has_role| has_role)
+                        };
+                        return Ok(Literal::Bool(success));
+                    }
+                }
+                TokenType::AccountAgeDays => {
+                    let profile = user.profile.ok_or("Roblox profile was not loaded")?;
+                    let created = DateTime::parse_from_rfc3339(&profile.created)
+                        .map_err(|_| "Invalid account creation date".to_string())?;
+                    let age = Utc::now().signed_duration_since(created).num_days();
+                    return Ok(Literal::Number(age));
+                }
+                TokenType::IsBanned => {
+                    let profile = user.profile.ok_or("Roblox profile was not loaded")?;
+                    return Ok(Literal::Bool(profile.is_banned));
+                }
+                TokenType::DisplayName => {
+                    let profile = user.profile.ok_or("Roblox profile was not loaded")?;
+                    let display_name = profile.display_name.as_ref().unwrap_or(&profile.name);
+                    return Ok(Literal::String(display_name.clone()));
+                }
+                TokenType::UsernameEquals
+                | TokenType::UsernameStartsWith
+                | TokenType::UsernameMatches => {
+                    if let Some(Literal::String(arg)) = args.first() {
+                        let success = name_matches(token.token_type, user.username, arg)?;
+                        return Ok(Literal::Bool(success));
+                    }
+                }
+                TokenType::NameEquals | TokenType::NameStartsWith | TokenType::NameMatches => {
+                    if let Some(Literal::String(arg)) = args.first() {
+                        let profile = user.profile.ok_or("Roblox profile was not loaded")?;
+                        let mut success = name_matches(token.token_type, user.username, arg)?;
+                        if let Some(display_name) = &profile.display_name {
+                            success = success || name_matches(token.token_type, display_name, arg)?;
+                        }
+                        return Ok(Literal::Bool(success));
+                    }
+                }
                 _ => return Err("Invalid Function".to_string()),
             },
         }
@@ -164,9 +271,296 @@ impl Expression {
                         }
                     }
                 }
+                TokenType::HasAsset | TokenType::HasBadge | TokenType::HasGamepass => {
+                    if args.len() != 1 {
+                        return Err(ParseError(
+                            token.clone(),
+                            "Expected 1 argument. {Asset Id}".to_string(),
+                        ));
+                    }
+                    match args.first() {
+                        Some(Literal::Number(_)) => {}
+                        _ => {
+                            return Err(ParseError(
+                                token.clone(),
+                                "Expected Asset Id to be an integer".to_string(),
+                            ))
+                        }
+                    }
+                }
+                TokenType::IsInAnyGroup | TokenType::HasAnyRole | TokenType::HasAllRoles => {
+                    let (name, description) = if token.token_type == TokenType::IsInAnyGroup {
+                        ("Group Ids", "Group Id")
+                    } else {
+                        ("Role Ids", "Role Id")
+                    };
+                    if args.len() != 1 {
+                        return Err(ParseError(
+                            token.clone(),
+                            format!("Expected 1 argument. [{{{name}}}]"),
+                        ));
+                    }
+                    match args.first() {
+                        Some(Literal::List(items))
+                            if items.iter().all(|i| matches!(i, Literal::Number(_))) => {}
+                        _ => {
+                            return Err(ParseError(
+                                token.clone(),
+                                format!(
+                                "Expected {name} to be a list of integers. [{description}, ...]"
+                            ),
+                            ))
+                        }
+                    }
+                }
+                TokenType::UsernameEquals
+                | TokenType::UsernameStartsWith
+                | TokenType::UsernameMatches
+                | TokenType::NameEquals
+                | TokenType::NameStartsWith
+                | TokenType::NameMatches => {
+                    let is_pattern = matches!(
+                        token.token_type,
+                        TokenType::UsernameMatches | TokenType::NameMatches
+                    );
+                    let name = if is_pattern { "Pattern" } else { "Name" };
+                    if args.len() != 1 {
+                        return Err(ParseError(
+                            token.clone(),
+                            format!("Expected 1 argument. {{{name}}}"),
+                        ));
+                    }
+                    match args.first() {
+                        Some(Literal::String(arg)) => {
+                            if is_pattern {
+                                let case_insensitive = token.token_type == TokenType::NameMatches;
+                                pattern(arg, case_insensitive)
+                                    .map_err(|err| ParseError(token.clone(), err))?;
+                            }
+                        }
+                        _ => {
+                            return Err(ParseError(
+                                token.clone(),
+                                format!("Expected {name} to be a word"),
+                            ))
+                        }
+                    }
+                }
+                TokenType::AccountAgeDays | TokenType::IsBanned | TokenType::DisplayName => {
+                    if !args.is_empty() {
+                        return Err(ParseError(
+                            token.clone(),
+                            "Expected no arguments".to_string(),
+                        ));
+                    }
+                }
                 _ => return Err(ParseError(token.clone(), "Unknown function".to_string())),
             }
         }
         Ok(())
     }
+
+    /// The number of levels in the expression tree. The tree is walked without recursion so this
+    /// is safe to call on an expression of any depth
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((expr, depth)) = stack.pop() {
+            max = max.max(depth);
+            match expr {
+                Expression::Binary(left, _, right) => {
+                    stack.push((left, depth + 1));
+                    stack.push((right, depth + 1));
+                }
+                Expression::Unary(_, e)
+                | Expression::Grouping(e)
+                | Expression::Macro(_, Some(e))
+                | Expression::Quantifier(_, e) => stack.push((e, depth + 1)),
+                Expression::Literal(_)
+                | Expression::Function(_, _)
+                | Expression::Macro(_, None) => {}
+            }
+        }
+        max
+    }
+
+    /// Whether the expression calls a function that needs the Roblox profile of the user
+    pub fn uses_profile(&self) -> bool {
+        match self {
+            Expression::Binary(left, _, right) => left.uses_profile() || right.uses_profile(),
+            Expression::Unary(_, e)
+            | Expression::Grouping(e)
+            | Expression::Macro(_, Some(e))
+            | Expression::Quantifier(_, e) => e.uses_profile(),
+            Expression::Literal(_) | Expression::Macro(_, None) => false,
+            Expression::Function(token, _) => matches!(
+                token.token_type,
+                TokenType::AccountAgeDays
+                    | TokenType::IsBanned
+                    | TokenType::DisplayName
+                    | TokenType::NameEquals
+                    | TokenType::NameStartsWith
+                    | TokenType::NameMatches
+            ),
+        }
+    }
+
+    /// Whether the expression needs the ranks of every account linked to the user
+    pub fn uses_accounts(&self) -> bool {
+        match self {
+            Expression::Binary(left, _, right) => left.uses_accounts() || right.uses_accounts(),
+            Expression::Unary(_, e) | Expression::Grouping(e) | Expression::Macro(_, Some(e)) => {
+                e.uses_accounts()
+            }
+            Expression::Literal(_) | Expression::Function(_, _) | Expression::Macro(_, None) => {
+                false
+            }
+            Expression::Quantifier(_, _) => true,
+        }
+    }
+
+    /// Collects the assets whose ownership is checked by the expression
+    pub fn assets(&self, assets: &mut Vec<(AssetType, i64)>) {
+        match self {
+            Expression::Binary(left, _, right) => {
+                left.assets(assets);
+                right.assets(assets);
+            }
+            Expression::Unary(_, e)
+            | Expression::Grouping(e)
+            | Expression::Macro(_, Some(e))
+            | Expression::Quantifier(_, e) => e.assets(assets),
+            Expression::Literal(_) | Expression::Macro(_, None) => {}
+            Expression::Function(token, args) => {
+                if let (Some(asset_type), Some(Literal::Number(id))) =
+                    (asset_type(token.token_type), args.first())
+                {
+                    assets.push((asset_type, *id));
+                }
+            }
+        }
+    }
+}
+
+/// Role ids are compared as integers since ids that are zero or negative cannot be turned into a
+/// [`RoleId`](crate::id::RoleId)
+/// Applies a unary operator to the value of its operand
+pub(super) fn unary(operator: TokenType, value: Literal) -> Result<Literal, String> {
+    if operator == TokenType::Minus {
+        return match value {
+            Literal::Number(n) => n
+                .checked_neg()
+                .map(Literal::Number)
+                .ok_or_else(|| "Integer overflow".to_string()),
+            _ => Err("Expected a number after -".to_string()),
+        };
+    }
+    let flip = operator == TokenType::Not || operator == TokenType::Bang;
+    Ok(Literal::Bool(flip ^ value))
+}
+
+/// Applies a binary operator other than `and`/`or` to the values of its operands
+pub(super) fn binary(
+    operator: TokenType,
+    left: &Literal,
+    right: &Literal,
+) -> Result<Literal, String> {
+    match operator {
+        TokenType::Greater => Ok(Literal::Bool(left > right)),
+        TokenType::GreaterEqual => Ok(Literal::Bool(left >= right)),
+        TokenType::Less => Ok(Literal::Bool(left < right)),
+        TokenType::LessEqual => Ok(Literal::Bool(left <= right)),
+        TokenType::EqualEqual => Ok(Literal::Bool(left == right)),
+        TokenType::BangEqual => Ok(Literal::Bool(left != right)),
+        TokenType::In => {
+            if let Literal::List(items) = right {
+                return Ok(Literal::Bool(items.contains(left)));
+            }
+            Err("Expected a list after in".to_string())
+        }
+        TokenType::Plus
+        | TokenType::Minus
+        | TokenType::Star
+        | TokenType::Slash
+        | TokenType::Percent => arithmetic(operator, left, right),
+        _ => Err("Invalid Operator".to_string()),
+    }
+}
+
+fn has_role(user: &RoCommandUser, id: i64) -> bool {
+    user.roles.iter().any(|role| role.get() as i64 == id)
+}
+
+/// Checks a name against the argument of one of the name matching functions. The `Name*`
+/// functions ignore case
+fn name_matches(function: TokenType, name: &str, arg: &str) -> Result<bool, String> {
+    let success = match function {
+        TokenType::UsernameEquals => name == arg,
+        TokenType::UsernameStartsWith => name.starts_with(arg),
+        TokenType::NameEquals => name.to_lowercase() == arg.to_lowercase(),
+        TokenType::NameStartsWith => name.to_lowercase().starts_with(&arg.to_lowercase()),
+        TokenType::UsernameMatches => pattern(arg, false)?.is_match(name),
+        TokenType::NameMatches => pattern(arg, true)?.is_match(name),
+        _ => return Err("Invalid Function".to_string()),
+    };
+    Ok(success)
+}
+
+/// Compiles a pattern used by `UsernameMatches` or `NameMatches`. Patterns are cached since the
+/// same code is evaluated for every member of a guild
+fn pattern(pattern: &str, case_insensitive: bool) -> Result<Regex, String> {
+    let key = (pattern.to_string(), case_insensitive);
+    let mut patterns = PATTERNS.lock().unwrap_or_else(PoisonError::into_inner);
+    if let Some(regex) = patterns.get(&key) {
+        return Ok(regex.clone());
+    }
+
+    let regex = RegexBuilder::new(pattern)
+        .case_insensitive(case_insensitive)
+        .size_limit(MAX_PATTERN_SIZE)
+        .dfa_size_limit(MAX_PATTERN_SIZE)
+        .build()
+        .map_err(|err| match err {
+            RegexError::Syntax(message) => {
+                let reason = message.lines().last().unwrap_or_default();
+                format!("Invalid pattern: {}", reason.trim_start_matches("error: "))
+            }
+            RegexError::CompiledTooBig(_) => "Pattern is too large".to_string(),
+            _ => format!("Invalid pattern: {err}"),
+        })?;
+    if patterns.len() >= PATTERN_CACHE_SIZE {
+        patterns.clear();
+    }
+    patterns.insert(key, regex.clone());
+    Ok(regex)
+}
+
+const fn asset_type(token_type: TokenType) -> Option<AssetType> {
+    match token_type {
+        TokenType::HasAsset => Some(AssetType::Asset),
+        TokenType::HasBadge => Some(AssetType::Badge),
+        TokenType::HasGamepass => Some(AssetType::Gamepass),
+        _ => None,
+    }
+}
+
+fn arithmetic(operator: TokenType, left: &Literal, right: &Literal) -> Result<Literal, String> {
+    let (left, right) = match (left, right) {
+        (Literal::Number(l), Literal::Number(r)) => (*l, *r),
+        _ => return Err("Arithmetic operators can only be used on numbers".to_string()),
+    };
+    let result = match operator {
+        TokenType::Plus => left.checked_add(right),
+        TokenType::Minus => left.checked_sub(right),
+        TokenType::Star => left.checked_mul(right),
+        TokenType::Slash | TokenType::Percent if right == 0 => {
+            return Err("Division by zero".to_string())
+        }
+        TokenType::Slash => left.checked_div(right),
+        TokenType::Percent => left.checked_rem(right),
+        _ => return Err("Invalid Operator".to_string()),
+    };
+    result
+        .map(Literal::Number)
+        .ok_or_else(|| "Integer overflow".to_string())
 }
@@ -0,0 +1,233 @@
+use std::collections::HashMap;
+
+use crate::{id::GuildId, FromRow};
+
+use super::{
+    diagnostic::Diagnostic,
+    expression::Expression,
+    token::{Token, TokenType},
+    RoCommand, MAX_SIZE,
+};
+
+/// The maximum number of characters in the name of a macro
+pub const MAX_NAME_LENGTH: usize = 32;
+
+/// A named piece of code that can be called like a function by other code in the guild
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Macro {
+    pub guild_id: GuildId,
+    /// The name the macro is called by, i.e. `IsStaff` for `IsStaff()`
+    pub name: String,
+    pub code: String,
+}
+
+/// The compiled macros of a guild
+#[derive(Clone, Debug, Default)]
+pub struct Macros {
+    compiled: HashMap<String, CompiledMacro>,
+    errors: HashMap<String, Diagnostic>,
+}
+
+#[derive(Clone, Debug)]
+struct CompiledMacro {
+    expr: Expression,
+    /// The number of nodes in the expression with every macro call expanded
+    size: usize,
+}
+
+impl Macro {
+    /// Checks that the name can be called from code. Names may only contain letters & underscores
+    /// and cannot shadow a keyword or function
+    pub fn validate_name(name: &str) -> Result<(), String> {
+        if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH {
+            return Err(format!(
+                "Macro names must be between 1 and {MAX_NAME_LENGTH} characters long"
+            ));
+        }
+        if !name.chars().all(|c| c.is_ascii_alphabetic() || c == '_') {
+            return Err("Macro names may only contain letters and underscores".into());
+        }
+        if name.parse::<TokenType>().is_ok() {
+            return Err(format!("`{name}` is already a keyword or function"));
+        }
+        Ok(())
+    }
+}
+
+impl Macros {
+    /// Compiles every definition along with the macros it calls. A definition that fails to
+    /// compile, calls a broken macro or is part of a cycle is recorded in [`Macros::errors`] instead
+    #[must_use]
+    pub fn compile(definitions: &[Macro]) -> Self {
+        let sources = definitions
+            .iter()
+            .map(|m| (m.name.as_str(), m.code.as_str()))
+            .collect::<HashMap<_, _>>();
+        let mut macros = Self::default();
+        for definition in definitions {
+            macros.compile_macro(&definition.name, &sources, &mut Vec::new());
+        }
+        macros
+    }
+
+    /// The macros that could not be compiled along with the reason
+    #[must_use]
+    pub fn errors(&self) -> &HashMap<String, Diagnostic> {
+        &self.errors
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.compiled.is_empty() && self.errors.is_empty()
+    }
+
+    fn compile_macro(&mut self, name: &str, sources: &HashMap<&str, &str>, path: &mut Vec<String>) {
+        if self.compiled.contains_key(name) || self.errors.contains_key(name) {
+            return;
+        }
+        let code = match sources.get(name) {
+            Some(code) => *code,
+            None => return,
+        };
+
+        let mut command = match RoCommand::parse(code) {
+            Ok(c) => c,
+            Err(err) => {
+                self.errors.insert(name.to_string(), err);
+                return;
+            }
+        };
+
+        path.push(name.to_string());
+        let mut calls = Vec::new();
+        command.expr.macro_calls(&mut calls);
+        for call in calls {
+            if let Some(start) = path.iter().position(|p| *p == call.lexeme) {
+                let cycle = path[start..]
+                    .iter()
+                    .chain(std::iter::once(&call.lexeme))
+                    .map(String::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" -> ");
+                let err = Diagnostic::new(
+                    format!("Macros cannot call themselves. Found the cycle {cycle}"),
+                    call.span,
+                );
+                self.errors.insert(name.to_string(), err);
+                path.pop();
+                return;
+            }
+            self.compile_macro(&call.lexeme, sources, path);
+        }
+        path.pop();
+
+        let result = command
+            .resolve(self)
+            .and_then(|()| command.expr.check().map(|_| ()))
+            .and_then(|()| self.size(&command.expr));
+        match result {
+            Ok(size) => {
+                self.compiled.insert(
+                    name.to_string(),
+                    CompiledMacro {
+                        expr: command.expr,
+                        size,
+                    },
+                );
+            }
+            Err(err) => {
+                self.errors.insert(name.to_string(), err);
+            }
+        }
+    }
+
+    /// The compiled body of the macro called by `token`
+    fn get(&self, token: &Token) -> Result<&CompiledMacro, Diagnostic> {
+        if let Some(compiled) = self.compiled.get(&token.lexeme) {
+            return Ok(compiled);
+        }
+        let message = match self.errors.get(&token.lexeme) {
+            Some(err) => format!("The macro `{}` is invalid: {}", token.lexeme, err.message),
+            None => format!("Unknown function `{}`", token.lexeme),
+        };
+        Err(Diagnostic::new(message, token.span))
+    }
+
+    /// The number of nodes in the expression once every macro call in it is expanded
+    fn size(&self, expr: &Expression) -> Result<usize, Diagnostic> {
+        let size = match expr {
+            Expression::Binary(left, _, right) => self.size(left)? + self.size(right)?,
+            Expression::Unary(_, e) | Expression::Grouping(e) | Expression::Quantifier(_, e) => {
+                self.size(e)?
+            }
+            Expression::Literal(_) | Expression::Function(_, _) => 0,
+            Expression::Macro(token, _) => self.get(token)?.size,
+        };
+        Ok(size + 1)
+    }
+}
+
+impl Expression {
+    /// Collects the macros called directly by the expression
+    pub fn macro_calls<'a>(&'a self, calls: &mut Vec<&'a Token>) {
+        match self {
+            Expression::Binary(left, _, right) => {
+                left.macro_calls(calls);
+                right.macro_calls(calls);
+            }
+            Expression::Unary(_, e) | Expression::Grouping(e) | Expression::Quantifier(_, e) => {
+                e.macro_calls(calls);
+            }
+            Expression::Literal(_) | Expression::Function(_, _) => {}
+            Expression::Macro(token, _) => calls.push(token),
+        }
+    }
+
+    /// Replaces every macro call in the expression with the compiled body of the macro
+    pub fn resolve(&mut self, macros: &Macros) -> Result<(), Diagnostic> {
+        let size = macros.size(self)?;
+        if size > MAX_SIZE {
+            let mut calls = Vec::new();
+            self.macro_calls(&mut calls);
+            let span = calls.first().map(|c| c.span).unwrap_or_default();
+            return Err(Diagnostic::new(
+                format!(
+                    "Code cannot be larger than {MAX_SIZE} expressions once macros are expanded"
+                ),
+                span,
+            ));
+        }
+        self.inline(macros)
+    }
+
+    fn inline(&mut self, macros: &Macros) -> Result<(), Diagnostic> {
+        match self {
+            Expression::Binary(left, _, right) => {
+                left.inline(macros)?;
+                right.inline(macros)?;
+            }
+            Expression::Unary(_, e) | Expression::Grouping(e) | Expression::Quantifier(_, e) => {
+                e.inline(macros)?;
+            }
+            Expression::Literal(_) | Expression::Function(_, _) => {}
+            Expression::Macro(token, body) => {
+                *body = Some(Box::new(macros.get(token)?.expr.clone()));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromRow for Macro {
+    fn from_row(row: tokio_postgres::Row) -> Result<Self, tokio_postgres::Error> {
+        let guild_id = row.try_get("guild_id")?;
+        let name = row.try_get("name")?;
+        let code = row.try_get("code")?;
+
+        Ok(Self {
+            guild_id,
+            name,
+            code,
+        })
+    }
+}
@@ -1,24 +1,54 @@
+mod checker;
+mod diagnostic;
+mod explain;
 mod expression;
+mod macros;
 mod parser;
+mod printer;
 mod scanner;
 mod token;
 
-use crate::{id::RoleId, user::RoGuildUser};
+use crate::{bind::AssetType, id::RoleId, roblox::user::User as RobloxUser, user::RoGuildUser};
 
+use postgres_types::{FromSql, Type};
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    error::Error as StdError,
     fmt::{Display, Formatter, Result as FmtResult},
 };
 
 use expression::Expression;
-use parser::Parser;
+use parser::{Grammar, Parser};
 use scanner::Scanner;
 use token::Literal;
 
+pub use checker::ValueType;
+pub use diagnostic::Diagnostic;
+pub use explain::Explanation;
+pub use macros::{Macro, Macros, MAX_NAME_LENGTH};
+pub use token::Span;
+
+/// The maximum number of characters in a piece of code
+pub const MAX_LENGTH: usize = 4000;
+
+/// The maximum number of expressions in a piece of code once the macros it calls are expanded
+pub const MAX_SIZE: usize = 4000;
+
+/// The maximum depth of an expression tree. Evaluating, checking & formatting code recurses once
+/// per level so this keeps hostile code from overflowing the stack
+pub const MAX_DEPTH: usize = 256;
+
+/// The version of the grammar new code is parsed with. Saved code keeps the version it was written
+/// under so that code whose meaning changed with a later version can be pointed out.
+/// Version 0 is the grammar from before `and` was given a higher precedence than `or`
+pub const GRAMMAR_VERSION: i32 = 1;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct RoCommand {
     pub code: String,
     pub expr: Expression,
+    /// The version of the grammar the code was written under
+    pub grammar_version: i32,
 }
 
 #[derive(Debug)]
@@ -27,18 +57,144 @@ pub struct RoCommandUser<'rc> {
     pub roles: &'rc [RoleId],
     pub ranks: &'rc HashMap<i64, i64>,
     pub username: &'rc str,
+    /// The assets checked by the code that are in the user's inventory.
+    /// These are fetched ahead of evaluation using [`RoCommand::assets`]
+    pub owned_assets: &'rc HashSet<(AssetType, i64)>,
+    /// The Roblox profile of the user. This is only fetched if [`RoCommand::uses_profile`] is true
+    pub profile: Option<&'rc RobloxUser>,
+    /// The ranks of every Roblox account linked to the user keyed by the id of the account. This
+    /// is only fetched if [`RoCommand::uses_accounts`] is true. `AnyAccount` & `AllAccounts` fall
+    /// back to `ranks` when this is empty
+    pub accounts: &'rc HashMap<i64, HashMap<i64, i64>>,
 }
 
 impl RoCommand {
-    pub fn new(code: &str) -> Result<Self, String> {
+    /// Compiles, resolves the macros in & type checks the code. This should be used for any code
+    /// that is about to be saved
+    pub fn new(code: &str, macros: &Macros) -> Result<Self, Diagnostic> {
+        let mut command = Self::parse(code)?;
+        command.resolve(macros)?;
+        let kind = command.expr.check()?;
+        if kind != ValueType::Bool {
+            return Err(Diagnostic::new(
+                format!("Expected the code to evaluate to a Bool but found a {kind}"),
+                code_span(code),
+            ));
+        }
+        Ok(command)
+    }
+
+    /// Compiles the code without resolving macros or type checking it. This is used for code that
+    /// has already been saved so that binds & blacklists created before the type checker existed
+    /// continue to load. Any macros called must be resolved with [`RoCommand::resolve`] before
+    /// evaluation. The grammar version is set to the current one, so saved code should have it
+    /// replaced with the version it was stored with
+    pub fn parse(code: &str) -> Result<Self, Diagnostic> {
+        let length = code.chars().count();
+        if length > MAX_LENGTH {
+            return Err(Diagnostic::new(
+                format!("Code cannot be longer than {MAX_LENGTH} characters"),
+                span_of(code, MAX_LENGTH, length),
+            ));
+        }
+
         let mut scanner = Scanner::new(code);
         let tokens = scanner.scan_tokens()?;
         let mut parser = Parser::new(tokens);
-        let expr = parser.expression().map_err(|e| e.1)?;
-        Ok(Self {
+        let command = Self {
             code: code.into(),
-            expr,
-        })
+            expr: parser.parse()?,
+            grammar_version: GRAMMAR_VERSION,
+        };
+        command.check_depth()?;
+        Ok(command)
+    }
+
+    /// Replaces the macros called by the code with their compiled bodies
+    pub fn resolve(&mut self, macros: &Macros) -> Result<(), Diagnostic> {
+        self.expr.resolve(macros)?;
+        self.check_depth()
+    }
+
+    /// Whether the code calls any macros
+    #[must_use]
+    pub fn calls_macros(&self) -> bool {
+        let mut calls = Vec::new();
+        self.expr.macro_calls(&mut calls);
+        !calls.is_empty()
+    }
+
+    /// Whether the code was written before `and` was given a higher precedence than `or` and
+    /// equality a higher precedence than both, and is parsed differently since. Saved code for
+    /// which this is true may now evaluate to something else than when it was created
+    #[must_use]
+    pub fn precedence_changed(&self) -> bool {
+        if self.grammar_version >= GRAMMAR_VERSION {
+            return false;
+        }
+        if let Ok(tokens) = Scanner::new(&self.code).scan_tokens() {
+            let current = Parser::new(tokens.clone()).parse();
+            let legacy = Parser::with_grammar(tokens, Grammar::Legacy).parse();
+            if let (Ok(current), Ok(legacy)) = (current, legacy) {
+                return legacy != current;
+            }
+        }
+        false
+    }
+
+    fn check_depth(&self) -> Result<(), Diagnostic> {
+        if self.expr.depth() > MAX_DEPTH {
+            return Err(Diagnostic::new(
+                format!("Code cannot be nested more than {MAX_DEPTH} levels deep"),
+                code_span(&self.code),
+            ));
+        }
+        Ok(())
+    }
+
+    /// The code in its canonical form. Parsing this gives back the same expression
+    #[must_use]
+    pub fn formatted(&self) -> String {
+        self.expr.to_string()
+    }
+
+    /// The code as it should be shown & backed up. Code that spans multiple lines or has comments
+    /// is kept as it was written since formatting it would throw the comments away
+    #[must_use]
+    pub fn source(&self) -> String {
+        let mut scanner = Scanner::new(&self.code);
+        let has_comments = scanner.scan_tokens().is_ok() && !scanner.comments().is_empty();
+        if has_comments || self.code.contains('\n') {
+            self.code.clone()
+        } else {
+            self.formatted()
+        }
+    }
+
+    /// The assets, badges & gamepasses whose ownership is checked by the code
+    #[must_use]
+    pub fn assets(&self) -> Vec<(AssetType, i64)> {
+        let mut assets = Vec::new();
+        self.expr.assets(&mut assets);
+        assets
+    }
+
+    /// Whether the code needs the Roblox profile of the user to be evaluated
+    #[must_use]
+    pub fn uses_profile(&self) -> bool {
+        self.expr.uses_profile()
+    }
+
+    /// Whether the code needs the ranks of every account linked to the user to be evaluated
+    #[must_use]
+    pub fn uses_accounts(&self) -> bool {
+        self.expr.uses_accounts()
+    }
+
+    /// Evaluates the code & records the value of every sub-expression that was evaluated
+    #[must_use]
+    pub fn explain(&self, user: &RoCommandUser) -> Explanation {
+        self.expr.explain(user)
     }
 
     pub fn evaluate(&self, user: &RoCommandUser) -> Result<bool, String> {
@@ -50,6 +206,29 @@ impl RoCommand {
     }
 }
 
+/// A span covering all of `code`
+fn code_span(code: &str) -> Span {
+    span_of(code, 0, code.chars().count())
+}
+
+/// A span covering the characters of `code` from `start` to `end`
+fn span_of(code: &str, start: usize, end: usize) -> Span {
+    let mut line = 1;
+    let mut line_start = 0;
+    for (i, c) in code.chars().take(start).enumerate() {
+        if c == '\n' {
+            line += 1;
+            line_start = i + 1;
+        }
+    }
+    Span {
+        start,
+        end,
+        line,
+        column: start - line_start + 1,
+    }
+}
+
 impl Display for RoCommand {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         f.debug_struct("RoCommand")
@@ -57,3 +236,150 @@ impl Display for RoCommand {
             .finish()
     }
 }
+
+impl<'a> FromSql<'a> for RoCommand {
+    fn from_sql(ty: &Type, raw: &'a [u8]) -> Result<Self, Box<dyn StdError + Sync + Send>> {
+        let code = String::from_sql(ty, raw)?;
+        Ok(Self::parse(&code)?)
+    }
+
+    fn accepts(ty: &Type) -> bool {
+        <String as FromSql>::accepts(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+    use std::collections::{HashMap, HashSet};
+
+    use super::{
+        parser::Parser, scanner::Scanner, Macro, Macros, RoCommand, RoCommandUser, MAX_DEPTH,
+        MAX_LENGTH,
+    };
+    use crate::{
+        id::{GuildId, RoleId, UserId},
+        user::RoGuildUser,
+    };
+
+    /// Pieces of code that are joined together to make input that gets further than the scanner
+    const FRAGMENTS: &str = concat!(
+        "( ) [ ] , and or not ! == != < <= > >= + - * / % in -- ",
+        "true false 0 1 255 1000 -5 \"a\" \"\" ",
+        "GetRank(1000) HasRank(1000,10) IsInGroup(2000) HasRole(5) HasAnyRole([5,6]) ",
+        "AnyAccount( AllAccounts( DisplayName() AccountAgeDays() WithString( Staff() Loop()",
+    );
+
+    /// Debug builds use several times more stack for each level of nesting than release builds, so
+    /// code nested up to `MAX_DEPTH` levels overflows the default stack of a test thread
+    const STACK_SIZE: usize = 16 * 1024 * 1024;
+
+    fn with_stack<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+        std::thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn(f)
+            .unwrap()
+            .join()
+            .unwrap()
+    }
+
+    /// Scans, parses, compiles & evaluates the code, only failing if one of them panics
+    fn run(code: &str) {
+        let code = code.to_owned();
+        with_stack(move || run_on_stack(&code));
+    }
+
+    fn run_on_stack(code: &str) {
+        let user = RoGuildUser {
+            guild_id: GuildId::new(1),
+            discord_id: UserId::new(2),
+            roblox_id: 3,
+        };
+        let ranks = HashMap::from([(1000, 10), (2000, 255)]);
+        let accounts = HashMap::from([(3, ranks.clone())]);
+        let roles = [RoleId::new(5)];
+        let owned_assets = HashSet::new();
+        let command_user = RoCommandUser {
+            user: &user,
+            roles: &roles,
+            ranks: &ranks,
+            username: "builderman",
+            owned_assets: &owned_assets,
+            profile: None,
+            accounts: &accounts,
+        };
+        let macros = Macros::compile(&[
+            Macro {
+                guild_id: GuildId::new(1),
+                name: "Staff".into(),
+                code: "HasRank(1000, 10) or HasRole(5)".into(),
+            },
+            Macro {
+                guild_id: GuildId::new(1),
+                name: "Loop".into(),
+                code: "Loop()".into(),
+            },
+        ]);
+
+        if let Ok(tokens) = Scanner::new(code).scan_tokens() {
+            let _ = Parser::new(tokens).parse();
+        }
+        if let Ok(command) = RoCommand::parse(code) {
+            let _ = command.formatted();
+            let _ = command.source();
+            let _ = command.precedence_changed();
+        }
+        if let Ok(command) = RoCommand::new(code, &macros) {
+            let _ = command.evaluate(&command_user);
+            let _ = command.explain(&command_user);
+        }
+    }
+
+    fn fragment() -> impl Strategy<Value = &'static str> {
+        prop::sample::select(FRAGMENTS.split_whitespace().collect::<Vec<_>>())
+    }
+
+    fn fragments() -> impl Strategy<Value = String> {
+        let separator = prop::sample::select(vec![" ", "\n", ""]);
+        prop::collection::vec((fragment(), separator), 0..40)
+            .prop_map(|fragments| fragments.into_iter().flat_map(|(f, s)| [f, s]).collect())
+    }
+
+    proptest! {
+        #[test]
+        fn arbitrary_text_does_not_panic(code in any::<String>()) {
+            run(&code);
+        }
+
+        #[test]
+        fn arbitrary_tokens_do_not_panic(code in fragments()) {
+            run(&code);
+        }
+
+        #[test]
+        fn deep_nesting_does_not_panic(
+            depth in 0..MAX_DEPTH * 4,
+            open in prop::sample::select(&["(", "not ", "-", "!", "AnyAccount(", "[", "- "][..]),
+            close in prop::sample::select(&[")", "]", ""][..]),
+        ) {
+            let code = format!("{}true{}", open.repeat(depth), close.repeat(depth));
+            run(&code);
+            if depth > MAX_DEPTH {
+                prop_assert!(with_stack(move || RoCommand::parse(&code).is_err()));
+            }
+        }
+
+        #[test]
+        fn long_code_does_not_panic(
+            extra in 0..50usize,
+            fragment in fragment(),
+        ) {
+            let length = MAX_LENGTH + extra;
+            let code = fragment.repeat(length).chars().take(length).collect::<String>();
+            run(&code);
+            if extra > 0 {
+                prop_assert!(RoCommand::parse(&code).is_err());
+            }
+        }
+    }
+}
@@ -1,11 +1,25 @@
 use super::{
     expression::Expression,
-    token::{Literal, Token, TokenType},
+    token::{Literal, Span, Token, TokenType},
+    MAX_DEPTH,
 };
 
 pub struct Parser {
     tokens: Vec<Token>,
     current: usize,
+    grammar: Grammar,
+    /// The number of unary operators & brackets the parser is currently nested in
+    depth: usize,
+}
+
+/// The precedence rules used to parse the binary operators
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Grammar {
+    /// `or` < `and` < equality < comparison < arithmetic < unary
+    Current,
+    /// The original rules where equality sits below `and`/`or`, which share a single level.
+    /// This is only kept around to detect saved code whose meaning changed with the new rules
+    Legacy,
 }
 
 #[derive(Debug)]
@@ -13,43 +27,130 @@ pub struct ParseError(pub Token, pub String);
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, current: 0 }
+        Self::with_grammar(tokens, Grammar::Current)
+    }
+
+    pub fn with_grammar(mut tokens: Vec<Token>, grammar: Grammar) -> Self {
+        // The parser stops at the EOF token so it must always be there
+        if tokens.last().map(|t| t.token_type) != Some(TokenType::EOF) {
+            tokens.push(Token::new(TokenType::EOF, "", None, Span::default()));
+        }
+        Self {
+            tokens,
+            current: 0,
+            grammar,
+            depth: 0,
+        }
+    }
+
+    /// Parses the tokens as a single expression. Every token must be used up, so code such as
+    /// `1 == 1 )` or `IsInGroup(1) IsInGroup(2)` is rejected rather than cut short
+    pub fn parse(&mut self) -> Result<Expression, ParseError> {
+        let expr = self.expression()?;
+        if !self.is_at_end() {
+            let token = self.peek().clone();
+            let message = format!(
+                "Unexpected `{}` after the end of the expression",
+                token.lexeme
+            );
+            return Err(ParseError(token, message));
+        }
+        Ok(expr)
     }
 
-    pub fn expression(&mut self) -> Result<Expression, ParseError> {
-        self.equality()
+    fn expression(&mut self) -> Result<Expression, ParseError> {
+        match self.grammar {
+            Grammar::Current => self.or(),
+            Grammar::Legacy => self.legacy_equality(),
+        }
+    }
+
+    fn or(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.and()?;
+        while self.match_type(TokenType::Or) {
+            let operator = self.previous().clone();
+            let right = self.and()?;
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.equality()?;
+        while self.match_type(TokenType::And) {
+            let operator = self.previous().clone();
+            let right = self.equality()?;
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+
+        Ok(expr)
     }
 
     fn equality(&mut self) -> Result<Expression, ParseError> {
-        let mut expr = self.comparison1()?;
+        let mut expr = self.comparison()?;
         while self.match_types(vec![TokenType::BangEqual, TokenType::EqualEqual]) {
             let operator = self.previous().clone();
-            let right = self.comparison1()?;
+            let right = self.comparison()?;
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
         }
 
         Ok(expr)
     }
 
-    fn comparison1(&mut self) -> Result<Expression, ParseError> {
-        let mut expr = self.comparison2()?;
+    fn legacy_equality(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.legacy_logical()?;
+        while self.match_types(vec![TokenType::BangEqual, TokenType::EqualEqual]) {
+            let operator = self.previous().clone();
+            let right = self.legacy_logical()?;
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn legacy_logical(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.comparison()?;
         while self.match_types(vec![TokenType::And, TokenType::Or]) {
             let operator = self.previous().clone();
-            let right = self.comparison2()?;
+            let right = self.comparison()?;
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
         }
 
         Ok(expr)
     }
 
-    fn comparison2(&mut self) -> Result<Expression, ParseError> {
-        let mut expr = self.unary()?;
+    fn comparison(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.term()?;
         while self.match_types(vec![
             TokenType::Greater,
             TokenType::GreaterEqual,
             TokenType::Less,
             TokenType::LessEqual,
+            TokenType::In,
         ]) {
+            let operator = self.previous().clone();
+            let right = self.term()?;
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn term(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.factor()?;
+        while self.match_types(vec![TokenType::Plus, TokenType::Minus]) {
+            let operator = self.previous().clone();
+            let right = self.factor()?;
+            expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn factor(&mut self) -> Result<Expression, ParseError> {
+        let mut expr = self.unary()?;
+        while self.match_types(vec![TokenType::Star, TokenType::Slash, TokenType::Percent]) {
             let operator = self.previous().clone();
             let right = self.unary()?;
             expr = Expression::Binary(Box::new(expr), operator, Box::new(right));
@@ -59,9 +160,11 @@ impl Parser {
     }
 
     fn unary(&mut self) -> Result<Expression, ParseError> {
-        if self.match_types(vec![TokenType::Not, TokenType::Bang]) {
+        if self.match_types(vec![TokenType::Not, TokenType::Bang, TokenType::Minus]) {
             let operator = self.previous().clone();
+            self.nest()?;
             let right = self.unary()?;
+            self.depth -= 1;
             return Ok(Expression::Unary(operator, Box::new(right)));
         }
 
@@ -77,9 +180,21 @@ impl Parser {
         }
 
         if self.match_types(vec![TokenType::String, TokenType::Number]) {
-            return Ok(Expression::Literal(
-                self.previous().clone().literal.unwrap(),
-            ));
+            return Ok(Expression::Literal(self.literal()?));
+        }
+
+        if self.match_type(TokenType::LeftBracket) {
+            return Ok(Expression::Literal(self.list()?));
+        }
+
+        if self.match_types(vec![TokenType::AnyAccount, TokenType::AllAccounts]) {
+            let quantifier = self.previous().clone();
+            self.consume(TokenType::LeftParen, "Expect ( after function call".into())?;
+            self.nest()?;
+            let expr = self.expression()?;
+            self.depth -= 1;
+            self.consume(TokenType::RightParen, "Expect ) after expression".into())?;
+            return Ok(Expression::Quantifier(quantifier, Box::new(expr)));
         }
 
         if self.match_types(vec![
@@ -88,12 +203,33 @@ impl Parser {
             TokenType::HasRole,
             TokenType::IsInGroup,
             TokenType::GetRank,
+            TokenType::HasAsset,
+            TokenType::HasBadge,
+            TokenType::HasGamepass,
+            TokenType::AccountAgeDays,
+            TokenType::IsBanned,
+            TokenType::DisplayName,
+            TokenType::IsInAnyGroup,
+            TokenType::HasAnyRole,
+            TokenType::HasAllRoles,
+            TokenType::UsernameEquals,
+            TokenType::UsernameStartsWith,
+            TokenType::UsernameMatches,
+            TokenType::NameEquals,
+            TokenType::NameStartsWith,
+            TokenType::NameMatches,
         ]) {
             let func = self.previous().clone();
             self.consume(TokenType::LeftParen, "Expect ( after function call".into())?;
             let mut args = Vec::<Literal>::new();
-            while self.match_types(vec![TokenType::String, TokenType::Number]) {
-                args.push(self.previous().clone().literal.unwrap());
+            loop {
+                if self.match_types(vec![TokenType::String, TokenType::Number]) {
+                    args.push(self.literal()?);
+                } else if self.match_type(TokenType::LeftBracket) {
+                    args.push(self.list()?);
+                } else {
+                    break;
+                }
             }
             self.consume(TokenType::RightParen, "Expect ) after function args".into())?;
             let function = Expression::Function(func, args);
@@ -101,8 +237,21 @@ impl Parser {
             return Ok(function);
         }
 
+        if self.match_type(TokenType::Identifier) {
+            let name = self.previous().clone();
+            self.consume(TokenType::LeftParen, "Expect ( after function call".into())?;
+            // Macros don't take arguments so this is most likely a misspelt function
+            if !self.match_type(TokenType::RightParen) {
+                let message = format!("Unknown function `{}`", name.lexeme);
+                return Err(ParseError(name, message));
+            }
+            return Ok(Expression::Macro(name, None));
+        }
+
         if self.match_type(TokenType::LeftParen) {
+            self.nest()?;
             let expr = self.expression()?;
+            self.depth -= 1;
             self.consume(TokenType::RightParen, "Expect ) after expression".into())?;
             return Ok(Expression::Grouping(Box::new(expr)));
         }
@@ -110,6 +259,48 @@ impl Parser {
         Err(ParseError(self.peek().clone(), "Expect expression".into()))
     }
 
+    /// Parses the items of a list literal whose opening bracket has already been consumed
+    fn list(&mut self) -> Result<Literal, ParseError> {
+        let mut items = Vec::<Literal>::new();
+        while self.match_types(vec![TokenType::String, TokenType::Number]) {
+            let item = self.literal()?;
+            if let Some(first) = items.first() {
+                if first.value_type() != item.value_type() {
+                    return Err(ParseError(
+                        self.previous().clone(),
+                        format!(
+                            "Expected every item of the list to be a {}",
+                            first.value_type()
+                        ),
+                    ));
+                }
+            }
+            items.push(item);
+        }
+        self.consume(TokenType::RightBracket, "Expect ] after list items".into())?;
+        Ok(Literal::List(items))
+    }
+
+    /// The value of the string or number token that was just consumed
+    fn literal(&self) -> Result<Literal, ParseError> {
+        let token = self.previous();
+        token
+            .literal
+            .clone()
+            .ok_or_else(|| ParseError(token.clone(), "Expect a value".into()))
+    }
+
+    fn nest(&mut self) -> Result<(), ParseError> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err(ParseError(
+                self.previous().clone(),
+                format!("Code cannot be nested more than {MAX_DEPTH} levels deep"),
+            ));
+        }
+        Ok(())
+    }
+
     fn match_type(&mut self, token: TokenType) -> bool {
         if self.check(token) {
             self.advance();
@@ -162,3 +353,22 @@ impl Parser {
         &self.tokens[self.current - 1]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::super::RoCommand;
+
+    #[test]
+    fn trailing_tokens_are_rejected() {
+        for (code, unexpected) in [
+            ("IsInGroup(1) IsInGroup(2)", "IsInGroup"),
+            ("HasRank(1, 2) )", ")"),
+            ("1 == 1 2", "2"),
+        ] {
+            let err = RoCommand::parse(code).unwrap_err();
+            let start = code.rfind(unexpected).unwrap();
+            assert_eq!(err.span.start, start, "{code}");
+            assert_eq!(err.span.end, start + unexpected.len(), "{code}");
+        }
+    }
+}
@@ -0,0 +1,269 @@
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+
+use super::{
+    expression::Expression,
+    token::{Literal, TokenType},
+};
+
+impl Display for Literal {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Literal::String(s) => write!(f, "\"{s}\""),
+            Literal::Number(n) => write!(f, "{n}"),
+            Literal::Bool(b) => write!(f, "{b}"),
+            Literal::List(items) => {
+                f.write_char('[')?;
+                write_list(f, items)?;
+                f.write_char(']')
+            }
+        }
+    }
+}
+
+/// Writes the expression as canonical `RoLang`. Operators are separated by single spaces, `!` is
+/// written as `not` and brackets are only kept where the precedence rules need them, so parsing the
+/// output gives back the same expression without its groupings
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            Expression::Binary(left, operator, right) => {
+                let precedence = self.precedence();
+                write_operand(f, left, left.precedence() < precedence)?;
+                write!(f, " {} ", operator.lexeme)?;
+                write_operand(f, right, right.precedence() <= precedence)
+            }
+            Expression::Unary(operator, right) => {
+                if operator.token_type == TokenType::Minus {
+                    f.write_char('-')?;
+                    // `--` starts a comment so a negated negation keeps its brackets
+                    write_operand(f, right, right.precedence() < UNARY || right.is_negative())
+                } else {
+                    f.write_str("not ")?;
+                    write_operand(f, right, right.precedence() < UNARY)
+                }
+            }
+            Expression::Literal(literal) => write!(f, "{literal}"),
+            Expression::Grouping(e) => write!(f, "{e}"),
+            Expression::Function(function, args) => {
+                write!(f, "{}(", function.lexeme)?;
+                write_list(f, args)?;
+                f.write_char(')')
+            }
+            Expression::Macro(name, _) => write!(f, "{}()", name.lexeme),
+            Expression::Quantifier(quantifier, e) => write!(f, "{}({e})", quantifier.lexeme),
+        }
+    }
+}
+
+const UNARY: u8 = 7;
+
+impl Expression {
+    /// The precedence of the expression in the current grammar. Higher binds tighter
+    fn precedence(&self) -> u8 {
+        match self {
+            Expression::Binary(_, operator, _) => match operator.token_type {
+                TokenType::Or => 1,
+                TokenType::And => 2,
+                TokenType::EqualEqual | TokenType::BangEqual => 3,
+                TokenType::Plus | TokenType::Minus => 5,
+                TokenType::Star | TokenType::Slash | TokenType::Percent => 6,
+                _ => 4,
+            },
+            Expression::Unary(_, _) => UNARY,
+            Expression::Grouping(e) => e.precedence(),
+            Expression::Literal(_)
+            | Expression::Function(_, _)
+            | Expression::Macro(_, _)
+            | Expression::Quantifier(_, _) => 8,
+        }
+    }
+
+    /// Whether the formatted expression starts with a `-`
+    fn is_negative(&self) -> bool {
+        match self {
+            Expression::Unary(operator, _) => operator.token_type == TokenType::Minus,
+            Expression::Literal(Literal::Number(n)) => *n < 0,
+            Expression::Grouping(e) => e.is_negative(),
+            _ => false,
+        }
+    }
+}
+
+fn write_operand(f: &mut Formatter<'_>, operand: &Expression, bracket: bool) -> FmtResult {
+    if bracket {
+        write!(f, "({operand})")
+    } else {
+        write!(f, "{operand}")
+    }
+}
+
+fn write_list(f: &mut Formatter<'_>, items: &[Literal]) -> FmtResult {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{item}")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::super::{expression::Expression, token::TokenType, RoCommand};
+
+    /// The structure of an expression, leaving out spans, groupings & how the operators were spelt
+    fn shape(expr: &Expression) -> String {
+        match expr {
+            Expression::Binary(left, operator, right) => format!(
+                "({:?} {} {})",
+                operator.token_type,
+                shape(left),
+                shape(right)
+            ),
+            Expression::Unary(operator, right) => {
+                let token_type = match operator.token_type {
+                    TokenType::Bang => TokenType::Not,
+                    token_type => token_type,
+                };
+                format!("({token_type:?} {})", shape(right))
+            }
+            Expression::Literal(literal) => format!("{literal:?}"),
+            Expression::Grouping(e) => shape(e),
+            Expression::Function(function, args) => {
+                format!("({:?} {args:?})", function.token_type)
+            }
+            Expression::Macro(name, _) => format!("(Macro {})", name.lexeme),
+            Expression::Quantifier(quantifier, e) => {
+                format!("({:?} {})", quantifier.token_type, shape(e))
+            }
+        }
+    }
+
+    fn assert_round_trip(code: &str) {
+        let command = RoCommand::parse(code).unwrap();
+        let formatted = command.formatted();
+        let reparsed = RoCommand::parse(&formatted).unwrap();
+        assert_eq!(
+            shape(&reparsed.expr),
+            shape(&command.expr),
+            "`{code}` was formatted as `{formatted}`"
+        );
+        assert_eq!(reparsed.formatted(), formatted);
+    }
+
+    /// Code built from the grammar with spacing & brackets that often differ from the canonical form
+    fn code() -> impl Strategy<Value = String> {
+        let leaf = prop::sample::select(vec![
+            "true",
+            "false",
+            "0",
+            "7",
+            "\"a\"",
+            "[1, 2]",
+            "GetRank(1000)",
+            "IsInGroup(1)",
+            "HasRank(1000,10)",
+            "HasRole(5)",
+            "DisplayName()",
+            "Staff()",
+        ])
+        .prop_map(String::from);
+        let operators = vec![
+            "and", "or", "==", "!=", "<", "<=", ">", ">=", "in", "+", "-", "*", "/", "%",
+        ];
+        leaf.prop_recursive(8, 64, 2, move |inner| {
+            prop_oneof![
+                (
+                    inner.clone(),
+                    prop::sample::select(operators.clone()),
+                    inner.clone()
+                )
+                    .prop_map(|(left, operator, right)| format!("{left} {operator} {right}")),
+                (
+                    prop::sample::select(vec!["not ", "!", "-", "- "]),
+                    inner.clone()
+                )
+                    .prop_map(|(operator, right)| format!("{operator}{right}")),
+                inner.clone().prop_map(|e| format!("( {e})")),
+                (
+                    prop::sample::select(vec!["AnyAccount", "AllAccounts"]),
+                    inner
+                )
+                    .prop_map(|(quantifier, e)| format!("{quantifier}({e})")),
+            ]
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn formatting_round_trips(code in code()) {
+            if RoCommand::parse(&code).is_ok() {
+                assert_round_trip(&code);
+            }
+        }
+    }
+
+    #[test]
+    fn formatting_keeps_the_expression() {
+        let codes = [
+            "HasRank(1000, 255)",
+            "HasRank(1000,255)  and IsInGroup(2000) or !HasRole(1234)",
+            "HasRank(1000, 255) and (IsInGroup(2000) or not HasRole(1234))",
+            "(IsInGroup(1) or IsInGroup(2)) and (IsInGroup(3) or IsInGroup(4))",
+            "IsInGroup(1) or (IsInGroup(2) or IsInGroup(3))",
+            "not (IsInGroup(1) and IsInGroup(2))",
+            "not not true",
+            "GetRank(1000) - (10 - 5) >= 2 * (3 + 4) % 5",
+            "(GetRank(1) - 2) - 3 == GetRank(1) - (2 - 3)",
+            "-(-GetRank(1)) < - -5",
+            "(1 == 2) == (3 == 4)",
+            "(1 < 2) == true",
+            "GetRank(1000) in [1, 2, 3] and DisplayName() in [\"a\", \"b\"]",
+            "WithString(\"a -- b\") and UsernameStartsWith(\"x\")",
+            "AnyAccount(GetRank(1000) >= 5 and HasRank(1000, 10)) or AllAccounts(IsInGroup(1))",
+            "HasAnyRole([1, 2]) and HasAllRoles([3]) or IsInAnyGroup([4, 5])",
+            "AccountAgeDays() >= 30 and not IsBanned()",
+        ];
+        for code in codes {
+            assert_round_trip(code);
+        }
+    }
+
+    #[test]
+    fn formatting_is_canonical() {
+        let command = RoCommand::parse("( HasRank(1000,255)and !IsInGroup( 2 ) )").unwrap();
+        assert_eq!(
+            command.formatted(),
+            "HasRank(1000, 255) and not IsInGroup(2)"
+        );
+    }
+
+    #[test]
+    fn trailing_tokens_are_not_formatted() {
+        for code in [
+            "IsInGroup(1) IsInGroup(2)",
+            "HasRank(1000, 255) )",
+            "IsInGroup(1) or IsInGroup(2) true",
+        ] {
+            let err = RoCommand::parse(code).unwrap_err();
+            assert!(err.message.starts_with("Unexpected"), "{code}: {err}");
+        }
+    }
+
+    #[test]
+    fn source_keeps_comments_and_lines() {
+        let commented = "IsInGroup(1) -- staff";
+        assert_eq!(RoCommand::parse(commented).unwrap().source(), commented);
+
+        let multi_line = "IsInGroup(1)\nor IsInGroup(2)";
+        assert_eq!(RoCommand::parse(multi_line).unwrap().source(), multi_line);
+
+        let string = "WithString(\"a -- b\")  and  IsInGroup(1)";
+        assert_eq!(
+            RoCommand::parse(string).unwrap().source(),
+            "WithString(\"a -- b\") and IsInGroup(1)"
+        );
+    }
+}
@@ -1,10 +1,16 @@
-use super::token::{Literal, Token, TokenType};
+use super::{
+    diagnostic::Diagnostic,
+    token::{Literal, Span, Token, TokenType},
+};
 
 pub struct Scanner {
     source: Vec<char>,
     start: usize,
     current: usize,
+    line: usize,
+    line_start: usize,
     tokens: Vec<Token>,
+    comments: Vec<Token>,
 }
 
 #[allow(clippy::unused_self)]
@@ -14,24 +20,36 @@ impl Scanner {
             source: source.chars().collect(),
             start: 0,
             current: 0,
+            line: 1,
+            line_start: 0,
             tokens: Vec::<Token>::new(),
+            comments: Vec::new(),
         }
     }
 
-    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, String> {
+    /// The comments found while scanning. These are kept apart from the tokens given to the parser
+    pub fn comments(&self) -> &[Token] {
+        &self.comments
+    }
+
+    pub fn scan_tokens(&mut self) -> Result<Vec<Token>, Diagnostic> {
         while !self.is_at_end() {
             self.start = self.current;
             self.scan_token()?;
         }
-        self.tokens.push(Token::new(TokenType::EOF, "", None));
+        self.start = self.current;
+        self.tokens
+            .push(Token::new(TokenType::EOF, "", None, self.span()));
         Ok(self.tokens.clone())
     }
 
-    fn scan_token(&mut self) -> Result<(), String> {
+    fn scan_token(&mut self) -> Result<(), Diagnostic> {
         let c = self.advance();
         match c {
             '(' => self.add_token(TokenType::LeftParen, None),
             ')' => self.add_token(TokenType::RightParen, None),
+            '[' => self.add_token(TokenType::LeftBracket, None),
+            ']' => self.add_token(TokenType::RightBracket, None),
 
             '!' => {
                 if self.match_char('=') {
@@ -62,18 +80,44 @@ impl Scanner {
                 }
             }
 
+            '+' => self.add_token(TokenType::Plus, None),
+            '-' => {
+                if self.match_char('-') {
+                    // Comments run until the end of the line
+                    while self.peek() != '\n' && !self.is_at_end() {
+                        self.advance();
+                    }
+                    let comment = self.token(TokenType::Comment, None);
+                    self.comments.push(comment);
+                } else {
+                    self.add_token(TokenType::Minus, None);
+                }
+            }
+            '*' => self.add_token(TokenType::Star, None),
+            '/' => self.add_token(TokenType::Slash, None),
+            '%' => self.add_token(TokenType::Percent, None),
+
             ' ' | '\r' | '\t' | ',' => {}
+            '\n' => {
+                self.line += 1;
+                self.line_start = self.current;
+            }
 
             '"' => self.string()?,
             digit if self.is_digit(digit) => self.number()?,
-            alpha if self.is_alpha(alpha) => self.identifier()?,
+            alpha if self.is_alpha(alpha) => self.identifier(),
 
-            _ => return Err(String::from("Unexpected character")),
+            _ => {
+                return Err(Diagnostic::new(
+                    format!("Unexpected character `{c}`"),
+                    self.span(),
+                ))
+            }
         }
         Ok(())
     }
 
-    fn identifier(&mut self) -> Result<(), String> {
+    fn identifier(&mut self) {
         while self.is_alpha(self.peek()) {
             self.advance();
         }
@@ -81,11 +125,12 @@ impl Scanner {
         let text = self.source[self.start..self.current]
             .iter()
             .collect::<String>();
-        self.add_token(text.parse::<TokenType>()?, None);
-        Ok(())
+        // Names that aren't keywords or functions are macros, which are resolved after parsing
+        let token_type = text.parse::<TokenType>().unwrap_or(TokenType::Identifier);
+        self.add_token(token_type, None);
     }
 
-    fn number(&mut self) -> Result<(), String> {
+    fn number(&mut self) -> Result<(), Diagnostic> {
         while self.is_digit(self.peek()) {
             self.advance();
         }
@@ -94,18 +139,18 @@ impl Scanner {
             .iter()
             .collect::<String>()
             .parse()
-            .map_err(|_| String::from("Unexpected number"))?;
+            .map_err(|_| Diagnostic::new("Number is too large", self.span()))?;
         self.add_token(TokenType::Number, Some(Literal::Number(val)));
         Ok(())
     }
 
-    fn string(&mut self) -> Result<(), String> {
-        while self.peek() != '"' && !self.is_at_end() {
+    fn string(&mut self) -> Result<(), Diagnostic> {
+        while self.peek() != '"' && self.peek() != '\n' && !self.is_at_end() {
             self.advance();
         }
 
-        if self.is_at_end() {
-            return Err(String::from("Unterminated String"));
+        if self.peek() != '"' {
+            return Err(Diagnostic::new("Unterminated string", self.span()));
         }
 
         self.advance();
@@ -153,9 +198,23 @@ impl Scanner {
     }
 
     fn add_token(&mut self, token_type: TokenType, literal: Option<Literal>) {
+        let token = self.token(token_type, literal);
+        self.tokens.push(token);
+    }
+
+    fn token(&self, token_type: TokenType, literal: Option<Literal>) -> Token {
         let text = self.source[self.start..self.current]
             .iter()
             .collect::<String>();
-        self.tokens.push(Token::new(token_type, &text, literal));
+        Token::new(token_type, &text, literal, self.span())
+    }
+
+    fn span(&self) -> Span {
+        Span {
+            start: self.start,
+            end: self.current,
+            line: self.line,
+            column: self.start - self.line_start + 1,
+        }
     }
 }
@@ -5,6 +5,8 @@ use std::{cmp::PartialEq, convert::From, ops::BitXor, str::FromStr};
 pub enum TokenType {
     LeftParen,
     RightParen,
+    LeftBracket,
+    RightBracket,
 
     Bang,
     BangEqual,
@@ -15,21 +17,47 @@ pub enum TokenType {
     Less,
     LessEqual,
 
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Percent,
+
     String,
     Number,
+    Identifier,
 
     And,
     Or,
     Not,
+    In,
     True,
     False,
     EOF,
+    Comment,
 
     HasRank,
     WithString,
     IsInGroup,
     HasRole,
     GetRank,
+    HasAsset,
+    HasBadge,
+    HasGamepass,
+    AccountAgeDays,
+    IsBanned,
+    DisplayName,
+    IsInAnyGroup,
+    HasAnyRole,
+    HasAllRoles,
+    UsernameEquals,
+    UsernameStartsWith,
+    UsernameMatches,
+    NameEquals,
+    NameStartsWith,
+    NameMatches,
+    AnyAccount,
+    AllAccounts,
 }
 
 #[derive(Debug, Clone, Ord, PartialOrd, Eq, PartialEq)]
@@ -37,6 +65,20 @@ pub enum Literal {
     String(String),
     Number(i64),
     Bool(bool),
+    List(Vec<Literal>),
+}
+
+/// The location of a token in the source code. Offsets are counted in characters
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Span {
+    /// The offset of the first character of the span
+    pub start: usize,
+    /// The offset just past the last character of the span
+    pub end: usize,
+    /// The line the span starts on (1-indexed)
+    pub line: usize,
+    /// The column of the line the span starts on (1-indexed)
+    pub column: usize,
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -44,14 +86,16 @@ pub struct Token {
     pub token_type: TokenType,
     pub lexeme: String,
     pub literal: Option<Literal>,
+    pub span: Span,
 }
 
 impl Token {
-    pub fn new(token_type: TokenType, lexeme: &str, literal: Option<Literal>) -> Token {
+    pub fn new(token_type: TokenType, lexeme: &str, literal: Option<Literal>, span: Span) -> Token {
         Token {
             token_type,
             lexeme: lexeme.into(),
             literal,
+            span,
         }
     }
 }
@@ -64,6 +108,7 @@ impl FromStr for TokenType {
             "and" => TokenType::And,
             "or" => TokenType::Or,
             "not" => TokenType::Not,
+            "in" => TokenType::In,
             "true" => TokenType::True,
             "false" => TokenType::False,
             "HasRank" => TokenType::HasRank,
@@ -71,6 +116,23 @@ impl FromStr for TokenType {
             "IsInGroup" => TokenType::IsInGroup,
             "HasRole" => TokenType::HasRole,
             "GetRank" => TokenType::GetRank,
+            "HasAsset" => TokenType::HasAsset,
+            "HasBadge" => TokenType::HasBadge,
+            "HasGamepass" => TokenType::HasGamepass,
+            "AccountAgeDays" => TokenType::AccountAgeDays,
+            "IsBanned" => TokenType::IsBanned,
+            "DisplayName" => TokenType::DisplayName,
+            "IsInAnyGroup" => TokenType::IsInAnyGroup,
+            "HasAnyRole" => TokenType::HasAnyRole,
+            "HasAllRoles" => TokenType::HasAllRoles,
+            "UsernameEquals" => TokenType::UsernameEquals,
+            "UsernameStartsWith" => TokenType::UsernameStartsWith,
+            "UsernameMatches" => TokenType::UsernameMatches,
+            "NameEquals" => TokenType::NameEquals,
+            "NameStartsWith" => TokenType::NameStartsWith,
+            "NameMatches" => TokenType::NameMatches,
+            "AnyAccount" => TokenType::AnyAccount,
+            "AllAccounts" => TokenType::AllAccounts,
             _ => return Err(String::from("Invalid Keyword")),
         })
     }
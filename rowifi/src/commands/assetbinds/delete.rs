@@ -139,7 +139,7 @@ pub async fn assetbinds_delete(ctx: CommandContext, args: DeleteArguments) -> Co
 
                     let mut db = ctx.bot.database.get().await?;
                     let transaction = db.transaction().await?;
-                    let statement = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, asset_id, asset_type, discord_roles, priority, template) VALUES($1, $2, $3, $4, $5, $6, $7)").await?;
+                    let statement = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, asset_id, asset_type, discord_roles, priority, template, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)").await?;
                     for bind in binds_to_delete {
                         transaction
                             .execute(
@@ -152,6 +152,10 @@ pub async fn assetbinds_delete(ctx: CommandContext, args: DeleteArguments) -> Co
                                     &bind.discord_roles,
                                     &bind.priority,
                                     &bind.template,
+                                    &bind.removed_roles,
+                                    &bind.active_from,
+                                    &bind.active_until,
+                                    &bind.fallback_templates,
                                 ],
                             )
                             .await?;
@@ -2,12 +2,19 @@ mod delete;
 mod modify;
 mod new;
 
+use chrono::Utc;
 use itertools::Itertools;
 use rowifi_framework::prelude::*;
 use rowifi_models::bind::{Assetbind, BindType};
+use std::fmt::Write;
+
+use crate::utils::{active_window, fallbacks_str};
 
 pub use delete::assetbinds_delete;
-pub use modify::{ab_add_roles, ab_modify_priority, ab_modify_template, ab_remove_roles};
+pub use modify::{
+    ab_add_roles, ab_modify_active, ab_modify_fallbacks, ab_modify_priority, ab_modify_template,
+    ab_remove_roles, ab_removed_roles,
+};
 pub use new::assetbinds_new;
 
 pub fn assetbinds_config(cmds: &mut Vec<Command>) {
@@ -47,6 +54,26 @@ pub fn assetbinds_config(cmds: &mut Vec<Command>) {
         .description("Command to remove roles from an assetbind")
         .handler(ab_remove_roles);
 
+    let assetbinds_removed_roles_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["removed-roles"])
+        .description("Command to set the roles removed from members matching an assetbind")
+        .handler(ab_removed_roles);
+
+    let assetbinds_modify_active_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["active"])
+        .description("Command to set the time window during which a assetbind applies")
+        .handler(ab_modify_active);
+
+    let assetbinds_modify_fallbacks_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["fallbacks"])
+        .description(
+            "Command to set the templates tried when the nickname of an assetbind is too long",
+        )
+        .handler(ab_modify_fallbacks);
+
     let assetbinds_modify_cmd = Command::builder()
         .level(RoLevel::Admin)
         .names(&["modify", "m"])
@@ -55,6 +82,9 @@ pub fn assetbinds_config(cmds: &mut Vec<Command>) {
         .sub_command(assetbinds_modify_template_cmd)
         .sub_command(assetbinds_add_roles_cmd)
         .sub_command(assetbinds_remove_roles_cmd)
+        .sub_command(assetbinds_removed_roles_cmd)
+        .sub_command(assetbinds_modify_active_cmd)
+        .sub_command(assetbinds_modify_fallbacks_cmd)
         .no_handler();
 
     let assetbinds_delete_cmd = Command::builder()
@@ -102,26 +132,53 @@ pub async fn assetbinds_view(ctx: CommandContext) -> CommandResult {
     let mut pages = Vec::new();
     let mut page_count = 0;
 
-    for abs in &assetbinds.iter().chunks(12) {
-        let mut embed = EmbedBuilder::new()
-            .default_data()
-            .title("AssetBinds")
-            .description(format!("Page {}", page_count + 1));
-        for ab in abs {
-            let name = format!("Id: {}", ab.asset_id);
-            let roles_str = ab
-                .discord_roles
-                .iter()
-                .map(|r| format!("<@&{}>", r))
-                .collect::<String>();
-            let desc = format!(
-                "Type: {}\nTemplate: {}\nPriority: {}\nRoles: {}",
-                ab.asset_type, ab.template, ab.priority, roles_str
-            );
-            embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+    let now = Utc::now();
+    let (expired, assetbinds): (Vec<_>, Vec<_>) = assetbinds
+        .into_iter()
+        .partition(|ab| matches!(ab.active_until, Some(until) if until <= now));
+
+    for (title, assetbinds) in [("AssetBinds", assetbinds), ("Expired AssetBinds", expired)] {
+        for abs in &assetbinds.iter().chunks(12) {
+            let mut embed = EmbedBuilder::new()
+                .default_data()
+                .title(title)
+                .description(format!("Page {}", page_count + 1));
+            for ab in abs {
+                let name = format!("Id: {}", ab.asset_id);
+                let roles_str = ab.discord_roles.iter().map(|r| format!("<@&{r}>")).join("");
+                let mut desc = format!(
+                    "Type: {}\nTemplate: {}\nPriority: {}\nRoles: {}",
+                    ab.asset_type, ab.template, ab.priority, roles_str
+                );
+                if !ab.removed_roles.is_empty() {
+                    let _ = write!(
+                        desc,
+                        "\nRemoved Roles: {}",
+                        ab.removed_roles
+                            .iter()
+                            .map(|r| format!("<@&{r}>"))
+                            .join(" ")
+                    );
+                }
+                if ab.active_from.is_some() || ab.active_until.is_some() {
+                    let _ = write!(
+                        desc,
+                        "\nActive: {}",
+                        active_window(ab.active_from, ab.active_until)
+                    );
+                }
+                if !ab.fallback_templates.is_empty() {
+                    let _ = write!(
+                        desc,
+                        "\nFallbacks: {}",
+                        fallbacks_str(&ab.fallback_templates)
+                    );
+                }
+                embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+            }
+            pages.push(embed.build()?);
+            page_count += 1;
         }
-        pages.push(embed.build()?);
-        page_count += 1;
     }
 
     paginate_embed(&ctx, pages, page_count).await?;
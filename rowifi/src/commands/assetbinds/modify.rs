@@ -5,6 +5,8 @@ use rowifi_models::{
     id::RoleId,
 };
 
+use crate::utils::{active_window, check_manageable, fallbacks_str, parse_fallbacks, ActiveTime};
+
 #[derive(FromArgs)]
 pub struct ModifyPriority {
     #[arg(help = "The id of the asset to modify")]
@@ -153,6 +155,10 @@ pub async fn ab_add_roles(ctx: CommandContext, args: AddRoles) -> CommandResult
         }
     };
 
+    if !check_manageable(&ctx, "Asset Modification Failed", &role_ids).await? {
+        return Ok(());
+    }
+
     ctx.bot.database.execute("UPDATE binds SET discord_roles = array_cat(discord_roles, $1::BIGINT[]) WHERE bind_id = $2", &[&role_ids, &bind.bind_id]).await?;
 
     let modification = role_ids
@@ -231,6 +237,214 @@ pub async fn ab_remove_roles(ctx: CommandContext, args: RemoveRoles) -> CommandR
     ab_reply_log(ctx, name, desc).await
 }
 
+#[derive(FromArgs)]
+pub struct RemovedRoles {
+    #[arg(help = "The id of the asset to modify")]
+    pub asset_id: i64,
+    #[arg(
+        help = "The roles to remove from members matching the bind. Leave empty to clear",
+        rest
+    )]
+    pub roles: Option<String>,
+}
+
+pub async fn ab_removed_roles(ctx: CommandContext, args: RemovedRoles) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let asset_id = args.asset_id;
+
+    let mut role_ids = Vec::new();
+    for r in args.roles.unwrap_or_default().split_ascii_whitespace() {
+        if let Some(resolved) = &ctx.resolved {
+            role_ids.extend(resolved.roles.iter().map(|r| RoleId(*r.0)));
+        } else if let Some(r) = parse_role(r) {
+            role_ids.push(r);
+        }
+    }
+    role_ids = role_ids.into_iter().unique().collect::<Vec<_>>();
+
+    let assetbinds = ctx
+        .bot
+        .database
+        .query::<Assetbind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY asset_id",
+            &[&(guild_id), &BindType::Asset],
+        )
+        .await?;
+
+    let Some(bind) = assetbinds.iter().find(|a| a.asset_id == asset_id) else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Asset Modification Failed")
+            .description(format!("A bind with Asset Id {asset_id} does not exist"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET removed_roles = $1 WHERE bind_id = $2",
+            &[&role_ids, &bind.bind_id],
+        )
+        .await?;
+
+    let roles_str = |roles: &[RoleId]| {
+        if roles.is_empty() {
+            "None".to_string()
+        } else {
+            roles.iter().map(|r| format!("<@&{r}>")).join(" ")
+        }
+    };
+    let name = format!("Id: {asset_id}");
+    let desc = format!(
+        "`Removed Roles`: {} -> {}",
+        roles_str(&bind.removed_roles),
+        roles_str(&role_ids)
+    );
+
+    ab_reply_log(ctx, name, desc).await
+}
+
+#[derive(FromArgs)]
+pub struct ModifyActive {
+    #[arg(help = "The id of the asset to modify")]
+    pub asset_id: i64,
+    #[arg(help = "When the bind starts applying. Use `none` to apply right away")]
+    pub active_from: ActiveTime,
+    #[arg(help = "When the bind stops applying. Use `none` to never expire")]
+    pub active_until: ActiveTime,
+}
+
+pub async fn ab_modify_active(ctx: CommandContext, args: ModifyActive) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let asset_id = args.asset_id;
+    let active_from = args.active_from.0;
+    let active_until = args.active_until.0;
+
+    if let (Some(from), Some(until)) = (active_from, active_until) {
+        if from >= until {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Asset Modification Failed")
+                .description("The bind must become active before it expires")
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    }
+
+    let assetbinds = ctx
+        .bot
+        .database
+        .query::<Assetbind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY asset_id",
+            &[&(guild_id), &BindType::Asset],
+        )
+        .await?;
+
+    let Some(bind) = assetbinds.iter().find(|a| a.asset_id == asset_id) else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Asset Modification Failed")
+            .description(format!("A bind with Asset Id {asset_id} does not exist"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET active_from = $1, active_until = $2 WHERE bind_id = $3",
+            &[&active_from, &active_until, &bind.bind_id],
+        )
+        .await?;
+
+    let name = format!("Id: {asset_id}");
+    let desc = format!(
+        "`Active`: {} -> {}",
+        active_window(bind.active_from, bind.active_until),
+        active_window(active_from, active_until)
+    );
+
+    ab_reply_log(ctx, name, desc).await
+}
+
+#[derive(FromArgs)]
+pub struct ModifyFallbacks {
+    #[arg(help = "The id of the asset to modify")]
+    pub asset_id: i64,
+    #[arg(
+        help = "The templates to try in order when the nickname is too long, separated by `|`. Use `none` to clear them",
+        rest
+    )]
+    pub fallbacks: String,
+}
+
+pub async fn ab_modify_fallbacks(ctx: CommandContext, args: ModifyFallbacks) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let asset_id = args.asset_id;
+    let fallbacks = args.fallbacks;
+
+    if fallbacks.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Asset Modification Failed")
+            .description("You have entered no fallback templates. Use `none` to clear them")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+    let fallbacks = parse_fallbacks(&fallbacks);
+
+    let assetbinds = ctx
+        .bot
+        .database
+        .query::<Assetbind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY asset_id",
+            &[&(guild_id), &BindType::Asset],
+        )
+        .await?;
+
+    let Some(bind) = assetbinds.iter().find(|a| a.asset_id == asset_id) else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Asset Modification Failed")
+            .description(format!("A bind with Asset Id {asset_id} does not exist"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET fallback_templates = $1 WHERE bind_id = $2",
+            &[&fallbacks, &bind.bind_id],
+        )
+        .await?;
+
+    let name = format!("Id: {asset_id}");
+    let desc = format!(
+        "`Fallbacks`: {} -> {}",
+        fallbacks_str(&bind.fallback_templates),
+        fallbacks_str(&fallbacks)
+    );
+
+    ab_reply_log(ctx, name, desc).await
+}
+
 async fn ab_reply_log(ctx: CommandContext, name: String, desc: String) -> CommandResult {
     let guild_id = ctx.guild_id.unwrap();
     let embed = EmbedBuilder::new()
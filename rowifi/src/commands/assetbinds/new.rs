@@ -6,6 +6,8 @@ use rowifi_models::{
     id::{BindId, RoleId},
 };
 
+use crate::utils::check_manageable;
+
 #[derive(FromArgs)]
 pub struct NewArguments {
     #[arg(help = "The type of asset to create")]
@@ -80,6 +82,10 @@ pub async fn assetbinds_new(ctx: CommandContext, args: NewArguments) -> CommandR
         }
     }
 
+    if !check_manageable(&ctx, "Bind Addition Failed", &roles).await? {
+        return Ok(());
+    }
+
     let bind = Assetbind {
         // default is entered here since this field is not used in the insertion. The struct is only constructed to ensure we have
         // collected all fields.
@@ -87,6 +93,10 @@ pub async fn assetbinds_new(ctx: CommandContext, args: NewArguments) -> CommandR
         asset_id,
         asset_type,
         discord_roles: roles.into_iter().unique().collect::<Vec<_>>(),
+        removed_roles: Vec::new(),
+        active_from: None,
+        active_until: None,
+        fallback_templates: Vec::new(),
         priority,
         template: Template(template_str.clone()),
     };
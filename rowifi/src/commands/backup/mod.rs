@@ -79,7 +79,7 @@ pub async fn backup(ctx: CommandContext) -> CommandResult {
         let r = data
             .binds
             .iter()
-            .filter(|b| b.kind() == BindType::Rank)
+            .filter(|b| matches!(b.kind(), BindType::Rank | BindType::Range))
             .count();
         let g = data
             .binds
@@ -1,7 +1,10 @@
 use rowifi_database::postgres::types::Json;
 use rowifi_framework::prelude::*;
 use rowifi_models::{
-    bind::{AssetbindBackup, Bind, BindBackup, CustombindBackup, GroupbindBackup, RankbindBackup},
+    bind::{
+        AssetbindBackup, Bind, BindBackup, CustombindBackup, GroupbindBackup, RangebindBackup,
+        RankbindBackup,
+    },
     guild::backup::{GuildBackup, GuildBackupData},
     id::{BackupId, UserId},
     user::{RoUser, UserFlags},
@@ -80,34 +83,69 @@ pub async fn backup_new(ctx: CommandContext, args: BackupArguments) -> CommandRe
                 .filter_map(|r| roles.get(r).cloned())
                 .collect();
 
+            let removed_roles = b
+                .removed_roles()
+                .iter()
+                .filter_map(|r| roles.get(r).cloned())
+                .collect();
+
             match b {
                 Bind::Rank(r) => BindBackup::Rank(RankbindBackup {
                     group_id: r.group_id,
                     group_rank_id: r.group_rank_id,
                     roblox_rank_id: r.roblox_rank_id,
                     discord_roles,
+                    removed_roles,
+                    template: r.template,
+                    fallback_templates: r.fallback_templates,
+                    priority: r.priority,
+                    active_from: r.active_from,
+                    active_until: r.active_until,
+                }),
+                Bind::Range(r) => BindBackup::Range(RangebindBackup {
+                    group_id: r.group_id,
+                    discord_roles,
+                    removed_roles,
+                    min_rank: r.min_rank,
+                    max_rank: r.max_rank,
                     template: r.template,
+                    fallback_templates: r.fallback_templates,
                     priority: r.priority,
+                    active_from: r.active_from,
+                    active_until: r.active_until,
                 }),
                 Bind::Group(g) => BindBackup::Group(GroupbindBackup {
                     group_id: g.group_id,
                     discord_roles,
+                    removed_roles,
                     template: g.template,
+                    fallback_templates: g.fallback_templates,
                     priority: g.priority,
+                    active_from: g.active_from,
+                    active_until: g.active_until,
                 }),
                 Bind::Custom(c) => BindBackup::Custom(CustombindBackup {
                     custom_bind_id: c.custom_bind_id,
-                    code: c.code,
+                    code: c.command.source(),
                     discord_roles,
+                    removed_roles,
                     template: c.template,
+                    fallback_templates: c.fallback_templates,
                     priority: c.priority,
+                    active_from: c.active_from,
+                    active_until: c.active_until,
+                    grammar_version: c.command.grammar_version,
                 }),
                 Bind::Asset(a) => BindBackup::Asset(AssetbindBackup {
                     asset_id: a.asset_id,
                     asset_type: a.asset_type,
                     discord_roles,
+                    removed_roles,
                     template: a.template,
+                    fallback_templates: a.fallback_templates,
                     priority: a.priority,
+                    active_from: a.active_from,
+                    active_until: a.active_until,
                 }),
             }
         })
@@ -125,6 +163,8 @@ pub async fn backup_new(ctx: CommandContext, args: BackupArguments) -> CommandRe
             blacklist_action: guild.blacklist_action,
             update_on_join: guild.update_on_join,
             binds,
+            default_template: guild.default_template,
+            unverified_template: guild.unverified_template,
         }),
     };
 
@@ -1,13 +1,13 @@
 use itertools::Itertools;
 use rowifi_framework::prelude::*;
 use rowifi_models::{
-    bind::{Assetbind, Bind, BindBackup, BindType, Custombind, Groupbind, Rankbind},
+    bind::{Assetbind, Bind, BindBackup, BindType, Custombind, Groupbind, Rangebind, Rankbind},
     guild::{backup::GuildBackup, GuildType, RoGuild},
     id::{BindId, RoleId},
     rolang::RoCommand,
     user::{RoUser, UserFlags},
 };
-use std::collections::HashMap;
+use std::{collections::HashMap, fmt::Write};
 
 use super::BackupArguments;
 
@@ -70,7 +70,7 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
         .0
         .binds
         .iter()
-        .flat_map(|b| b.discord_roles())
+        .flat_map(|b| b.discord_roles().iter().chain(b.removed_roles()))
         .cloned()
         .unique()
         .collect::<Vec<_>>();
@@ -124,51 +124,98 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
         .filter_map(|v| roles_map.get(v).copied())
         .collect();
 
+    let mut skipped = Vec::new();
     let binds = data
         .binds
         .into_iter()
-        .map(|b| {
+        .filter_map(|b| {
             let discord_roles = b
                 .discord_roles()
                 .iter()
                 .filter_map(|v| roles_map.get(v).copied())
                 .collect();
+            let removed_roles = b
+                .removed_roles()
+                .iter()
+                .filter_map(|v| roles_map.get(v).copied())
+                .collect();
 
-            match b {
+            let bind = match b {
                 BindBackup::Rank(r) => Bind::Rank(Rankbind {
                     bind_id: BindId::default(),
                     group_id: r.group_id,
                     group_rank_id: r.group_rank_id,
                     roblox_rank_id: r.roblox_rank_id,
                     discord_roles,
+                    removed_roles,
+                    template: r.template,
+                    fallback_templates: r.fallback_templates,
+                    priority: r.priority,
+                    active_from: r.active_from,
+                    active_until: r.active_until,
+                }),
+                BindBackup::Range(r) => Bind::Range(Rangebind {
+                    bind_id: BindId::default(),
+                    group_id: r.group_id,
+                    discord_roles,
+                    removed_roles,
+                    min_rank: r.min_rank,
+                    max_rank: r.max_rank,
                     template: r.template,
+                    fallback_templates: r.fallback_templates,
                     priority: r.priority,
+                    active_from: r.active_from,
+                    active_until: r.active_until,
                 }),
                 BindBackup::Group(g) => Bind::Group(Groupbind {
                     bind_id: BindId::default(),
                     group_id: g.group_id,
                     discord_roles,
+                    removed_roles,
                     template: g.template,
+                    fallback_templates: g.fallback_templates,
                     priority: g.priority,
+                    active_from: g.active_from,
+                    active_until: g.active_until,
                 }),
-                BindBackup::Custom(c) => Bind::Custom(Custombind {
-                    bind_id: BindId::default(),
-                    custom_bind_id: c.custom_bind_id,
-                    code: c.code.clone(),
-                    command: RoCommand::new(&c.code).unwrap(),
-                    discord_roles,
-                    template: c.template,
-                    priority: c.priority,
-                }),
+                BindBackup::Custom(c) => {
+                    // Code that no longer compiles is skipped instead of failing the whole restore
+                    let mut command = match RoCommand::parse(&c.code) {
+                        Ok(command) => command,
+                        Err(err) => {
+                            skipped.push((c.custom_bind_id, err.message));
+                            return None;
+                        }
+                    };
+                    command.grammar_version = c.grammar_version;
+                    Bind::Custom(Custombind {
+                        bind_id: BindId::default(),
+                        custom_bind_id: c.custom_bind_id,
+                        command,
+                        code: c.code,
+                        discord_roles,
+                        removed_roles,
+                        template: c.template,
+                        fallback_templates: c.fallback_templates,
+                        priority: c.priority,
+                        active_from: c.active_from,
+                        active_until: c.active_until,
+                    })
+                }
                 BindBackup::Asset(a) => Bind::Asset(Assetbind {
                     bind_id: BindId::default(),
                     asset_id: a.asset_id,
                     asset_type: a.asset_type,
                     discord_roles,
+                    removed_roles,
                     template: a.template,
+                    fallback_templates: a.fallback_templates,
                     priority: a.priority,
+                    active_from: a.active_from,
+                    active_until: a.active_until,
                 }),
-            }
+            };
+            Some(bind)
         })
         .collect::<Vec<_>>();
 
@@ -190,12 +237,14 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
         bypass_roles: Vec::new(),
         nickname_bypass_roles: Vec::new(),
         log_channel: None,
+        default_template: data.default_template,
+        unverified_template: data.unverified_template,
     };
 
     let mut db = ctx.bot.database.get().await?;
     let transaction = db.transaction().await?;
 
-    let insert_guild = transaction.prepare_cached("UPDATE guilds SET kind = $2, premium_owner = $3, command_prefix = $4, verification_roles = $5, verified_roles = $6, blacklists = $7, disabled_channels = $8, registered_groups = $9, auto_detection = $10, blacklist_action = $11, update_on_join = $12, admin_roles = $13, trainer_roles = $14, bypass_roles = $15, nickname_bypass_roles = $16 WHERE guild_id = $1").await?;
+    let insert_guild = transaction.prepare_cached("UPDATE guilds SET kind = $2, premium_owner = $3, command_prefix = $4, verification_roles = $5, verified_roles = $6, blacklists = $7, disabled_channels = $8, registered_groups = $9, auto_detection = $10, blacklist_action = $11, update_on_join = $12, admin_roles = $13, trainer_roles = $14, bypass_roles = $15, nickname_bypass_roles = $16, default_template = $17, unverified_template = $18 WHERE guild_id = $1").await?;
     transaction
         .execute(
             &insert_guild,
@@ -216,6 +265,8 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
                 &guild.trainer_roles,
                 &guild.bypass_roles,
                 &guild.nickname_bypass_roles,
+                &guild.default_template,
+                &guild.unverified_template,
             ],
         )
         .await?;
@@ -227,10 +278,11 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
         .execute(&delete_binds, &[&guild.guild_id])
         .await?;
 
-    let add_rank = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, group_rank_id, roblox_rank_id, template, priority, discord_roles) VALUES($1, $2, $3, $4, $5, $6, $7, $8)").await?;
-    let add_group = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, discord_roles, priority, template) VALUES($1, $2, $3, $4, $5, $6)").await?;
-    let add_custom = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, custom_bind_id, discord_roles, code, priority, template) VALUES($1, $2, $3, $4, $5, $6, $7)").await?;
-    let add_asset = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, asset_id, asset_type, discord_roles, priority, template) VALUES($1, $2, $3, $4, $5, $6, $7)").await?;
+    let add_rank = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, group_rank_id, roblox_rank_id, template, priority, discord_roles, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)").await?;
+    let add_range = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, min_rank, max_rank, template, priority, discord_roles, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)").await?;
+    let add_group = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, discord_roles, priority, template, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)").await?;
+    let add_custom = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, custom_bind_id, discord_roles, code, priority, template, removed_roles, active_from, active_until, fallback_templates, grammar_version) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)").await?;
+    let add_asset = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, asset_id, asset_type, discord_roles, priority, template, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)").await?;
     for bind in binds {
         match bind {
             Bind::Rank(r) => {
@@ -246,6 +298,31 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
                             &r.template,
                             &r.priority,
                             &r.discord_roles,
+                            &r.removed_roles,
+                            &r.active_from,
+                            &r.active_until,
+                            &r.fallback_templates,
+                        ],
+                    )
+                    .await?
+            }
+            Bind::Range(r) => {
+                transaction
+                    .execute(
+                        &add_range,
+                        &[
+                            &BindType::Range,
+                            &guild.guild_id,
+                            &r.group_id,
+                            &r.min_rank,
+                            &r.max_rank,
+                            &r.template,
+                            &r.priority,
+                            &r.discord_roles,
+                            &r.removed_roles,
+                            &r.active_from,
+                            &r.active_until,
+                            &r.fallback_templates,
                         ],
                     )
                     .await?
@@ -261,6 +338,10 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
                             &g.discord_roles,
                             &g.priority,
                             &g.template,
+                            &g.removed_roles,
+                            &g.active_from,
+                            &g.active_until,
+                            &g.fallback_templates,
                         ],
                     )
                     .await?
@@ -277,6 +358,11 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
                             &c.code,
                             &c.priority,
                             &c.template,
+                            &c.removed_roles,
+                            &c.active_from,
+                            &c.active_until,
+                            &c.fallback_templates,
+                            &c.command.grammar_version,
                         ],
                     )
                     .await?
@@ -293,6 +379,10 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
                             &a.discord_roles,
                             &a.priority,
                             &a.template,
+                            &a.removed_roles,
+                            &a.active_from,
+                            &a.active_until,
+                            &a.fallback_templates,
                         ],
                     )
                     .await?
@@ -309,9 +399,14 @@ pub async fn backup_restore(ctx: CommandContext, args: BackupArguments) -> Comma
     ctx.bot.log_channels.remove(&guild_id);
     ctx.bot.prefixes.insert(guild_id, guild.command_prefix);
 
-    ctx.respond()
-        .content("Backup successfully restored")?
-        .exec()
-        .await?;
+    let mut content = "Backup successfully restored".to_string();
+    if !skipped.is_empty() {
+        content.push_str("\nThese custombinds were not restored since their code is invalid:");
+        for (id, message) in skipped {
+            let _ = write!(content, "\n- `{id}`: {message}");
+        }
+    }
+    let content = content.chars().take(2000).collect::<String>();
+    ctx.respond().content(&content)?.exec().await?;
     Ok(())
 }
@@ -0,0 +1,97 @@
+use itertools::Itertools;
+use rowifi_framework::{constants::EMBED_FIELD_DESCRIPTION_LIMIT, prelude::*};
+use rowifi_models::{
+    bind::{lint_binds, Bind, LintInput},
+    roblox::id::GroupId,
+};
+use std::collections::HashMap;
+
+pub async fn binds_lint(ctx: CommandContext) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let binds = ctx
+        .bot
+        .database
+        .query::<Bind>("SELECT * FROM binds WHERE guild_id = $1", &[&(guild_id)])
+        .await?;
+    if binds.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Bind Lint Failed")
+            .description("No binds were found associated with this server")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let group_ids = binds
+        .iter()
+        .filter_map(|b| match b {
+            Bind::Rank(r) => Some(r.group_id),
+            Bind::Range(r) => Some(r.group_id),
+            _ => None,
+        })
+        .unique()
+        .collect::<Vec<_>>();
+    let mut group_ranks = HashMap::new();
+    for group_id in group_ids {
+        // A group that no longer exists has none of the ranks bound to it
+        let ranks = match ctx
+            .bot
+            .roblox
+            .get_group_ranks(GroupId(group_id as u64))
+            .await?
+        {
+            Some(group) => group.roles.iter().map(|r| i64::from(r.rank)).collect(),
+            None => Vec::new(),
+        };
+        group_ranks.insert(group_id, ranks);
+    }
+
+    let guild_roles = ctx.bot.cache.roles(guild_id);
+    let macros = ctx.bot.database.get_macros(guild_id).await?;
+    let lints = lint_binds(&LintInput {
+        binds: &binds,
+        guild_roles: &guild_roles,
+        group_ranks: &group_ranks,
+        macros: &macros,
+    });
+
+    if lints.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::DarkGreen as u32)
+            .title("Bind Lint")
+            .description("No problems were found with the binds of this server")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let mut pages = Vec::new();
+    let mut page_count = 0;
+    for lints in &lints.iter().chunks(12) {
+        let mut embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Bind Lint")
+            .description(format!("Page {}", page_count + 1));
+        for lint in lints {
+            let mut desc = lint.to_string();
+            if desc.chars().count() > EMBED_FIELD_DESCRIPTION_LIMIT {
+                desc = desc
+                    .chars()
+                    .take(EMBED_FIELD_DESCRIPTION_LIMIT - 3)
+                    .collect::<String>();
+                desc.push_str("...");
+            }
+            embed = embed.field(EmbedFieldBuilder::new(lint.title(), desc).build());
+        }
+        pages.push(embed.build()?);
+        page_count += 1;
+    }
+    paginate_embed(&ctx, pages, page_count).await?;
+    Ok(())
+}
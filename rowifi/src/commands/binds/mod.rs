@@ -0,0 +1,22 @@
+mod lint;
+
+use rowifi_framework::prelude::*;
+
+use lint::binds_lint;
+
+pub fn binds_config(cmds: &mut Vec<Command>) {
+    let binds_lint_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["lint"])
+        .description("Command to check the binds of the server for conflicts & mistakes")
+        .handler(binds_lint);
+
+    let binds_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["binds"])
+        .description("Module to interact with all the binds of the server")
+        .group("Binds")
+        .sub_command(binds_lint_cmd)
+        .no_handler();
+    cmds.push(binds_cmd);
+}
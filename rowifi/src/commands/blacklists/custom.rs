@@ -7,6 +7,8 @@ use rowifi_models::{
 };
 use std::collections::HashMap;
 
+use crate::utils::{fetch_account_ranks, fetch_owned_assets};
+
 #[derive(FromArgs)]
 pub struct BlacklistCustomArguments {
     #[arg(help = "Code to use in the blacklist", rest)]
@@ -63,18 +65,40 @@ pub async fn blacklist_custom(
         .collect::<HashMap<_, _>>();
     let roblox_user = ctx.bot.roblox.get_user(user_id, false).await?;
 
+    let macros = ctx.bot.database.get_macros(guild_id).await?;
+    let command = match RoCommand::new(&code, &macros) {
+        Ok(c) => c,
+        Err(err) => {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Custom Blacklist Addition Failed")
+                .description(err.render(&code))
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    };
+    let owned_assets = fetch_owned_assets(&ctx.bot.roblox, user_id, command.assets()).await?;
+    let profile = if command.uses_profile() {
+        Some(ctx.bot.roblox.get_user_profile(user_id).await?)
+    } else {
+        None
+    };
+    let accounts = if command.uses_accounts() {
+        fetch_account_ranks(&ctx.bot.database, &ctx.bot.roblox, &user, &ranks).await?
+    } else {
+        HashMap::new()
+    };
     let command_user = RoCommandUser {
         user: &user,
         roles: &member.roles,
         ranks: &ranks,
         username: &roblox_user.name,
-    };
-    let command = match RoCommand::new(&code) {
-        Ok(c) => c,
-        Err(s) => {
-            ctx.respond().content(&s)?.exec().await?;
-            return Ok(());
-        }
+        owned_assets: &owned_assets,
+        profile: profile.as_ref(),
+        accounts: &accounts,
     };
     if let Err(res) = command.evaluate(&command_user) {
         ctx.respond().content(&res)?.exec().await?;
@@ -7,6 +7,8 @@ use itertools::Itertools;
 use rowifi_framework::prelude::*;
 use rowifi_models::blacklist::BlacklistData;
 
+use crate::commands::PRECEDENCE_WARNING;
+
 pub use custom::blacklist_custom;
 pub use delete::blacklist_delete;
 pub use group::blacklist_group;
@@ -96,7 +98,13 @@ pub async fn blacklist(ctx: CommandContext) -> CommandResult {
                     bl.reason
                 ),
                 BlacklistData::Custom(code) => {
-                    format!("Type: {}\nCode: {}\nReason: {}", bl.kind(), code, bl.reason)
+                    let mut desc =
+                        format!("Type: {}\nCode: {}\nReason: {}", bl.kind(), code, bl.reason);
+                    if code.precedence_changed() {
+                        desc.push('\n');
+                        desc.push_str(PRECEDENCE_WARNING);
+                    }
+                    desc
                 }
             };
             embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
@@ -138,8 +138,8 @@ pub async fn custombinds_delete(
                     let mut db = ctx.bot.database.get().await?;
                     let transaction = db.transaction().await?;
                     let statement = transaction.prepare_cached(r#"
-                        INSERT INTO binds(bind_type, guild_id, custom_bind_id, discord_roles, code, priority, template) 
-                        VALUES($1, $2, (SELECT COALESCE(max(custom_bind_id) + 1, 1) FROM binds WHERE guild_id = $2 AND bind_type = $1), $3, $4, $5, $6)
+                        INSERT INTO binds(bind_type, guild_id, custom_bind_id, discord_roles, code, priority, template, removed_roles, active_from, active_until, fallback_templates, grammar_version) 
+                        VALUES($1, $2, (SELECT COALESCE(max(custom_bind_id) + 1, 1) FROM binds WHERE guild_id = $2 AND bind_type = $1), $3, $4, $5, $6, $7, $8, $9, $10, $11)
                     "#).await?;
                     for bind in binds_to_delete {
                         transaction
@@ -152,6 +152,11 @@ pub async fn custombinds_delete(
                                     &bind.code,
                                     &bind.priority,
                                     &bind.template,
+                                    &bind.removed_roles,
+                                    &bind.active_from,
+                                    &bind.active_until,
+                                    &bind.fallback_templates,
+                                    &bind.command.grammar_version,
                                 ],
                             )
                             .await?;
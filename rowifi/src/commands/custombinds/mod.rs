@@ -1,18 +1,29 @@
 mod delete;
 mod modify;
+mod test;
 
 pub mod new;
 
+use chrono::{DateTime, Utc};
 use itertools::Itertools;
 use rowifi_framework::{constants::EMBED_FIELD_DESCRIPTION_LIMIT, prelude::*};
 use rowifi_models::bind::{BindType, Custombind};
+use std::fmt::Write;
 use twilight_http::request::AttachmentFile;
 
+use crate::utils::{active_window, fallbacks_str};
+
 use delete::custombinds_delete;
 use modify::{
-    cb_add_roles, cb_modify_code, cb_modify_priority, cb_modify_template, cb_remove_roles,
+    cb_add_roles, cb_modify_active, cb_modify_code, cb_modify_fallbacks, cb_modify_priority,
+    cb_modify_template, cb_remove_roles, cb_removed_roles,
 };
 use new::custombinds_new;
+use test::custombinds_test;
+
+/// Shown next to saved code that is parsed differently since the precedence update
+pub const PRECEDENCE_WARNING: &str =
+    "⚠️ This code is evaluated differently since the `and`/`or` precedence update. Add brackets to restore the old meaning, or save the code again to keep the new one.";
 
 pub fn custombinds_config(cmds: &mut Vec<Command>) {
     let custombinds_view_cmd = Command::builder()
@@ -57,6 +68,26 @@ pub fn custombinds_config(cmds: &mut Vec<Command>) {
         .description("Command to remove roles from a custombind")
         .handler(cb_remove_roles);
 
+    let custombinds_removed_roles_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["removed-roles"])
+        .description("Command to set the roles removed from members matching a custombind")
+        .handler(cb_removed_roles);
+
+    let custombinds_modify_active_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["active"])
+        .description("Command to set the time window during which a custombind applies")
+        .handler(cb_modify_active);
+
+    let custombinds_modify_fallbacks_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["fallbacks"])
+        .description(
+            "Command to set the templates tried when the nickname of a custombind is too long",
+        )
+        .handler(cb_modify_fallbacks);
+
     let custombinds_modify_cmd = Command::builder()
         .level(RoLevel::Admin)
         .names(&["modify", "m"])
@@ -66,6 +97,9 @@ pub fn custombinds_config(cmds: &mut Vec<Command>) {
         .sub_command(custombinds_modify_code_cmd)
         .sub_command(custombinds_add_roles_cmd)
         .sub_command(custombinds_remove_roles_cmd)
+        .sub_command(custombinds_removed_roles_cmd)
+        .sub_command(custombinds_modify_active_cmd)
+        .sub_command(custombinds_modify_fallbacks_cmd)
         .no_handler();
 
     let custombinds_new_cmd = Command::builder()
@@ -74,6 +108,12 @@ pub fn custombinds_config(cmds: &mut Vec<Command>) {
         .description("Command to create a custombind")
         .handler(custombinds_new);
 
+    let custombinds_test_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["test"])
+        .description("Command to evaluate a custombind against a member without updating them")
+        .handler(custombinds_test);
+
     let custombinds_cmd = Command::builder()
         .level(RoLevel::Admin)
         .names(&["custombinds", "cb"])
@@ -83,6 +123,7 @@ pub fn custombinds_config(cmds: &mut Vec<Command>) {
         .sub_command(custombinds_delete_cmd)
         .sub_command(custombinds_modify_cmd)
         .sub_command(custombinds_new_cmd)
+        .sub_command(custombinds_test_cmd)
         .handler(custombinds_view);
     cmds.push(custombinds_cmd);
 }
@@ -112,7 +153,7 @@ pub async fn custombinds_view(ctx: CommandContext) -> CommandResult {
 
     let text_file_mode = custombinds
         .iter()
-        .any(|c| c.code.len() >= EMBED_FIELD_DESCRIPTION_LIMIT);
+        .any(|c| c.command.source().len() >= EMBED_FIELD_DESCRIPTION_LIMIT);
 
     if text_file_mode {
         let mut text = String::new();
@@ -123,9 +164,35 @@ pub async fn custombinds_view(ctx: CommandContext) -> CommandResult {
                 .map(|r| format!("<@&{}> ", r))
                 .collect::<String>();
             text.push_str(&format!(
-                "Bind Id: {}\nCode: {}\nTemplate: {}\nPriority: {}\nDiscord Roles: {}\n\n",
-                cb.custom_bind_id, cb.code, cb.template, cb.priority, roles_str
+                "Bind Id: {}\nCode: {}\nTemplate: {}\nPriority: {}\nDiscord Roles: {}\n",
+                cb.custom_bind_id,
+                cb.command.source(),
+                cb.template,
+                cb.priority,
+                roles_str
             ));
+            if cb.active_from.is_some() || cb.active_until.is_some() {
+                let time_str = |time: Option<DateTime<Utc>>| {
+                    time.map_or_else(
+                        || "N/A".to_string(),
+                        |t| t.format("%Y-%m-%d %H:%M UTC").to_string(),
+                    )
+                };
+                let _ = write!(
+                    text,
+                    "Active From: {}\nActive Until: {}\n",
+                    time_str(cb.active_from),
+                    time_str(cb.active_until)
+                );
+            }
+            if !cb.fallback_templates.is_empty() {
+                let _ = writeln!(text, "Fallbacks: {}", fallbacks_str(&cb.fallback_templates));
+            }
+            if cb.command.precedence_changed() {
+                text.push_str(PRECEDENCE_WARNING);
+                text.push('\n');
+            }
+            text.push('\n');
         }
 
         ctx.respond()
@@ -138,27 +205,68 @@ pub async fn custombinds_view(ctx: CommandContext) -> CommandResult {
     } else {
         let mut pages = Vec::new();
         let mut page_count = 0;
-        for cbs in &custombinds.iter().chunks(12) {
-            let mut embed = EmbedBuilder::new()
-                .default_data()
-                .title("Custombinds")
-                .description(format!("Page {}", page_count + 1));
-            let cbs = cbs.sorted_by_key(|c| c.custom_bind_id);
-            for cb in cbs {
-                let name = format!("Bind Id: {}\n", cb.custom_bind_id);
-                let roles_str = cb
-                    .discord_roles
-                    .iter()
-                    .map(|r| format!("<@&{}> ", r))
-                    .collect::<String>();
-                let desc = format!(
-                    "Code: {}\nTemplate: {}\nPriority: {}\nRoles: {}",
-                    cb.code, cb.template, cb.priority, roles_str
-                );
-                embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+        let now = Utc::now();
+        let (expired, custombinds): (Vec<_>, Vec<_>) = custombinds
+            .into_iter()
+            .partition(|cb| matches!(cb.active_until, Some(until) if until <= now));
+
+        for (title, custombinds) in [
+            ("Custombinds", custombinds),
+            ("Expired Custombinds", expired),
+        ] {
+            for cbs in &custombinds.iter().chunks(12) {
+                let mut embed = EmbedBuilder::new()
+                    .default_data()
+                    .title(title)
+                    .description(format!("Page {}", page_count + 1));
+                let cbs = cbs.sorted_by_key(|c| c.custom_bind_id);
+                for cb in cbs {
+                    let name = format!("Bind Id: {}\n", cb.custom_bind_id);
+                    let roles_str = cb
+                        .discord_roles
+                        .iter()
+                        .map(|r| format!("<@&{r}>"))
+                        .join(" ");
+                    let mut desc = format!(
+                        "Code: {}\nTemplate: {}\nPriority: {}\nRoles: {}",
+                        cb.command.source(),
+                        cb.template,
+                        cb.priority,
+                        roles_str
+                    );
+                    if !cb.removed_roles.is_empty() {
+                        let _ = write!(
+                            desc,
+                            "\nRemoved Roles: {}",
+                            cb.removed_roles
+                                .iter()
+                                .map(|r| format!("<@&{r}>"))
+                                .join(" ")
+                        );
+                    }
+                    if cb.active_from.is_some() || cb.active_until.is_some() {
+                        let _ = write!(
+                            desc,
+                            "\nActive: {}",
+                            active_window(cb.active_from, cb.active_until)
+                        );
+                    }
+                    if !cb.fallback_templates.is_empty() {
+                        let _ = write!(
+                            desc,
+                            "\nFallbacks: {}",
+                            fallbacks_str(&cb.fallback_templates)
+                        );
+                    }
+                    if cb.command.precedence_changed() {
+                        desc.push('\n');
+                        desc.push_str(PRECEDENCE_WARNING);
+                    }
+                    embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+                }
+                pages.push(embed.build()?);
+                page_count += 1;
             }
-            pages.push(embed.build()?);
-            page_count += 1;
         }
         paginate_embed(&ctx, pages, page_count).await?;
     }
@@ -8,6 +8,11 @@ use rowifi_models::{
 };
 use std::collections::HashMap;
 
+use crate::utils::{
+    active_window, check_manageable, fallbacks_str, fetch_account_ranks, fetch_owned_assets,
+    parse_fallbacks, ActiveTime,
+};
+
 #[derive(FromArgs)]
 pub struct ModifyCode {
     #[arg(help = "The ID of the bind")]
@@ -80,18 +85,40 @@ pub async fn cb_modify_code(ctx: CommandContext, args: ModifyCode) -> CommandRes
         .collect::<HashMap<_, _>>();
     let roblox_user = ctx.bot.roblox.get_user(user_id, false).await?;
 
+    let macros = ctx.bot.database.get_macros(guild_id).await?;
+    let command = match RoCommand::new(&code, &macros) {
+        Ok(c) => c,
+        Err(err) => {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Custom Bind Modification Failed")
+                .description(err.render(&code))
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    };
+    let owned_assets = fetch_owned_assets(&ctx.bot.roblox, user_id, command.assets()).await?;
+    let profile = if command.uses_profile() {
+        Some(ctx.bot.roblox.get_user_profile(user_id).await?)
+    } else {
+        None
+    };
+    let accounts = if command.uses_accounts() {
+        fetch_account_ranks(&ctx.bot.database, &ctx.bot.roblox, &user, &ranks).await?
+    } else {
+        HashMap::new()
+    };
     let command_user = RoCommandUser {
         user: &user,
         roles: &member.roles,
         ranks: &ranks,
         username: &roblox_user.name,
-    };
-    let command = match RoCommand::new(&code) {
-        Ok(c) => c,
-        Err(s) => {
-            ctx.respond().content(&s)?.exec().await?;
-            return Ok(());
-        }
+        owned_assets: &owned_assets,
+        profile: profile.as_ref(),
+        accounts: &accounts,
     };
     if let Err(res) = command.evaluate(&command_user) {
         ctx.respond().content(&res)?.exec().await?;
@@ -100,8 +127,8 @@ pub async fn cb_modify_code(ctx: CommandContext, args: ModifyCode) -> CommandRes
     ctx.bot
         .database
         .execute(
-            "UPDATE binds SET code = $1 WHERE bind_id = $2",
-            &[&code, &bind.bind_id],
+            "UPDATE binds SET code = $1, grammar_version = $2 WHERE bind_id = $3",
+            &[&code, &command.grammar_version, &bind.bind_id],
         )
         .await?;
 
@@ -280,6 +307,10 @@ pub async fn cb_add_roles(ctx: CommandContext, args: AddRoles) -> CommandResult
         }
     };
 
+    if !check_manageable(&ctx, "Custom Bind Modification Failed", &role_ids).await? {
+        return Ok(());
+    }
+
     ctx.bot.database.execute("UPDATE binds SET discord_roles = array_cat(discord_roles, $1::BIGINT[]) WHERE bind_id = $2", &[&role_ids, &bind.bind_id]).await?;
 
     let modification = role_ids
@@ -361,6 +392,223 @@ pub async fn cb_remove_roles(ctx: CommandContext, args: RemoveRoles) -> CommandR
     cb_reply_log(ctx, name, desc).await
 }
 
+#[derive(FromArgs)]
+pub struct RemovedRoles {
+    #[arg(help = "The ID of the bind")]
+    pub id: i32,
+    #[arg(
+        help = "The roles to remove from members matching the bind. Leave empty to clear",
+        rest
+    )]
+    pub roles: Option<String>,
+}
+
+pub async fn cb_removed_roles(ctx: CommandContext, args: RemovedRoles) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let id_to_modify = args.id;
+
+    let mut role_ids = Vec::new();
+    for r in args.roles.unwrap_or_default().split_ascii_whitespace() {
+        if let Some(resolved) = &ctx.resolved {
+            role_ids.extend(resolved.roles.iter().map(|r| RoleId(*r.0)));
+        } else if let Some(r) = parse_role(r) {
+            role_ids.push(r);
+        }
+    }
+    role_ids = role_ids.into_iter().unique().collect::<Vec<_>>();
+
+    let custombinds = ctx
+        .bot
+        .database
+        .query::<Custombind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY custom_bind_id",
+            &[&(guild_id), &BindType::Custom],
+        )
+        .await?;
+
+    let Some(bind) = custombinds
+        .iter()
+        .find(|c| c.custom_bind_id == id_to_modify)
+    else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Modification Failed")
+            .description(format!("There was no bind found with id {id_to_modify}"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET removed_roles = $1 WHERE bind_id = $2",
+            &[&role_ids, &bind.bind_id],
+        )
+        .await?;
+
+    let roles_str = |roles: &[RoleId]| {
+        if roles.is_empty() {
+            "None".to_string()
+        } else {
+            roles.iter().map(|r| format!("<@&{r}>")).join(" ")
+        }
+    };
+    let name = format!("Id: {id_to_modify}");
+    let desc = format!(
+        "`Removed Roles`: {} -> {}",
+        roles_str(&bind.removed_roles),
+        roles_str(&role_ids)
+    );
+
+    cb_reply_log(ctx, name, desc).await
+}
+
+#[derive(FromArgs)]
+pub struct ModifyActive {
+    #[arg(help = "The ID of the bind")]
+    pub id: i32,
+    #[arg(help = "When the bind starts applying. Use `none` to apply right away")]
+    pub active_from: ActiveTime,
+    #[arg(help = "When the bind stops applying. Use `none` to never expire")]
+    pub active_until: ActiveTime,
+}
+
+pub async fn cb_modify_active(ctx: CommandContext, args: ModifyActive) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let id_to_modify = args.id;
+    let active_from = args.active_from.0;
+    let active_until = args.active_until.0;
+
+    if let (Some(from), Some(until)) = (active_from, active_until) {
+        if from >= until {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Custom Bind Modification Failed")
+                .description("The bind must become active before it expires")
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    }
+
+    let custombinds = ctx
+        .bot
+        .database
+        .query::<Custombind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY custom_bind_id",
+            &[&(guild_id), &BindType::Custom],
+        )
+        .await?;
+
+    let Some(bind) = custombinds
+        .iter()
+        .find(|c| c.custom_bind_id == id_to_modify)
+    else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Modification Failed")
+            .description(format!("There was no bind found with id {id_to_modify}"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET active_from = $1, active_until = $2 WHERE bind_id = $3",
+            &[&active_from, &active_until, &bind.bind_id],
+        )
+        .await?;
+
+    let name = format!("Id: {id_to_modify}");
+    let desc = format!(
+        "`Active`: {} -> {}",
+        active_window(bind.active_from, bind.active_until),
+        active_window(active_from, active_until)
+    );
+
+    cb_reply_log(ctx, name, desc).await
+}
+
+#[derive(FromArgs)]
+pub struct ModifyFallbacks {
+    #[arg(help = "The ID of the bind")]
+    pub id: i32,
+    #[arg(
+        help = "The templates to try in order when the nickname is too long, separated by `|`. Use `none` to clear them",
+        rest
+    )]
+    pub fallbacks: String,
+}
+
+pub async fn cb_modify_fallbacks(ctx: CommandContext, args: ModifyFallbacks) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let id_to_modify = args.id;
+    let fallbacks = args.fallbacks;
+
+    if fallbacks.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Modification Failed")
+            .description("You have entered no fallback templates. Use `none` to clear them")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+    let fallbacks = parse_fallbacks(&fallbacks);
+
+    let custombinds = ctx
+        .bot
+        .database
+        .query::<Custombind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY custom_bind_id",
+            &[&(guild_id), &BindType::Custom],
+        )
+        .await?;
+
+    let Some(bind) = custombinds
+        .iter()
+        .find(|c| c.custom_bind_id == id_to_modify)
+    else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Modification Failed")
+            .description(format!("There was no bind found with id {id_to_modify}"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET fallback_templates = $1 WHERE bind_id = $2",
+            &[&fallbacks, &bind.bind_id],
+        )
+        .await?;
+
+    let name = format!("Id: {id_to_modify}");
+    let desc = format!(
+        "`Fallbacks`: {} -> {}",
+        fallbacks_str(&bind.fallback_templates),
+        fallbacks_str(&fallbacks)
+    );
+
+    cb_reply_log(ctx, name, desc).await
+}
+
 async fn cb_reply_log(ctx: CommandContext, name: String, desc: String) -> CommandResult {
     let guild_id = ctx.guild_id.unwrap();
     let embed = EmbedBuilder::new()
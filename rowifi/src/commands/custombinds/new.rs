@@ -9,6 +9,8 @@ use rowifi_models::{
 };
 use std::collections::HashMap;
 
+use crate::utils::{check_manageable, fetch_account_ranks, fetch_owned_assets};
+
 #[allow(clippy::option_option)]
 pub struct CustombindsNewArguments {
     pub code: String,
@@ -171,18 +173,40 @@ pub async fn custombinds_new_common(
         .collect::<HashMap<_, _>>();
     let roblox_user = ctx.bot.roblox.get_user(user_id, false).await?;
 
+    let macros = ctx.bot.database.get_macros(guild_id).await?;
+    let command = match RoCommand::new(&args.code, &macros) {
+        Ok(c) => c,
+        Err(err) => {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Custom Bind Addition Failed")
+                .description(err.render(&args.code))
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    };
+    let owned_assets = fetch_owned_assets(&ctx.bot.roblox, user_id, command.assets()).await?;
+    let profile = if command.uses_profile() {
+        Some(ctx.bot.roblox.get_user_profile(user_id).await?)
+    } else {
+        None
+    };
+    let accounts = if command.uses_accounts() {
+        fetch_account_ranks(&ctx.bot.database, &ctx.bot.roblox, &user, &ranks).await?
+    } else {
+        HashMap::new()
+    };
     let command_user = RoCommandUser {
         user: &user,
         roles: &member.roles,
         ranks: &ranks,
         username: &roblox_user.name,
-    };
-    let command = match RoCommand::new(&args.code) {
-        Ok(c) => c,
-        Err(s) => {
-            ctx.respond().content(&s)?.exec().await?;
-            return Ok(());
-        }
+        owned_assets: &owned_assets,
+        profile: profile.as_ref(),
+        accounts: &accounts,
     };
     if let Err(res) = command.evaluate(&command_user) {
         ctx.respond().content(&res)?.exec().await?;
@@ -233,6 +257,16 @@ pub async fn custombinds_new_common(
                         label: "{display-name}".into(),
                         value: "{display-name}".into(),
                     },
+                    SelectMenuOption {
+                        default: false,
+                        description: Some(
+                            "Sets the nickname as the display name on Roblox, or the username if there is none"
+                                .into(),
+                        ),
+                        emoji: None,
+                        label: "{smart-name}".into(),
+                        value: "{smart-name}".into(),
+                    },
                 ],
                 placeholder: None,
             };
@@ -284,6 +318,10 @@ pub async fn custombinds_new_common(
         }
     }
 
+    if !check_manageable(&ctx, "Custom Bind Addition Failed", &roles).await? {
+        return Ok(());
+    }
+
     let bind = Custombind {
         // default is entered here since this field is not used in inserting the bind. The struct is only created for thr purpose
         // of ensuring all fields are collected.
@@ -293,14 +331,18 @@ pub async fn custombinds_new_common(
         priority,
         command,
         discord_roles: roles.into_iter().unique().collect::<Vec<_>>(),
+        removed_roles: Vec::new(),
+        active_from: None,
+        active_until: None,
+        fallback_templates: Vec::new(),
         template,
     };
 
     let row = ctx.bot.database.query_one::<Row>(r#"
-        INSERT INTO binds(bind_type, guild_id, custom_bind_id, discord_roles, code, priority, template) 
-        VALUES($1, $2, (SELECT COALESCE(max(custom_bind_id) + 1, 1) FROM binds WHERE guild_id = $2 AND bind_type = $1), $3, $4, $5, $6)
+        INSERT INTO binds(bind_type, guild_id, custom_bind_id, discord_roles, code, priority, template, grammar_version) 
+        VALUES($1, $2, (SELECT COALESCE(max(custom_bind_id) + 1, 1) FROM binds WHERE guild_id = $2 AND bind_type = $1), $3, $4, $5, $6, $7)
         RETURNING custom_bind_id, bind_id"#,
-     &[&BindType::Custom, &(guild_id), &bind.discord_roles, &bind.code, &bind.priority, &bind.template]
+     &[&BindType::Custom, &(guild_id), &bind.discord_roles, &bind.code, &bind.priority, &bind.template, &bind.command.grammar_version]
     ).await?;
     let bind_id: BindId = row.get("bind_id");
 
@@ -0,0 +1,150 @@
+use rowifi_framework::{constants::EMBED_DESCRIPTION_LIMIT, prelude::*};
+use rowifi_models::{
+    bind::{BindType, Custombind},
+    id::UserId,
+    roblox::id::UserId as RobloxUserId,
+    rolang::RoCommandUser,
+};
+use std::collections::HashMap;
+
+use super::PRECEDENCE_WARNING;
+use crate::utils::{fetch_account_ranks, fetch_owned_assets};
+
+#[derive(FromArgs)]
+pub struct CustombindsTestArguments {
+    #[arg(help = "The ID of the custombind to test")]
+    pub id: i32,
+    #[arg(help = "The member to test the custombind against")]
+    pub user_id: UserId,
+}
+
+pub async fn custombinds_test(
+    ctx: CommandContext,
+    args: CustombindsTestArguments,
+) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let custombinds = ctx
+        .bot
+        .database
+        .query::<Custombind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY custom_bind_id",
+            &[&(guild_id), &BindType::Custom],
+        )
+        .await?;
+    let Some(bind) = custombinds.iter().find(|c| c.custom_bind_id == args.id) else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Test Failed")
+            .description(format!("There was no bind found with id {}", args.id))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    let Some(member) = ctx.member(guild_id, args.user_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Test Failed")
+            .description("No such member was found")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+    let Some(user) = ctx
+        .bot
+        .database
+        .get_linked_user(args.user_id, guild_id)
+        .await?
+    else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Custom Bind Test Failed")
+            .description("User was not verified. Please ask them to verify themselves")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    let mut command = bind.command.clone();
+    if command.calls_macros() {
+        let macros = ctx.bot.database.get_macros(guild_id).await?;
+        if let Err(err) = command.resolve(&macros) {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Custom Bind Test Failed")
+                .description(format!("The bind could not be evaluated: {err}"))
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    }
+
+    let user_id = RobloxUserId(user.roblox_id as u64);
+    let ranks = ctx
+        .bot
+        .roblox
+        .get_user_roles(user_id)
+        .await?
+        .iter()
+        .map(|r| (r.group.id.0 as i64, i64::from(r.role.rank)))
+        .collect::<HashMap<_, _>>();
+    let roblox_user = ctx.bot.roblox.get_user(user_id, false).await?;
+    let owned_assets = fetch_owned_assets(&ctx.bot.roblox, user_id, command.assets()).await?;
+    let profile = if command.uses_profile() {
+        Some(ctx.bot.roblox.get_user_profile(user_id).await?)
+    } else {
+        None
+    };
+    let accounts = if command.uses_accounts() {
+        fetch_account_ranks(&ctx.bot.database, &ctx.bot.roblox, &user, &ranks).await?
+    } else {
+        HashMap::new()
+    };
+    let command_user = RoCommandUser {
+        user: &user,
+        roles: &member.roles,
+        ranks: &ranks,
+        username: &roblox_user.name,
+        owned_assets: &owned_assets,
+        profile: profile.as_ref(),
+        accounts: &accounts,
+    };
+
+    let explanation = command.explain(&command_user);
+    let outcome = match command.evaluate(&command_user) {
+        Ok(true) => "The bind applies to this member".to_string(),
+        Ok(false) => "The bind does not apply to this member".to_string(),
+        Err(err) => format!("The bind could not be evaluated: {err}"),
+    };
+
+    let mut desc = format!(
+        "{outcome}. No roles or nicknames were changed.\n```\n{}```",
+        explanation.render()
+    );
+    if desc.chars().count() > EMBED_DESCRIPTION_LIMIT {
+        desc = desc
+            .chars()
+            .take(EMBED_DESCRIPTION_LIMIT - 4)
+            .collect::<String>();
+        desc.push_str("\n```");
+    }
+    let mut embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::Blue as u32)
+        .title(format!("Custom Bind {} Test", bind.custom_bind_id))
+        .description(desc);
+    if command.precedence_changed() {
+        embed = embed.field(EmbedFieldBuilder::new("Warning", PRECEDENCE_WARNING).build());
+    }
+    ctx.respond().embeds(&[embed.build()?])?.exec().await?;
+
+    Ok(())
+}
@@ -9,8 +9,11 @@ use rowifi_models::{
 };
 use std::str::FromStr;
 
-use crate::commands::{
-    custombinds::new::custombinds_new_common, log_rankbind, new::CustombindsNewArguments,
+use crate::{
+    commands::{
+        custombinds::new::custombinds_new_common, log_rankbind, new::CustombindsNewArguments,
+    },
+    utils::check_manageable,
 };
 
 lazy_static! {
@@ -335,6 +338,16 @@ async fn bind_asset(ctx: CommandContext, guild_id: GuildId) -> CommandResult {
                 label: "{display-name}".into(),
                 value: "{display-name}".into(),
             },
+            SelectMenuOption {
+                default: false,
+                description: Some(
+                    "Sets the nickname as the display name on Roblox, or the username if there is none"
+                        .into(),
+                ),
+                emoji: None,
+                label: "{smart-name}".into(),
+                value: "{smart-name}".into(),
+            },
         ],
         placeholder: None,
     };
@@ -374,6 +387,10 @@ async fn bind_asset(ctx: CommandContext, guild_id: GuildId) -> CommandResult {
         }
     }
 
+    if !check_manageable(&ctx, "Asset Bind Addition Failed", &discord_roles).await? {
+        return Ok(());
+    }
+
     let bind = Assetbind {
         // default is entered here since this field is not used in the insertion. The struct is only constructed to ensure we have
         // collected all fields.
@@ -381,6 +398,10 @@ async fn bind_asset(ctx: CommandContext, guild_id: GuildId) -> CommandResult {
         asset_id,
         asset_type,
         discord_roles: discord_roles.into_iter().unique().collect::<Vec<_>>(),
+        removed_roles: Vec::new(),
+        active_from: None,
+        active_until: None,
+        fallback_templates: Vec::new(),
         priority,
         template: template.clone(),
     };
@@ -543,6 +564,16 @@ async fn bind_group(ctx: CommandContext, guild_id: GuildId) -> CommandResult {
                 label: "{display-name}".into(),
                 value: "{display-name}".into(),
             },
+            SelectMenuOption {
+                default: false,
+                description: Some(
+                    "Sets the nickname as the display name on Roblox, or the username if there is none"
+                        .into(),
+                ),
+                emoji: None,
+                label: "{smart-name}".into(),
+                value: "{smart-name}".into(),
+            },
             SelectMenuOption {
                 default: false,
                 description: Some("Let RoWifi determine the template from the ranks".into()),
@@ -590,6 +621,9 @@ async fn bind_group(ctx: CommandContext, guild_id: GuildId) -> CommandResult {
         }
     }
     discord_roles = discord_roles.into_iter().unique().collect();
+    if !check_manageable(&ctx, "Bind Addition Failed", &discord_roles).await? {
+        return Ok(());
+    }
 
     let should_groupbind =
         rank_ids.len() == roblox_group.roles.len() - 1 && rank_ids.iter().any(|r| r.rank != 0);
@@ -612,6 +646,10 @@ async fn bind_group(ctx: CommandContext, guild_id: GuildId) -> CommandResult {
         bind_id: BindId::default(),
         group_id,
         discord_roles: discord_roles.into_iter().unique().collect::<Vec<_>>(),
+        removed_roles: Vec::new(),
+        active_from: None,
+        active_until: None,
+        fallback_templates: Vec::new(),
         priority,
         template,
     };
@@ -694,6 +732,10 @@ async fn bind_rank(
             roblox_rank_id: group_rank.id.0 as i64,
             priority,
             discord_roles: discord_roles.clone(),
+            removed_roles: Vec::new(),
+            active_from: None,
+            active_until: None,
+            fallback_templates: Vec::new(),
             template,
         };
 
@@ -10,7 +10,7 @@ use tower::ServiceBuilder;
 use bind::bind;
 use reset::reset;
 use serverinfo::serverinfo;
-use update_mul::{update_all, update_role};
+use update_mul::{update_all, update_all_cancel, update_all_status, update_role};
 
 pub fn group_config(cmds: &mut Vec<Command>) {
     let serverinfo_cmd = Command::builder()
@@ -25,11 +25,23 @@ pub fn group_config(cmds: &mut Vec<Command>) {
     let update_all_srv = ServiceBuilder::new()
         .layer(bucket.clone())
         .service(CommandHandler::new(update_all));
+    let update_all_status_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["status"])
+        .description("Command to view the progress of the latest mass update")
+        .handler(update_all_status);
+    let update_all_cancel_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["cancel"])
+        .description("Command to cancel the running mass update")
+        .handler(update_all_cancel);
     let update_all_cmd = Command::builder()
         .level(RoLevel::Admin)
         .names(&["update-all"])
         .description("Command to update all members in the server")
         .group("Premium")
+        .sub_command(update_all_status_cmd)
+        .sub_command(update_all_cancel_cmd)
         .service(Box::new(update_all_srv));
 
     let update_role_srv = ServiceBuilder::new()
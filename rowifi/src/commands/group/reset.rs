@@ -1,5 +1,6 @@
 use rowifi_framework::prelude::*;
 use rowifi_models::{
+    bind::Template,
     blacklist::Blacklist,
     guild::{BlacklistActionType, GuildType},
     id::{ChannelId, RoleId, UserId},
@@ -42,9 +43,9 @@ pub async fn reset(ctx: CommandContext) -> CommandResult {
     }
 
     let upsert = transaction.prepare_cached(
-        r#"INSERT INTO guilds(guild_id, kind, premium_owner, command_prefix, verification_roles, verified_roles, blacklists, disabled_channels, registered_groups, auto_detection, blacklist_action, update_on_join, admin_roles, trainer_roles, bypass_roles, nickname_bypass_roles, log_channel)
-        VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17) ON CONFLICT (guild_id) DO UPDATE SET
-        kind = $2, premium_owner = $3, command_prefix = $4, verification_roles = $5, verified_roles = $6, blacklists = $7, disabled_channels = $8, registered_groups = $9, auto_detection = $10, blacklist_action = $11, update_on_join = $12, admin_roles = $13, trainer_roles = $14, bypass_roles = $15, nickname_bypass_roles = $16, log_channel = $17"#
+        r"INSERT INTO guilds(guild_id, kind, premium_owner, command_prefix, verification_roles, verified_roles, blacklists, disabled_channels, registered_groups, auto_detection, blacklist_action, update_on_join, admin_roles, trainer_roles, bypass_roles, nickname_bypass_roles, log_channel, default_template, unverified_template)
+        VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19) ON CONFLICT (guild_id) DO UPDATE SET
+        kind = $2, premium_owner = $3, command_prefix = $4, verification_roles = $5, verified_roles = $6, blacklists = $7, disabled_channels = $8, registered_groups = $9, auto_detection = $10, blacklist_action = $11, update_on_join = $12, admin_roles = $13, trainer_roles = $14, bypass_roles = $15, nickname_bypass_roles = $16, log_channel = $17, default_template = $18, unverified_template = $19"
     ).await?;
     transaction
         .execute(
@@ -67,6 +68,8 @@ pub async fn reset(ctx: CommandContext) -> CommandResult {
                 &Vec::<RoleId>::new(),      // bypass_roles
                 &Vec::<RoleId>::new(),      // nickname_bypass_roles
                 &None::<ChannelId>,         // log_channel
+                &None::<Template>,          // default_template
+                &None::<Template>,          // unverified_template
             ],
         )
         .await?;
@@ -65,9 +65,9 @@ pub async fn serverinfo(ctx: CommandContext) -> CommandResult {
             EmbedFieldBuilder::new(
                 "Rankbinds",
                 rows.iter()
-                    .find(|r| r.bind_type == BindType::Rank)
+                    .filter(|r| matches!(r.bind_type, BindType::Rank | BindType::Range))
                     .map(|r| r.count)
-                    .unwrap_or_default()
+                    .sum::<i64>()
                     .to_string(),
             )
             .inline(),
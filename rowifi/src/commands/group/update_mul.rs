@@ -1,17 +1,26 @@
 use itertools::Itertools;
-use rowifi_database::postgres::Row;
 use rowifi_framework::prelude::*;
 use rowifi_models::{
-    bind::Bind, discord::gateway::payload::outgoing::RequestGuildMembers, guild::GuildType,
-    id::RoleId, roblox::id::UserId as RobloxUserId,
+    bind::Bind,
+    guild::GuildType,
+    id::{RoleId, UserId},
+    job::{JobStatus, UpdateJob},
+    roblox::id::UserId as RobloxUserId,
 };
-use std::{env, sync::atomic::Ordering};
-use tokio::time::sleep;
-use twilight_gateway::Event;
+use twilight_http::request::AttachmentFile;
 
-use crate::services::auto_detection::{execute_chunk, mass_update_user};
+use crate::{
+    services::update_jobs::{linked_users, request_members, run_job},
+    utils::{plan_chunk, resolve_macros, DryRun, DryRunReport},
+};
 
-pub async fn update_all(ctx: CommandContext) -> CommandResult {
+#[derive(FromArgs)]
+pub struct UpdateAllArguments {
+    #[arg(help = "Pass `--dry-run` to preview the changes without updating anyone")]
+    pub dry_run: Option<DryRun>,
+}
+
+pub async fn update_all(ctx: CommandContext, args: UpdateAllArguments) -> CommandResult {
     let guild_id = ctx.guild_id.unwrap();
     let guild = ctx.bot.database.get_guild(guild_id).await?;
     if guild.kind == GuildType::Free {
@@ -26,142 +35,15 @@ pub async fn update_all(ctx: CommandContext) -> CommandResult {
         return Ok(());
     }
 
-    ctx.respond()
-        .content("Updating all members...")?
-        .exec()
-        .await?;
-    tracing::info!("Update-all queue started in {}", guild_id);
-
-    let log_embed = EmbedBuilder::new()
-        .default_data()
-        .title(format!("Action by {}", ctx.author.name))
-        .description("Started an `update-all` queue")
-        .build()
-        .unwrap();
-    ctx.log_guild(guild_id, log_embed).await;
-
-    let server = ctx.bot.cache.guild(guild_id).unwrap();
-    let mut members = ctx
-        .bot
-        .cache
-        .members(guild_id)
-        .into_iter()
-        .map(|m| m.0.get() as i64)
-        .collect::<Vec<_>>();
-    if (members.len() as i64) < server.member_count.load(Ordering::SeqCst) / 2 {
-        let req = RequestGuildMembers::builder(server.id.0).query("", None);
-        let shard_id = (guild_id.0.get() >> 22) % ctx.bot.total_shards;
-        if ctx.bot.cluster.command(shard_id, &req).await.is_err() {
-            ctx.respond().content("There was an issue in requesting the server members. Please try again. If the issue persists, please contact our support server.")?.exec().await?;
-            return Ok(());
-        }
-        let _ = ctx
-            .bot
-            .standby
-            .wait_for_event(move |event: &Event| {
-                if let Event::MemberChunk(mc) = event {
-                    if mc.guild_id == guild_id.0 && mc.chunk_index == mc.chunk_count - 1 {
-                        return true;
-                    }
-                }
-                false
-            })
-            .await;
-        members = ctx
-            .bot
-            .cache
-            .members(guild_id)
-            .into_iter()
-            .map(|m| m.0.get() as i64)
-            .collect::<Vec<_>>();
-    }
-
-    let rows = ctx
-        .bot
-        .database
-        .query::<Row>(
-            r#"
-            SELECT users.discord_id, l.roblox_id, users.default_roblox_id FROM 
-            (SELECT * FROM linked_users WHERE guild_id = $1) AS l
-            RIGHT JOIN users
-            ON users.discord_id = l.discord_id
-            WHERE users.discord_id = ANY($2)
-        "#,
-            &[&guild.guild_id, &members],
-        )
-        .await?;
-    let mut users = Vec::new();
-    for row in rows {
-        match mass_update_user(&row, guild.guild_id) {
-            Ok(u) => users.push(u),
-            Err(err) => tracing::error!("error in deserializing user: {}", err),
-        }
-    }
-    tracing::trace!(users = ?users);
-
-    let binds = ctx
-        .bot
-        .database
-        .query::<Bind>("SELECT * FROM binds WHERE guild_id = $1", &[&(guild_id)])
-        .await?;
-
-    let guild_roles = ctx.bot.cache.roles(guild_id);
-    let c = ctx.clone();
-    let channel_id = ctx.channel_id;
-
-    let chunk_size = if let Ok(chunk_size) = env::var("CHUNK_SIZE") {
-        chunk_size.parse::<usize>().unwrap_or(5)
-    } else {
-        5
-    };
-
-    tokio::spawn(async move {
-        let all_roles = binds
-            .iter()
-            .flat_map(|b| b.discord_roles())
-            .unique()
-            .collect::<Vec<_>>();
-        for user_chunk in users.chunks(100) {
-            let user_ids = user_chunk
-                .iter()
-                .map(|u| RobloxUserId(u.roblox_id as u64))
-                .collect::<Vec<_>>();
-            if let Err(err) = c.bot.roblox.get_users(&user_ids).await {
-                tracing::error!(err = ?err);
-            }
-            for user_sec_chunk in user_chunk.chunks(chunk_size) {
-                let (_, _) = tokio::join!(
-                    execute_chunk(
-                        user_sec_chunk,
-                        &ctx.bot,
-                        &server,
-                        &guild,
-                        &guild_roles,
-                        false,
-                        None,
-                        &binds,
-                        &all_roles
-                    ),
-                    sleep(Duration::from_secs(1))
-                );
-            }
-        }
-        let _ = c
-            .bot
-            .http
-            .create_message(channel_id.0)
-            .content("Finished updating all members")
-            .unwrap()
-            .exec()
-            .await;
-    });
-    Ok(())
+    mass_update(ctx, None, args.dry_run.is_some(), "update-all").await
 }
 
 #[derive(FromArgs)]
 pub struct UpdateMultipleArguments {
     #[arg(help = "The role or its id whose members are to be updated")]
     pub role: RoleId,
+    #[arg(help = "Pass `--dry-run` to preview the changes without updating anyone")]
+    pub dry_run: Option<DryRun>,
 }
 
 pub async fn update_role(ctx: CommandContext, args: UpdateMultipleArguments) -> CommandResult {
@@ -190,95 +72,101 @@ pub async fn update_role(ctx: CommandContext, args: UpdateMultipleArguments) ->
         .into());
     }
 
-    ctx.respond()
-        .content("Updating all members...")?
-        .exec()
-        .await?;
-    tracing::info!("Update-all queue started in {}", guild_id);
-
-    let log_embed = EmbedBuilder::new()
-        .default_data()
-        .title(format!("Action by {}", ctx.author.name))
-        .description("Started an `update-role` queue")
-        .build()
-        .unwrap();
-    ctx.log_guild(guild_id, log_embed).await;
+    mass_update(ctx, Some(role_id), args.dry_run.is_some(), "update-role").await
+}
 
-    let server = ctx.bot.cache.guild(guild_id).unwrap();
-    let mut members = ctx
-        .bot
-        .cache
-        .members(guild_id)
-        .into_iter()
-        .map(|m| m.0.get() as i64)
-        .collect::<Vec<_>>();
-    if (members.len() as i64) < server.member_count.load(Ordering::SeqCst) / 2 {
-        let req = RequestGuildMembers::builder(server.id.0).query("", None);
-        let shard_id = (guild_id.0.get() >> 22) % ctx.bot.total_shards;
-        if ctx.bot.cluster.command(shard_id, &req).await.is_err() {
-            ctx.respond().content("There was an issue in requesting the server members. Please try again. If the issue persists, please contact our support server.")?.exec().await?;
+/// Starts a mass update job, or plans the update of every member when it's a dry run
+async fn mass_update(
+    ctx: CommandContext,
+    role_filter: Option<RoleId>,
+    dry_run: bool,
+    command: &str,
+) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    if !dry_run {
+        let running = ctx
+            .bot
+            .database
+            .query_opt::<UpdateJob>(
+                "SELECT * FROM update_jobs WHERE guild_id = $1 AND status = $2",
+                &[&guild_id, &JobStatus::Running],
+            )
+            .await?;
+        if running.is_some() {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Update All Failed")
+                .description("An update of this server is already running. Use `update-all status` to view its progress or `update-all cancel` to stop it")
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
             return Ok(());
         }
-        let _ = ctx
-            .bot
-            .standby
-            .wait_for_event(move |event: &Event| {
-                if let Event::MemberChunk(mc) = event {
-                    if mc.guild_id == guild_id.0 && mc.chunk_index == mc.chunk_count - 1 {
-                        return true;
-                    }
-                }
-                false
-            })
-            .await;
-        members = ctx
-            .bot
-            .cache
-            .members(guild_id)
-            .into_iter()
-            .map(|m| m.0.get() as i64)
-            .collect::<Vec<_>>();
     }
 
-    let rows = ctx
-        .bot
-        .database
-        .query::<Row>(
-            r#"
-            SELECT users.discord_id, l.roblox_id, users.default_roblox_id FROM 
-            (SELECT * FROM linked_users WHERE guild_id = $1) AS l
-            RIGHT JOIN users
-            ON users.discord_id = l.discord_id
-            WHERE users.discord_id = ANY($2)
-        "#,
-            &[&guild.guild_id, &members],
-        )
-        .await?;
-    let mut users = Vec::new();
-    for row in rows {
-        match mass_update_user(&row, guild.guild_id) {
-            Ok(u) => users.push(u),
-            Err(err) => tracing::error!("error in deserializing user: {}", err),
-        }
+    if dry_run {
+        ctx.respond()
+            .content("Planning the update of all members. Nothing will be changed...")?
+            .exec()
+            .await?;
+    } else {
+        ctx.respond()
+            .content("Updating all members...")?
+            .exec()
+            .await?;
+    }
+
+    let server = ctx.bot.cache.guild(guild_id).unwrap();
+    if !request_members(&ctx.bot, &server).await {
+        ctx.respond().content("There was an issue in requesting the server members. Please try again. If the issue persists, please contact our support server.")?.exec().await?;
+        return Ok(());
+    }
+
+    if !dry_run {
+        let job = ctx
+            .bot
+            .database
+            .query_one::<UpdateJob>(
+                "INSERT INTO update_jobs(guild_id, channel_id, author_id, role_filter) VALUES($1, $2, $3, $4) RETURNING *",
+                &[&guild_id, &ctx.channel_id, &UserId(ctx.author.id), &role_filter],
+            )
+            .await?;
+        tracing::info!(
+            job_id = job.job_id,
+            "Update-all queue started in {}",
+            guild_id
+        );
+
+        let log_embed = EmbedBuilder::new()
+            .default_data()
+            .title(format!("Action by {}", ctx.author.name))
+            .description(format!("Started an `{command}` queue"))
+            .build()
+            .unwrap();
+        ctx.log_guild(guild_id, log_embed).await;
+
+        tokio::spawn(run_job(ctx.bot.clone(), job));
+        return Ok(());
     }
-    tracing::trace!(users = ?users);
 
-    let binds = ctx
+    let mut guild = ctx.bot.database.get_guild(guild_id).await?;
+    let mut binds = ctx
         .bot
         .database
         .query::<Bind>("SELECT * FROM binds WHERE guild_id = $1", &[&(guild_id)])
         .await?;
+    resolve_macros(&ctx.bot.database, &mut guild, &mut binds).await?;
+    let users = linked_users(&ctx.bot, guild_id, 0).await?;
     let guild_roles = ctx.bot.cache.roles(guild_id);
-    let c = ctx.clone();
-    let channel_id = ctx.channel_id;
-
-    let chunk_size = if let Ok(chunk_size) = env::var("CHUNK_SIZE") {
-        chunk_size.parse::<usize>().unwrap_or(5)
+    let title = if role_filter.is_some() {
+        "Update Role Dry Run"
     } else {
-        5
+        "Update All Dry Run"
     };
 
     tokio::spawn(async move {
+        let mut report = DryRunReport::new();
         let all_roles = binds
             .iter()
             .flat_map(|b| b.discord_roles())
@@ -289,34 +177,148 @@ pub async fn update_role(ctx: CommandContext, args: UpdateMultipleArguments) ->
                 .iter()
                 .map(|u| RobloxUserId(u.roblox_id as u64))
                 .collect::<Vec<_>>();
-            if let Err(err) = c.bot.roblox.get_users(&user_ids).await {
+            if let Err(err) = ctx.bot.roblox.get_users(&user_ids).await {
                 tracing::error!(err = ?err);
             }
-            for user_sec_chunk in user_chunk.chunks(chunk_size) {
-                let (_, _) = tokio::join!(
-                    execute_chunk(
-                        user_sec_chunk,
-                        &ctx.bot,
-                        &server,
-                        &guild,
-                        &guild_roles,
-                        false,
-                        Some(role_id),
-                        &binds,
-                        &all_roles
-                    ),
-                    sleep(Duration::from_secs(1))
-                );
-            }
+            plan_chunk(
+                user_chunk,
+                &ctx.bot,
+                &server,
+                &guild,
+                &guild_roles,
+                role_filter,
+                &binds,
+                &all_roles,
+                &mut report,
+            )
+            .await;
         }
-        let _ = c
+
+        let embed = match report.embed(title) {
+            Ok(e) => e,
+            Err(err) => {
+                tracing::error!(err = ?err);
+                return;
+            }
+        };
+        let _ = ctx
             .bot
             .http
-            .create_message(channel_id.0)
-            .content("Finished updating all members")
+            .create_message(ctx.channel_id.0)
+            .embeds(&[embed])
             .unwrap()
+            .attach(&[AttachmentFile::from_bytes(
+                "dry-run.csv",
+                report.csv().as_bytes(),
+            )])
             .exec()
             .await;
     });
     Ok(())
 }
+
+pub async fn update_all_status(ctx: CommandContext) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let Some(job) = ctx
+        .bot
+        .database
+        .query_opt::<UpdateJob>(
+            "SELECT * FROM update_jobs WHERE guild_id = $1 ORDER BY job_id DESC LIMIT 1",
+            &[&guild_id],
+        )
+        .await?
+    else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Update All Status Failed")
+            .description("No mass update has been run in this server")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    #[allow(clippy::cast_precision_loss)]
+    let percentage = if job.total > 0 {
+        job.processed as f64 * 100.0 / job.total as f64
+    } else {
+        0.0
+    };
+    let mut embed = EmbedBuilder::new()
+        .default_data()
+        .title("Update All Status")
+        .field(EmbedFieldBuilder::new("Status", job.status.to_string()).inline())
+        .field(
+            EmbedFieldBuilder::new(
+                "Progress",
+                format!("{}/{} ({percentage:.1}%)", job.processed, job.total),
+            )
+            .inline(),
+        )
+        .field(EmbedFieldBuilder::new("Started By", format!("<@{}>", job.author_id)).inline())
+        .field(
+            EmbedFieldBuilder::new("Started", format!("<t:{}:R>", job.started_at.timestamp()))
+                .inline(),
+        )
+        .field(
+            EmbedFieldBuilder::new(
+                "Last Progress",
+                format!("<t:{}:R>", job.updated_at.timestamp()),
+            )
+            .inline(),
+        );
+    if let Some(role_filter) = job.role_filter {
+        embed = embed.field(EmbedFieldBuilder::new("Role", format!("<@&{role_filter}>")).inline());
+    }
+    if let Some(failure) = job.failure {
+        embed = embed.field(EmbedFieldBuilder::new("Failure", failure));
+    }
+    ctx.respond().embeds(&[embed.build()?])?.exec().await?;
+    Ok(())
+}
+
+pub async fn update_all_cancel(ctx: CommandContext) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let cancelled = ctx
+        .bot
+        .database
+        .query_opt::<UpdateJob>(
+            "UPDATE update_jobs SET status = $1, updated_at = NOW() WHERE guild_id = $2 AND status = $3 RETURNING *",
+            &[&JobStatus::Cancelled, &guild_id, &JobStatus::Running],
+        )
+        .await?;
+    let Some(job) = cancelled else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Update All Cancel Failed")
+            .description("There is no mass update running in this server")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    let embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::DarkGreen as u32)
+        .title("Success!")
+        .description(format!(
+            "The mass update was cancelled after updating {}/{} members",
+            job.processed, job.total
+        ))
+        .build()
+        .unwrap();
+    ctx.respond().embeds(&[embed])?.exec().await?;
+
+    let log_embed = EmbedBuilder::new()
+        .default_data()
+        .title(format!("Action by {}", ctx.author.name))
+        .description("Cancelled the running mass update")
+        .build()
+        .unwrap();
+    ctx.log_guild(guild_id, log_embed).await;
+
+    Ok(())
+}
@@ -137,7 +137,7 @@ pub async fn groupbinds_delete(
 
                     let mut db = ctx.bot.database.get().await?;
                     let transaction = db.transaction().await?;
-                    let statement = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, discord_roles, priority, template) VALUES($1, $2, $3, $4, $5, $6)").await?;
+                    let statement = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, discord_roles, priority, template, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)").await?;
                     for bind in binds_to_delete {
                         transaction
                             .execute(
@@ -149,6 +149,10 @@ pub async fn groupbinds_delete(
                                     &bind.discord_roles,
                                     &bind.priority,
                                     &bind.template,
+                                    &bind.removed_roles,
+                                    &bind.active_from,
+                                    &bind.active_until,
+                                    &bind.fallback_templates,
                                 ],
                             )
                             .await?;
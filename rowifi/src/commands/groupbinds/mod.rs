@@ -2,12 +2,19 @@ mod delete;
 mod modify;
 mod new;
 
+use chrono::Utc;
 use itertools::Itertools;
 use rowifi_framework::prelude::*;
 use rowifi_models::bind::{BindType, Groupbind};
+use std::fmt::Write;
+
+use crate::utils::{active_window, fallbacks_str};
 
 pub use delete::groupbinds_delete;
-pub use modify::{gb_add_roles, gb_modify_priority, gb_modify_template, gb_remove_roles};
+pub use modify::{
+    gb_add_roles, gb_modify_active, gb_modify_fallbacks, gb_modify_priority, gb_modify_template,
+    gb_remove_roles, gb_removed_roles,
+};
 pub use new::groupbinds_new;
 
 pub fn groupbinds_config(cmds: &mut Vec<Command>) {
@@ -47,6 +54,26 @@ pub fn groupbinds_config(cmds: &mut Vec<Command>) {
         .description("Command to remove roles from a groupbind")
         .handler(gb_remove_roles);
 
+    let groupbinds_removed_roles_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["removed-roles"])
+        .description("Command to set the roles removed from members matching a groupbind")
+        .handler(gb_removed_roles);
+
+    let groupbinds_modify_active_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["active"])
+        .description("Command to set the time window during which a groupbind applies")
+        .handler(gb_modify_active);
+
+    let groupbinds_modify_fallbacks_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["fallbacks"])
+        .description(
+            "Command to set the templates tried when the nickname of a groupbind is too long",
+        )
+        .handler(gb_modify_fallbacks);
+
     let groupbinds_modify_cmd = Command::builder()
         .level(RoLevel::Admin)
         .names(&["modify", "m"])
@@ -55,6 +82,9 @@ pub fn groupbinds_config(cmds: &mut Vec<Command>) {
         .sub_command(groupbinds_modify_template_cmd)
         .sub_command(groupbinds_add_roles_cmd)
         .sub_command(groupbinds_remove_roles_cmd)
+        .sub_command(groupbinds_removed_roles_cmd)
+        .sub_command(groupbinds_modify_active_cmd)
+        .sub_command(groupbinds_modify_fallbacks_cmd)
         .no_handler();
 
     let groupbinds_new_cmd = Command::builder()
@@ -101,27 +131,58 @@ pub async fn groupbinds_view(ctx: CommandContext) -> CommandResult {
 
     let mut pages = Vec::new();
     let mut page_count = 0;
-    for gbs in &groupbinds.iter().chunks(12) {
-        let mut embed = EmbedBuilder::new()
-            .default_data()
-            .title("Groupbinds")
-            .description(format!("Page {}", page_count + 1));
-        let gbs = gbs.sorted_by_key(|g| g.group_id);
-        for gb in gbs {
-            let name = format!("Group Id: {}", gb.group_id);
-            let desc = format!(
-                "Template: {}\nPriority: {}\nRoles: {}",
-                gb.template,
-                gb.priority,
-                gb.discord_roles
-                    .iter()
-                    .map(|r| format!("<@&{}> ", r))
-                    .collect::<String>()
-            );
-            embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+    let now = Utc::now();
+    let (expired, groupbinds): (Vec<_>, Vec<_>) = groupbinds
+        .into_iter()
+        .partition(|gb| matches!(gb.active_until, Some(until) if until <= now));
+
+    for (title, groupbinds) in [("Groupbinds", groupbinds), ("Expired Groupbinds", expired)] {
+        for gbs in &groupbinds.iter().chunks(12) {
+            let mut embed = EmbedBuilder::new()
+                .default_data()
+                .title(title)
+                .description(format!("Page {}", page_count + 1));
+            let gbs = gbs.sorted_by_key(|g| g.group_id);
+            for gb in gbs {
+                let name = format!("Group Id: {}", gb.group_id);
+                let mut desc = format!(
+                    "Template: {}\nPriority: {}\nRoles: {}",
+                    gb.template,
+                    gb.priority,
+                    gb.discord_roles
+                        .iter()
+                        .map(|r| format!("<@&{r}>"))
+                        .join(" ")
+                );
+                if !gb.removed_roles.is_empty() {
+                    let _ = write!(
+                        desc,
+                        "\nRemoved Roles: {}",
+                        gb.removed_roles
+                            .iter()
+                            .map(|r| format!("<@&{r}>"))
+                            .join(" ")
+                    );
+                }
+                if gb.active_from.is_some() || gb.active_until.is_some() {
+                    let _ = write!(
+                        desc,
+                        "\nActive: {}",
+                        active_window(gb.active_from, gb.active_until)
+                    );
+                }
+                if !gb.fallback_templates.is_empty() {
+                    let _ = write!(
+                        desc,
+                        "\nFallbacks: {}",
+                        fallbacks_str(&gb.fallback_templates)
+                    );
+                }
+                embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+            }
+            pages.push(embed.build()?);
+            page_count += 1;
         }
-        pages.push(embed.build()?);
-        page_count += 1;
     }
     paginate_embed(&ctx, pages, page_count).await?;
     Ok(())
@@ -6,6 +6,8 @@ use rowifi_models::{
     id::{BindId, RoleId},
 };
 
+use crate::utils::check_manageable;
+
 #[derive(FromArgs)]
 pub struct GroupbindsNewArguments {
     #[arg(help = "The Roblox Group Id to create a bind with")]
@@ -74,12 +76,20 @@ pub async fn groupbinds_new(ctx: CommandContext, args: GroupbindsNewArguments) -
         }
     }
 
+    if !check_manageable(&ctx, "Bind Addition Failed", &roles).await? {
+        return Ok(());
+    }
+
     let bind = Groupbind {
         // default is entered here since this field is not used in the insertion. The struct is only constructed to ensure we have
         // collected all fields.
         bind_id: BindId::default(),
         group_id,
         discord_roles: roles.into_iter().unique().collect::<Vec<_>>(),
+        removed_roles: Vec::new(),
+        active_from: None,
+        active_until: None,
+        fallback_templates: Vec::new(),
         priority,
         template: Template(template_str.clone()),
     };
@@ -0,0 +1,65 @@
+use rowifi_framework::prelude::*;
+use rowifi_models::rolang::Macro;
+
+use super::{broken_dependents, broken_description, macros_reply_log};
+
+#[derive(FromArgs)]
+pub struct MacrosDeleteArguments {
+    #[arg(help = "The name of the macro to delete")]
+    pub name: String,
+}
+
+pub async fn macros_delete(ctx: CommandContext, args: MacrosDeleteArguments) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let name = args.name;
+
+    let definitions = ctx
+        .bot
+        .database
+        .query::<Macro>("SELECT * FROM macros WHERE guild_id = $1", &[&(guild_id)])
+        .await?;
+    let deleted = match definitions.iter().find(|m| m.name == name) {
+        Some(m) => m.clone(),
+        None => {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Macro Deletion Failed")
+                .description(format!("There was no macro found with the name `{name}`"))
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+    };
+
+    let remaining = definitions
+        .iter()
+        .filter(|m| m.name != name)
+        .cloned()
+        .collect::<Vec<_>>();
+    let broken = broken_dependents(&ctx, guild_id, &definitions, &remaining).await?;
+    if !broken.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Deletion Failed")
+            .description(broken_description(&broken))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    ctx.bot
+        .database
+        .execute(
+            "DELETE FROM macros WHERE guild_id = $1 AND name = $2",
+            &[&(guild_id), &name],
+        )
+        .await?;
+
+    let name = format!("Name: {name}()");
+    let desc = format!("`Code`: {}", deleted.code);
+    macros_reply_log(ctx, "Macro Deletion", name, desc).await
+}
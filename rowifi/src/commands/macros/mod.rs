@@ -0,0 +1,215 @@
+mod delete;
+mod modify;
+mod new;
+
+use itertools::Itertools;
+use rowifi_framework::{
+    constants::{EMBED_DESCRIPTION_LIMIT, EMBED_FIELD_DESCRIPTION_LIMIT},
+    prelude::*,
+};
+use rowifi_models::{
+    bind::{BindType, Custombind},
+    blacklist::BlacklistData,
+    id::GuildId,
+    rolang::{Macro, Macros, RoCommand},
+};
+use std::fmt::Write;
+
+use delete::macros_delete;
+use modify::macros_modify;
+use new::macros_new;
+
+pub fn macros_config(cmds: &mut Vec<Command>) {
+    let macros_view_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["view"])
+        .description("Command to view the macros of a server")
+        .handler(macros_view);
+
+    let macros_new_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["new"])
+        .description("Command to create a macro")
+        .handler(macros_new);
+
+    let macros_modify_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["modify", "m"])
+        .description("Command to modify the code of a macro")
+        .handler(macros_modify);
+
+    let macros_delete_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["delete", "d", "remove"])
+        .description("Command to delete a macro")
+        .handler(macros_delete);
+
+    let macros_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["macros", "macro"])
+        .description("Module to interact with the macros of a server")
+        .group("Binds")
+        .sub_command(macros_view_cmd)
+        .sub_command(macros_new_cmd)
+        .sub_command(macros_modify_cmd)
+        .sub_command(macros_delete_cmd)
+        .handler(macros_view);
+    cmds.push(macros_cmd);
+}
+
+pub async fn macros_view(ctx: CommandContext) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let definitions = ctx
+        .bot
+        .database
+        .query::<Macro>(
+            "SELECT * FROM macros WHERE guild_id = $1 ORDER BY name",
+            &[&(guild_id)],
+        )
+        .await?;
+    if definitions.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .title("Macro Viewing Failed")
+            .color(Color::Red as u32)
+            .description("No macros were found associated with this server")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let macros = Macros::compile(&definitions);
+    let mut pages = Vec::new();
+    let mut page_count = 0;
+    for defs in &definitions.iter().chunks(12) {
+        let mut embed = EmbedBuilder::new()
+            .default_data()
+            .title("Macros")
+            .description(format!("Page {}", page_count + 1));
+        for def in defs {
+            let mut desc = match RoCommand::parse(&def.code) {
+                Ok(command) => format!("Code: {}", command.source()),
+                Err(_) => format!("Code: {}", def.code),
+            };
+            if let Some(err) = macros.errors().get(&def.name) {
+                let _ = write!(desc, "\n⚠️ This macro is invalid: {}", err.message);
+            }
+            embed = embed.field(
+                EmbedFieldBuilder::new(format!("{}()", def.name), truncate_field(desc)).build(),
+            );
+        }
+        pages.push(embed.build()?);
+        page_count += 1;
+    }
+    paginate_embed(&ctx, pages, page_count).await?;
+    Ok(())
+}
+
+/// Checks that changing the macros of a guild from `old` to `new` does not break any other macro,
+/// custombind or custom blacklist. Returns a description of everything that would break
+async fn broken_dependents(
+    ctx: &CommandContext,
+    guild_id: GuildId,
+    old: &[Macro],
+    new: &[Macro],
+) -> Result<Vec<String>, RoError> {
+    let old_macros = Macros::compile(old);
+    let new_macros = Macros::compile(new);
+
+    let mut broken = new_macros
+        .errors()
+        .iter()
+        .filter(|(name, _)| !old_macros.errors().contains_key(*name))
+        .map(|(name, err)| format!("Macro `{name}`: {err}"))
+        .sorted()
+        .collect::<Vec<_>>();
+
+    let custombinds = ctx
+        .bot
+        .database
+        .query::<Custombind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type  = $2 ORDER BY custom_bind_id",
+            &[&(guild_id), &BindType::Custom],
+        )
+        .await?;
+    for bind in custombinds {
+        if !bind.command.calls_macros() || RoCommand::new(&bind.command.code, &old_macros).is_err()
+        {
+            continue;
+        }
+        if let Err(err) = RoCommand::new(&bind.command.code, &new_macros) {
+            broken.push(format!("Custom Bind {}: {err}", bind.custom_bind_id));
+        }
+    }
+
+    let guild = ctx.bot.database.get_guild(guild_id).await?;
+    for blacklist in guild.blacklists {
+        if let BlacklistData::Custom(command) = blacklist.data {
+            if !command.calls_macros() || RoCommand::new(&command.code, &old_macros).is_err() {
+                continue;
+            }
+            if let Err(err) = RoCommand::new(&command.code, &new_macros) {
+                broken.push(format!("Blacklist {}: {err}", blacklist.blacklist_id));
+            }
+        }
+    }
+
+    Ok(broken)
+}
+
+/// Lists the broken dependents in a single embed description
+fn broken_description(broken: &[String]) -> String {
+    let mut desc = String::from("This change would break the following:\n");
+    for (index, line) in broken.iter().enumerate() {
+        if desc.chars().count() + line.chars().count() > EMBED_DESCRIPTION_LIMIT - 32 {
+            let _ = write!(desc, "...and {} more", broken.len() - index);
+            break;
+        }
+        desc.push_str(line);
+        desc.push('\n');
+    }
+    desc
+}
+
+/// Cuts the value of an embed field down to the most Discord allows
+fn truncate_field(mut desc: String) -> String {
+    if desc.chars().count() > EMBED_FIELD_DESCRIPTION_LIMIT {
+        desc = desc
+            .chars()
+            .take(EMBED_FIELD_DESCRIPTION_LIMIT - 3)
+            .collect::<String>();
+        desc.push_str("...");
+    }
+    desc
+}
+
+async fn macros_reply_log(
+    ctx: CommandContext,
+    action: &str,
+    name: String,
+    desc: String,
+) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let desc = truncate_field(desc);
+    let embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::DarkGreen as u32)
+        .title("Success!")
+        .description(format!("{action} was successful"))
+        .field(EmbedFieldBuilder::new(name.clone(), desc.clone()))
+        .build()
+        .unwrap();
+    ctx.respond().embeds(&[embed])?.exec().await?;
+
+    let log_embed = EmbedBuilder::new()
+        .default_data()
+        .title(format!("Action by {}", ctx.author.name))
+        .description(action)
+        .field(EmbedFieldBuilder::new(name, desc))
+        .build()
+        .unwrap();
+    ctx.log_guild(guild_id, log_embed).await;
+
+    Ok(())
+}
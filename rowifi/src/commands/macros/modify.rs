@@ -0,0 +1,83 @@
+use rowifi_framework::prelude::*;
+use rowifi_models::rolang::{Macro, Macros};
+
+use super::{broken_dependents, broken_description, macros_reply_log};
+
+#[derive(FromArgs)]
+pub struct MacrosModifyArguments {
+    #[arg(help = "The name of the macro")]
+    pub name: String,
+    #[arg(help = "The code to change to", rest)]
+    pub code: String,
+}
+
+pub async fn macros_modify(ctx: CommandContext, args: MacrosModifyArguments) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let name = args.name;
+    let code = args.code;
+
+    let definitions = ctx
+        .bot
+        .database
+        .query::<Macro>("SELECT * FROM macros WHERE guild_id = $1", &[&(guild_id)])
+        .await?;
+    if !definitions.iter().any(|m| m.name == name) {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Modification Failed")
+            .description(format!("There was no macro found with the name `{name}`"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let modified = definitions
+        .iter()
+        .cloned()
+        .map(|mut m| {
+            if m.name == name {
+                m.code.clone_from(&code);
+            }
+            m
+        })
+        .collect::<Vec<_>>();
+    let macros = Macros::compile(&modified);
+    if let Some(err) = macros.errors().get(&name) {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Modification Failed")
+            .description(err.render(&code))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let broken = broken_dependents(&ctx, guild_id, &definitions, &modified).await?;
+    if !broken.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Modification Failed")
+            .description(broken_description(&broken))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE macros SET code = $3 WHERE guild_id = $1 AND name = $2",
+            &[&(guild_id), &name, &code],
+        )
+        .await?;
+
+    let name = format!("Name: {name}()");
+    let desc = format!("`New Code`: {code}");
+    macros_reply_log(ctx, "Macro Modification", name, desc).await
+}
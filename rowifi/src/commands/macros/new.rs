@@ -0,0 +1,77 @@
+use rowifi_framework::prelude::*;
+use rowifi_models::rolang::{Macro, Macros};
+
+use super::macros_reply_log;
+
+#[derive(FromArgs)]
+pub struct MacrosNewArguments {
+    #[arg(help = "The name to call the macro by")]
+    pub name: String,
+    #[arg(help = "The code of the macro", rest)]
+    pub code: String,
+}
+
+pub async fn macros_new(ctx: CommandContext, args: MacrosNewArguments) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let name = args.name;
+    let code = args.code;
+
+    if let Err(err) = Macro::validate_name(&name) {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Creation Failed")
+            .description(err)
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let mut definitions = ctx
+        .bot
+        .database
+        .query::<Macro>("SELECT * FROM macros WHERE guild_id = $1", &[&(guild_id)])
+        .await?;
+    if definitions.iter().any(|m| m.name == name) {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Creation Failed")
+            .description(format!("A macro with the name `{name}` already exists"))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    definitions.push(Macro {
+        guild_id,
+        name: name.clone(),
+        code: code.clone(),
+    });
+    let macros = Macros::compile(&definitions);
+    if let Some(err) = macros.errors().get(&name) {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Macro Creation Failed")
+            .description(err.render(&code))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    ctx.bot
+        .database
+        .execute(
+            "INSERT INTO macros(guild_id, name, code) VALUES($1, $2, $3)",
+            &[&(guild_id), &name, &code],
+        )
+        .await?;
+
+    let name = format!("Name: {name}()");
+    let desc = format!("`Code`: {code}");
+    macros_reply_log(ctx, "Macro Creation", name, desc).await
+}
@@ -2,11 +2,13 @@ mod analytics;
 // mod api;
 mod assetbinds;
 mod backup;
+mod binds;
 mod blacklists;
 mod custombinds;
 mod events;
 mod group;
 mod groupbinds;
+mod macros;
 mod premium;
 mod rankbinds;
 mod settings;
@@ -16,11 +18,13 @@ pub use analytics::*;
 // pub use api::*;
 pub use assetbinds::*;
 pub use backup::*;
+pub use binds::*;
 pub use blacklists::*;
 pub use custombinds::*;
 pub use events::*;
 pub use group::*;
 pub use groupbinds::*;
+pub use macros::*;
 pub use premium::*;
 pub use rankbinds::*;
 pub use settings::*;
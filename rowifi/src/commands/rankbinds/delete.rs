@@ -1,14 +1,17 @@
 use itertools::Itertools;
 use rowifi_database::dynamic_args;
 use rowifi_framework::prelude::*;
-use rowifi_models::bind::{BindType, Rankbind};
+use rowifi_models::bind::{Bind, BindType};
 use std::str::FromStr;
 
 #[derive(FromArgs)]
 pub struct RankBindsDelete {
     #[arg(help = "The Group ID of the Rankbind to delete")]
     pub group_id: i64,
-    #[arg(help = "The Rank ID of the Rankbind to delete", rest)]
+    #[arg(
+        help = "The Rank IDs of the Rankbinds to delete. Range binds lying entirely within the given ranks are deleted as well",
+        rest
+    )]
     pub rank_id: String,
 }
 
@@ -17,9 +20,9 @@ pub async fn rankbinds_delete(ctx: CommandContext, args: RankBindsDelete) -> Com
     let rankbinds = ctx
         .bot
         .database
-        .query::<Rankbind>(
-            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2",
-            &[&(guild_id), &BindType::Rank],
+        .query::<Bind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND (bind_type = $2 OR bind_type = $3)",
+            &[&(guild_id), &BindType::Rank, &BindType::Range],
         )
         .await?;
 
@@ -34,27 +37,26 @@ pub async fn rankbinds_delete(ctx: CommandContext, args: RankBindsDelete) -> Com
 
     let mut binds_to_delete = Vec::new();
     for rank in rank_ids_to_delete {
-        match rank {
-            RankId::Range(r1, r2) => {
-                let binds = rankbinds.iter().filter(|r| {
-                    r.group_id == group_id && r.group_rank_id >= r1 && r.group_rank_id <= r2
-                });
-                binds_to_delete.extend(binds);
+        let (r1, r2) = match rank {
+            RankId::Range(r1, r2) => (r1, r2),
+            RankId::Single(rank) => (rank, rank),
+        };
+        let binds = rankbinds.iter().filter(|b| match b {
+            Bind::Rank(r) => {
+                r.group_id == group_id && r.group_rank_id >= r1 && r.group_rank_id <= r2
             }
-            RankId::Single(rank) => {
-                if let Some(b) = rankbinds
-                    .iter()
-                    .find(|r| r.group_id == group_id && r.group_rank_id == rank)
-                {
-                    binds_to_delete.push(b);
-                }
-            }
-        }
+            Bind::Range(r) => r.group_id == group_id && r.min_rank >= r1 && r.max_rank <= r2,
+            _ => false,
+        });
+        binds_to_delete.extend(binds);
     }
+    let binds_to_delete = binds_to_delete
+        .into_iter()
+        .unique_by(|b| b.bind_id())
+        .collect::<Vec<_>>();
     let bind_ids = binds_to_delete
         .iter()
-        .map(|r| &r.bind_id)
-        .unique()
+        .map(|b| b.bind_id())
         .collect::<Vec<_>>();
 
     if binds_to_delete.is_empty() {
@@ -107,7 +109,11 @@ pub async fn rankbinds_delete(ctx: CommandContext, args: RankBindsDelete) -> Com
 
     let ids_str = binds_to_delete
         .iter()
-        .map(|r| format!("`Id`: {}\n", r.roblox_rank_id))
+        .map(|b| match b {
+            Bind::Range(r) => format!("`Ranks`: {}-{}\n", r.min_rank, r.max_rank),
+            Bind::Rank(r) => format!("`Id`: {}\n", r.roblox_rank_id),
+            _ => String::new(),
+        })
         .collect::<String>();
     let log_embed = EmbedBuilder::new()
         .default_data()
@@ -154,23 +160,54 @@ pub async fn rankbinds_delete(ctx: CommandContext, args: RankBindsDelete) -> Com
 
                     let mut db = ctx.bot.database.get().await?;
                     let transaction = db.transaction().await?;
-                    let stmt = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, group_rank_id, roblox_rank_id, template, priority, discord_roles) VALUES($1, $2, $3, $4, $5, $6, $7, $8)").await?;
+                    let rank_stmt = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, group_rank_id, roblox_rank_id, template, priority, discord_roles, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)").await?;
+                    let range_stmt = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, min_rank, max_rank, template, priority, discord_roles, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)").await?;
                     for bind in binds_to_delete {
-                        transaction
-                            .execute(
-                                &stmt,
-                                &[
-                                    &BindType::Rank,
-                                    &(guild_id),
-                                    &bind.group_id,
-                                    &bind.group_rank_id,
-                                    &bind.roblox_rank_id,
-                                    &bind.template,
-                                    &bind.priority,
-                                    &bind.discord_roles,
-                                ],
-                            )
-                            .await?;
+                        match bind {
+                            Bind::Rank(bind) => {
+                                transaction
+                                    .execute(
+                                        &rank_stmt,
+                                        &[
+                                            &BindType::Rank,
+                                            &(guild_id),
+                                            &bind.group_id,
+                                            &bind.group_rank_id,
+                                            &bind.roblox_rank_id,
+                                            &bind.template,
+                                            &bind.priority,
+                                            &bind.discord_roles,
+                                            &bind.removed_roles,
+                                            &bind.active_from,
+                                            &bind.active_until,
+                                            &bind.fallback_templates,
+                                        ],
+                                    )
+                                    .await?;
+                            }
+                            Bind::Range(bind) => {
+                                transaction
+                                    .execute(
+                                        &range_stmt,
+                                        &[
+                                            &BindType::Range,
+                                            &(guild_id),
+                                            &bind.group_id,
+                                            &bind.min_rank,
+                                            &bind.max_rank,
+                                            &bind.template,
+                                            &bind.priority,
+                                            &bind.discord_roles,
+                                            &bind.removed_roles,
+                                            &bind.active_from,
+                                            &bind.active_until,
+                                            &bind.fallback_templates,
+                                        ],
+                                    )
+                                    .await?;
+                            }
+                            _ => {}
+                        }
                     }
                     transaction.commit().await?;
 
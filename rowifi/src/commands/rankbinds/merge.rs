@@ -0,0 +1,137 @@
+use itertools::Itertools;
+use rowifi_database::dynamic_args;
+use rowifi_framework::prelude::*;
+use rowifi_models::{
+    bind::{BindType, Rangebind, Rankbind},
+    roblox::id::GroupId,
+};
+
+pub async fn rankbinds_merge(ctx: CommandContext) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let rankbinds = ctx
+        .bot
+        .database
+        .query::<Rankbind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2",
+            &[&(guild_id), &BindType::Rank],
+        )
+        .await?;
+
+    let group_ids = rankbinds.iter().map(|r| r.group_id).unique().collect_vec();
+    let mut merged = Vec::new();
+    for group_id in group_ids {
+        // Ranks are only consecutive with respect to the ranks that exist in the group
+        let group_ranks = match ctx
+            .bot
+            .roblox
+            .get_group_ranks(GroupId(group_id as u64))
+            .await?
+        {
+            Some(g) => g.roles.iter().map(|r| i64::from(r.rank)).collect_vec(),
+            None => continue,
+        };
+        merged.extend(Rangebind::merge(group_id, &rankbinds, &group_ranks));
+    }
+
+    if merged.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rankbinds Merge Failed")
+            .description("There were no consecutive rankbinds with the same roles, template & priority to merge")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let bind_ids = merged
+        .iter()
+        .flat_map(|(_, ids)| ids.iter())
+        .collect::<Vec<_>>();
+
+    let mut database = ctx.bot.database.get().await?;
+    let transaction = database.transaction().await?;
+
+    let delete = transaction
+        .prepare_cached(&format!(
+            "DELETE FROM binds WHERE bind_id IN ({})",
+            dynamic_args(bind_ids.len())
+        ))
+        .await?;
+    transaction.execute_raw(&delete, &bind_ids).await?;
+
+    let insert = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, min_rank, max_rank, template, priority, discord_roles, removed_roles, active_from, active_until, fallback_templates) VALUES($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)").await?;
+    for (bind, _) in &merged {
+        transaction
+            .execute(
+                &insert,
+                &[
+                    &BindType::Range,
+                    &(guild_id),
+                    &bind.group_id,
+                    &bind.min_rank,
+                    &bind.max_rank,
+                    &bind.template,
+                    &bind.priority,
+                    &bind.discord_roles,
+                    &bind.removed_roles,
+                    &bind.active_from,
+                    &bind.active_until,
+                    &bind.fallback_templates,
+                ],
+            )
+            .await?;
+    }
+
+    transaction.commit().await?;
+
+    let mut embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::DarkGreen as u32)
+        .title("Success!")
+        .description(format!(
+            "Merged {} rankbinds into {} range binds",
+            bind_ids.len(),
+            merged.len()
+        ));
+    let mut log_desc = String::new();
+    for (count, (bind, ids)) in merged.iter().enumerate() {
+        let line = format!(
+            "Group {}: Ranks {}-{} ({} binds)\n",
+            bind.group_id,
+            bind.min_rank,
+            bind.max_rank,
+            ids.len()
+        );
+        if count < 12 {
+            let name = format!(
+                "Group {} | Ranks: {}-{}",
+                bind.group_id, bind.min_rank, bind.max_rank
+            );
+            let desc = format!(
+                "Template: `{}`\nPriority: {}\n Roles: {}",
+                bind.template,
+                bind.priority,
+                bind.discord_roles
+                    .iter()
+                    .map(|r| format!("<@&{r}>"))
+                    .join(" ")
+            );
+            embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+        }
+        log_desc.push_str(&line);
+    }
+    ctx.respond().embeds(&[embed.build()?])?.exec().await?;
+
+    let log_embed = EmbedBuilder::new()
+        .default_data()
+        .title(format!("Action by {}", ctx.author.name))
+        .description("Rank Bind Merge")
+        .field(EmbedFieldBuilder::new("Range Binds Created", log_desc))
+        .build()
+        .unwrap();
+    ctx.log_guild(guild_id, log_embed).await;
+
+    Ok(())
+}
@@ -1,12 +1,18 @@
 mod delete;
+mod merge;
 mod modify;
 mod new;
 
+use chrono::Utc;
 use itertools::Itertools;
 use rowifi_framework::prelude::*;
-use rowifi_models::bind::{BindType, Rankbind};
+use rowifi_models::bind::{Bind, BindType};
+use std::fmt::Write;
+
+use crate::utils::{active_window, fallbacks_str};
 
 pub use delete::*;
+pub use merge::*;
 pub use modify::*;
 pub use new::*;
 
@@ -41,6 +47,26 @@ pub fn rankbinds_config(cmds: &mut Vec<Command>) {
         .description("Command to remove roles from a rankbind")
         .handler(rb_remove_roles);
 
+    let rankbinds_removed_roles_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["removed-roles"])
+        .description("Command to set the roles removed from members matching a rankbind")
+        .handler(rb_removed_roles);
+
+    let rankbinds_modify_active_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["active"])
+        .description("Command to set the time window during which a rankbind applies")
+        .handler(rb_modify_active);
+
+    let rankbinds_modify_fallbacks_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["fallbacks"])
+        .description(
+            "Command to set the templates tried when the nickname of a rankbind is too long",
+        )
+        .handler(rb_modify_fallbacks);
+
     let rankbinds_modify_command = Command::builder()
         .level(RoLevel::Admin)
         .names(&["modify", "m"])
@@ -49,6 +75,9 @@ pub fn rankbinds_config(cmds: &mut Vec<Command>) {
         .sub_command(rankbinds_modify_template_cmd)
         .sub_command(rankbinds_add_roles_cmd)
         .sub_command(rankbinds_remove_roles_cmd)
+        .sub_command(rankbinds_removed_roles_cmd)
+        .sub_command(rankbinds_modify_active_cmd)
+        .sub_command(rankbinds_modify_fallbacks_cmd)
         .no_handler();
 
     let rankbinds_delete_command = Command::builder()
@@ -57,6 +86,14 @@ pub fn rankbinds_config(cmds: &mut Vec<Command>) {
         .description("Command to delete an existing rankbind")
         .handler(rankbinds_delete);
 
+    let rankbinds_merge_command = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["merge"])
+        .description(
+            "Command to merge consecutive identical rankbinds into range binds. Existing rankbinds are never merged automatically, so this has to be run once in every server that wants them merged",
+        )
+        .handler(rankbinds_merge);
+
     let rankbinds_view_command = Command::builder()
         .level(RoLevel::Admin)
         .names(&["view"])
@@ -71,6 +108,7 @@ pub fn rankbinds_config(cmds: &mut Vec<Command>) {
         .sub_command(rankbinds_new_command)
         .sub_command(rankbinds_modify_command)
         .sub_command(rankbinds_delete_command)
+        .sub_command(rankbinds_merge_command)
         .sub_command(rankbinds_view_command)
         .handler(rankbinds_view);
 
@@ -82,11 +120,31 @@ pub async fn rankbinds_view(ctx: CommandContext) -> Result<(), RoError> {
     let rankbinds = ctx
         .bot
         .database
-        .query::<Rankbind>(
-            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2 ORDER BY group_id ASC, group_rank_id ASC",
-            &[&(guild_id), &BindType::Rank],
+        .query::<Bind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND (bind_type = $2 OR bind_type = $3)",
+            &[&(guild_id), &BindType::Rank, &BindType::Range],
         )
         .await?;
+    // (Group Id, Lowest Rank, Field Name, Bind)
+    let rankbinds = rankbinds
+        .iter()
+        .filter_map(|b| match b {
+            Bind::Rank(r) => Some((
+                r.group_id,
+                r.group_rank_id,
+                format!("Rank: {}", r.group_rank_id),
+                b,
+            )),
+            Bind::Range(r) => Some((
+                r.group_id,
+                r.min_rank,
+                format!("Ranks: {}-{}", r.min_rank, r.max_rank),
+                b,
+            )),
+            _ => None,
+        })
+        .sorted_by_key(|(group_id, rank, _, _)| (*group_id, *rank))
+        .collect_vec();
 
     if rankbinds.is_empty() {
         let embed = EmbedBuilder::new()
@@ -102,29 +160,59 @@ pub async fn rankbinds_view(ctx: CommandContext) -> Result<(), RoError> {
 
     let mut pages = Vec::new();
     let mut page_count: usize = 0;
-    let distinct_groups = rankbinds.iter().group_by(|r| r.group_id);
-    for group in &distinct_groups {
-        for rbs in &group.1.collect_vec().iter().chunks(12) {
-            let mut embed = EmbedBuilder::new()
-                .default_data()
-                .title("Rankbinds")
-                .description(format!("Group {} | Page {}", group.0, page_count + 1));
-            let rbs = rbs.sorted_by_key(|r| r.group_rank_id);
-            for rb in rbs {
-                let name = format!("Rank: {}", rb.group_rank_id);
-                let desc = format!(
-                    "Template: `{}`\nPriority: {}\n Roles: {}",
-                    rb.template,
-                    rb.priority,
-                    rb.discord_roles
-                        .iter()
-                        .map(|r| format!("<@&{}> ", r))
-                        .collect::<String>()
-                );
-                embed = embed.field(EmbedFieldBuilder::new(name, desc).inline().build());
+    let now = Utc::now();
+    let (expired, rankbinds): (Vec<_>, Vec<_>) = rankbinds
+        .into_iter()
+        .partition(|(_, _, _, rb)| rb.is_expired(now));
+
+    for (title, rankbinds) in [("Rankbinds", rankbinds), ("Expired Rankbinds", expired)] {
+        let distinct_groups = rankbinds.iter().group_by(|r| r.0);
+        for group in &distinct_groups {
+            for rbs in &group.1.collect_vec().iter().chunks(12) {
+                let mut embed = EmbedBuilder::new()
+                    .default_data()
+                    .title(title)
+                    .description(format!("Group {} | Page {}", group.0, page_count + 1));
+                for (_, _, name, rb) in rbs {
+                    let mut desc = format!(
+                        "Template: `{}`\nPriority: {}\n Roles: {}",
+                        rb.template(),
+                        rb.priority(),
+                        rb.discord_roles()
+                            .iter()
+                            .map(|r| format!("<@&{r}>"))
+                            .join(" ")
+                    );
+                    if !rb.removed_roles().is_empty() {
+                        let _ = write!(
+                            desc,
+                            "\nRemoved Roles: {}",
+                            rb.removed_roles()
+                                .iter()
+                                .map(|r| format!("<@&{r}>"))
+                                .join(" ")
+                        );
+                    }
+                    if rb.active_from().is_some() || rb.active_until().is_some() {
+                        let _ = write!(
+                            desc,
+                            "\nActive: {}",
+                            active_window(rb.active_from(), rb.active_until())
+                        );
+                    }
+                    if !rb.fallback_templates().is_empty() {
+                        let _ = write!(
+                            desc,
+                            "\nFallbacks: {}",
+                            fallbacks_str(rb.fallback_templates())
+                        );
+                    }
+                    embed =
+                        embed.field(EmbedFieldBuilder::new(name.clone(), desc).inline().build());
+                }
+                pages.push(embed.build()?);
+                page_count += 1;
             }
-            pages.push(embed.build()?);
-            page_count += 1;
         }
     }
     paginate_embed(&ctx, pages, page_count).await?;
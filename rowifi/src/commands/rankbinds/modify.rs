@@ -1,71 +1,55 @@
 use itertools::Itertools;
 use rowifi_framework::prelude::*;
 use rowifi_models::{
-    bind::{BindType, Rankbind},
+    bind::{Bind, BindType},
     id::RoleId,
     roblox::id::GroupId,
 };
 
-use super::new::PREFIX_REGEX;
+use super::new::{CreateType, PREFIX_REGEX};
+use crate::utils::{active_window, check_manageable, fallbacks_str, parse_fallbacks, ActiveTime};
 
 #[derive(FromArgs)]
 pub struct ModifyPriority {
     #[arg(help = "The Group ID of the rankbind to modify")]
     pub group_id: i64,
-    #[arg(help = "The Rank ID of the rankbind to modify")]
-    pub rank_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
     #[arg(help = "The priority to set")]
     pub priority: i32,
 }
 
 pub async fn rb_modify_priority(ctx: CommandContext, args: ModifyPriority) -> CommandResult {
-    let guild_id = ctx.guild_id.unwrap();
-    let rankbinds = ctx
-        .bot
-        .database
-        .query::<Rankbind>(
-            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2",
-            &[&(guild_id), &BindType::Rank],
-        )
-        .await?;
-
     let group_id = args.group_id;
     let rank_id = args.rank_id;
     let priority = args.priority;
 
-    let bind = match rankbinds
-        .iter()
-        .find(|r| r.group_id == group_id && r.group_rank_id == rank_id)
-    {
-        Some(b) => b,
-        None => {
-            let embed = EmbedBuilder::new()
-                .default_data()
-                .color(Color::Red as u32)
-                .title("Rank Bind Modification Failed")
-                .description(format!(
-                    "There was no bind found with Group Id {} and Rank Id {}",
-                    group_id, rank_id
-                ))
-                .build()
-                .unwrap();
-            ctx.respond().embeds(&[embed])?.exec().await?;
-            return Ok(());
-        }
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
     };
 
     ctx.bot
         .database
         .execute(
             "UPDATE binds SET priority = $1 WHERE bind_id = $2",
-            &[&priority, &bind.bind_id],
+            &[&priority, &bind.bind_id()],
         )
         .await?;
 
     let name = format!("Group Id: {group_id}");
     let desc = format!(
         "Rank Id: {rank_id}\n`Priority`: {0} -> {priority}",
-        bind.priority
+        bind.priority()
     );
 
     rb_reply_log(ctx, name, desc).await
@@ -75,14 +59,13 @@ pub async fn rb_modify_priority(ctx: CommandContext, args: ModifyPriority) -> Co
 pub struct ModifyTemplate {
     #[arg(help = "The Group ID of the rankbind to modify")]
     pub group_id: i64,
-    #[arg(help = "The Rank ID of the rankbind to modify")]
-    pub rank_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
     #[arg(help = "The template to set", rest)]
     pub template: String,
 }
 
 pub async fn rb_modify_template(ctx: CommandContext, args: ModifyTemplate) -> CommandResult {
-    let guild_id = ctx.guild_id.unwrap();
     let group_id = args.group_id;
     let rank_id = args.rank_id;
     let template = args.template;
@@ -99,34 +82,18 @@ pub async fn rb_modify_template(ctx: CommandContext, args: ModifyTemplate) -> Co
         return Ok(());
     }
 
-    let rankbinds = ctx
-        .bot
-        .database
-        .query::<Rankbind>(
-            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2",
-            &[&(guild_id), &BindType::Rank],
-        )
-        .await?;
-
-    let bind = match rankbinds
-        .iter()
-        .find(|r| r.group_id == group_id && r.group_rank_id == rank_id)
-    {
-        Some(b) => b,
-        None => {
-            let embed = EmbedBuilder::new()
-                .default_data()
-                .color(Color::Red as u32)
-                .title("Rank Bind Modification Failed")
-                .description(format!(
-                    "There was no bind found with Group Id {} and Rank Id {}",
-                    group_id, rank_id
-                ))
-                .build()
-                .unwrap();
-            ctx.respond().embeds(&[embed])?.exec().await?;
-            return Ok(());
-        }
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
     };
 
     let roblox_group = ctx
@@ -134,8 +101,12 @@ pub async fn rb_modify_template(ctx: CommandContext, args: ModifyTemplate) -> Co
         .roblox
         .get_group_ranks(GroupId(group_id as u64))
         .await?;
+    // Range binds take the prefix of the lowest rank in the range
+    let lowest_rank = match rank_id {
+        CreateType::Single(id) | CreateType::Multiple(id, _) => id,
+    };
     let roblox_rank = match &roblox_group {
-        Some(g) => g.roles.iter().find(|r| i64::from(r.rank) == rank_id),
+        Some(g) => g.roles.iter().find(|r| i64::from(r.rank) == lowest_rank),
         None => None,
     };
     let template = match template.as_str() {
@@ -158,14 +129,14 @@ pub async fn rb_modify_template(ctx: CommandContext, args: ModifyTemplate) -> Co
         .database
         .execute(
             "UPDATE binds SET template = $1 WHERE bind_id = $2",
-            &[&template, &bind.bind_id],
+            &[&template, &bind.bind_id()],
         )
         .await?;
 
     let name = format!("Group Id: {group_id}");
     let desc = format!(
         "Rank Id: {rank_id}\n`Template`: {0} -> {template}",
-        bind.template
+        bind.template()
     );
 
     rb_reply_log(ctx, name, desc).await
@@ -175,14 +146,13 @@ pub async fn rb_modify_template(ctx: CommandContext, args: ModifyTemplate) -> Co
 pub struct AddRoles {
     #[arg(help = "The Group ID of the rankbind to modify")]
     pub group_id: i64,
-    #[arg(help = "The Rank ID of the rankbind to modify")]
-    pub rank_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
     #[arg(help = "The roles to add", rest)]
     pub roles: String,
 }
 
 pub async fn rb_add_roles(ctx: CommandContext, args: AddRoles) -> CommandResult {
-    let guild_id = ctx.guild_id.unwrap();
     let group_id = args.group_id;
     let rank_id = args.rank_id;
 
@@ -196,37 +166,25 @@ pub async fn rb_add_roles(ctx: CommandContext, args: AddRoles) -> CommandResult
     }
     role_ids = role_ids.into_iter().unique().collect::<Vec<_>>();
 
-    let rankbinds = ctx
-        .bot
-        .database
-        .query::<Rankbind>(
-            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2",
-            &[&(guild_id), &BindType::Rank],
-        )
-        .await?;
-
-    let bind = match rankbinds
-        .iter()
-        .find(|r| r.group_id == group_id && r.group_rank_id == rank_id)
-    {
-        Some(b) => b,
-        None => {
-            let embed = EmbedBuilder::new()
-                .default_data()
-                .color(Color::Red as u32)
-                .title("Rank Bind Modification Failed")
-                .description(format!(
-                    "There was no bind found with Group Id {} and Rank Id {}",
-                    group_id, rank_id
-                ))
-                .build()
-                .unwrap();
-            ctx.respond().embeds(&[embed])?.exec().await?;
-            return Ok(());
-        }
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
     };
 
-    ctx.bot.database.execute("UPDATE binds SET discord_roles = array_cat(discord_roles, $1::BIGINT[]) WHERE bind_id = $2", &[&role_ids, &bind.bind_id]).await?;
+    if !check_manageable(&ctx, "Rank Bind Modification Failed", &role_ids).await? {
+        return Ok(());
+    }
+
+    ctx.bot.database.execute("UPDATE binds SET discord_roles = array_cat(discord_roles, $1::BIGINT[]) WHERE bind_id = $2", &[&role_ids, &bind.bind_id()]).await?;
 
     let modification = role_ids
         .iter()
@@ -242,14 +200,13 @@ pub async fn rb_add_roles(ctx: CommandContext, args: AddRoles) -> CommandResult
 pub struct RemoveRoles {
     #[arg(help = "The Group ID of the rankbind to modify")]
     pub group_id: i64,
-    #[arg(help = "The Rank ID of the rankbind to modify")]
-    pub rank_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
     #[arg(help = "The roles to remove", rest)]
     pub roles: String,
 }
 
 pub async fn rb_remove_roles(ctx: CommandContext, args: RemoveRoles) -> CommandResult {
-    let guild_id = ctx.guild_id.unwrap();
     let group_id = args.group_id;
     let rank_id = args.rank_id;
 
@@ -263,56 +220,259 @@ pub async fn rb_remove_roles(ctx: CommandContext, args: RemoveRoles) -> CommandR
     }
     role_ids = role_ids.into_iter().unique().collect::<Vec<_>>();
 
-    let rankbinds = ctx
-        .bot
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    let mut roles_to_keep = bind.discord_roles().to_vec();
+    roles_to_keep.retain(|r| !role_ids.contains(r));
+    ctx.bot
         .database
-        .query::<Rankbind>(
-            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2",
-            &[&(guild_id), &BindType::Rank],
+        .execute(
+            "UPDATE binds SET discord_roles = $1 WHERE bind_id = $2",
+            &[&roles_to_keep, &bind.bind_id()],
         )
         .await?;
 
-    let bind = match rankbinds
+    let modification = role_ids
         .iter()
-        .find(|r| r.group_id == group_id && r.group_rank_id == rank_id)
-    {
-        Some(b) => b,
-        None => {
+        .map(|r| format!("<@&{}> ", r))
+        .collect::<String>();
+    let name = format!("Group Id: {group_id}");
+    let desc = format!("Rank Id: {rank_id}\n`Removed Roles`: {modification}");
+
+    rb_reply_log(ctx, name, desc).await
+}
+
+#[derive(FromArgs)]
+pub struct RemovedRoles {
+    #[arg(help = "The Group ID of the rankbind to modify")]
+    pub group_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
+    #[arg(
+        help = "The roles to remove from members matching the bind. Leave empty to clear",
+        rest
+    )]
+    pub roles: Option<String>,
+}
+
+pub async fn rb_removed_roles(ctx: CommandContext, args: RemovedRoles) -> CommandResult {
+    let group_id = args.group_id;
+    let rank_id = args.rank_id;
+
+    let mut role_ids = Vec::new();
+    for r in args.roles.unwrap_or_default().split_ascii_whitespace() {
+        if let Some(resolved) = &ctx.resolved {
+            role_ids.extend(resolved.roles.iter().map(|r| RoleId(*r.0)));
+        } else if let Some(r) = parse_role(r) {
+            role_ids.push(r);
+        }
+    }
+    role_ids = role_ids.into_iter().unique().collect::<Vec<_>>();
+
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET removed_roles = $1 WHERE bind_id = $2",
+            &[&role_ids, &bind.bind_id()],
+        )
+        .await?;
+
+    let roles_str = |roles: &[RoleId]| {
+        if roles.is_empty() {
+            "None".to_string()
+        } else {
+            roles.iter().map(|r| format!("<@&{r}>")).join(" ")
+        }
+    };
+    let name = format!("Group Id: {group_id}");
+    let desc = format!(
+        "Rank Id: {rank_id}\n`Removed Roles`: {} -> {}",
+        roles_str(bind.removed_roles()),
+        roles_str(&role_ids)
+    );
+
+    rb_reply_log(ctx, name, desc).await
+}
+
+#[derive(FromArgs)]
+pub struct ModifyActive {
+    #[arg(help = "The Group ID of the rankbind to modify")]
+    pub group_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
+    #[arg(help = "When the bind starts applying. Use `none` to apply right away")]
+    pub active_from: ActiveTime,
+    #[arg(help = "When the bind stops applying. Use `none` to never expire")]
+    pub active_until: ActiveTime,
+}
+
+pub async fn rb_modify_active(ctx: CommandContext, args: ModifyActive) -> CommandResult {
+    let group_id = args.group_id;
+    let rank_id = args.rank_id;
+    let active_from = args.active_from.0;
+    let active_until = args.active_until.0;
+
+    if let (Some(from), Some(until)) = (active_from, active_until) {
+        if from >= until {
             let embed = EmbedBuilder::new()
                 .default_data()
                 .color(Color::Red as u32)
                 .title("Rank Bind Modification Failed")
-                .description(format!(
-                    "There was no bind found with Group Id {} and Rank Id {}",
-                    group_id, rank_id
-                ))
+                .description("The bind must become active before it expires")
                 .build()
                 .unwrap();
             ctx.respond().embeds(&[embed])?.exec().await?;
             return Ok(());
         }
+    }
+
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
     };
 
-    let mut roles_to_keep = bind.discord_roles.clone();
-    roles_to_keep.retain(|r| !role_ids.contains(r));
     ctx.bot
         .database
         .execute(
-            "UPDATE binds SET discord_roles = $1 WHERE bind_id = $2",
-            &[&roles_to_keep, &bind.bind_id],
+            "UPDATE binds SET active_from = $1, active_until = $2 WHERE bind_id = $3",
+            &[&active_from, &active_until, &bind.bind_id()],
         )
         .await?;
 
-    let modification = role_ids
-        .iter()
-        .map(|r| format!("<@&{}> ", r))
-        .collect::<String>();
     let name = format!("Group Id: {group_id}");
-    let desc = format!("Rank Id: {rank_id}\n`Removed Roles`: {modification}");
+    let desc = format!(
+        "Rank Id: {rank_id}\n`Active`: {} -> {}",
+        active_window(bind.active_from(), bind.active_until()),
+        active_window(active_from, active_until)
+    );
 
     rb_reply_log(ctx, name, desc).await
 }
 
+#[derive(FromArgs)]
+pub struct ModifyFallbacks {
+    #[arg(help = "The Group ID of the rankbind to modify")]
+    pub group_id: i64,
+    #[arg(help = "The Rank ID of the rankbind, or the range of ranks of the range bind to modify")]
+    pub rank_id: CreateType,
+    #[arg(
+        help = "The templates to try in order when the nickname is too long, separated by `|`. Use `none` to clear them",
+        rest
+    )]
+    pub fallbacks: String,
+}
+
+pub async fn rb_modify_fallbacks(ctx: CommandContext, args: ModifyFallbacks) -> CommandResult {
+    let group_id = args.group_id;
+    let rank_id = args.rank_id;
+    let fallbacks = args.fallbacks;
+
+    if fallbacks.is_empty() {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description("You have entered no fallback templates. Use `none` to clear them")
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+    let fallbacks = parse_fallbacks(&fallbacks);
+
+    let Some(bind) = find_bind(&ctx, group_id, &rank_id).await? else {
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rank Bind Modification Failed")
+            .description(format!(
+                "There was no bind found with Group Id {group_id} and Rank Id {rank_id}"
+            ))
+            .build()
+            .unwrap();
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE binds SET fallback_templates = $1 WHERE bind_id = $2",
+            &[&fallbacks, &bind.bind_id()],
+        )
+        .await?;
+
+    let name = format!("Group Id: {group_id}");
+    let desc = format!(
+        "Rank Id: {rank_id}\n`Fallbacks`: {} -> {}",
+        fallbacks_str(bind.fallback_templates()),
+        fallbacks_str(&fallbacks)
+    );
+
+    rb_reply_log(ctx, name, desc).await
+}
+
+/// Finds the rankbind of the given rank, or the range bind spanning exactly the given ranks
+async fn find_bind(
+    ctx: &CommandContext,
+    group_id: i64,
+    rank_id: &CreateType,
+) -> Result<Option<Bind>, RoError> {
+    let guild_id = ctx.guild_id.unwrap();
+    let binds = ctx
+        .bot
+        .database
+        .query::<Bind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND (bind_type = $2 OR bind_type = $3)",
+            &[&(guild_id), &BindType::Rank, &BindType::Range],
+        )
+        .await?;
+
+    let bind = binds.into_iter().find(|b| match (b, rank_id) {
+        (Bind::Rank(r), CreateType::Single(id)) => r.group_id == group_id && r.group_rank_id == *id,
+        (Bind::Range(r), CreateType::Multiple(min, max)) => {
+            r.group_id == group_id && r.min_rank == *min && r.max_rank == *max
+        }
+        _ => false,
+    });
+    Ok(bind)
+}
+
 async fn rb_reply_log(ctx: CommandContext, name: String, desc: String) -> CommandResult {
     let guild_id = ctx.guild_id.unwrap();
     let embed = EmbedBuilder::new()
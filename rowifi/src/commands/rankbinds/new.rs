@@ -1,12 +1,16 @@
 use itertools::Itertools;
 use lazy_static::lazy_static;
 use regex::Regex;
-use rowifi_framework::prelude::*;
+use rowifi_database::dynamic_args;
+use rowifi_framework::{constants::EMBED_FIELD_DESCRIPTION_LIMIT, prelude::*};
 use rowifi_models::{
-    bind::{BindType, Rankbind, Template},
+    bind::{BindType, Rangebind, Rankbind, Template},
     id::{BindId, RoleId},
     roblox::id::GroupId,
 };
+use std::fmt::{Display, Formatter, Result as FmtResult};
+
+use crate::utils::check_manageable;
 
 #[derive(Debug, FromArgs)]
 pub struct NewRankbind {
@@ -107,6 +111,57 @@ pub async fn rankbinds_new(ctx: CommandContext, args: NewRankbind) -> CommandRes
         return Ok(());
     }
 
+    // Roles created or matched by `auto` are picked per rank below, so only the given roles are checked
+    let given_roles = match &ctx.resolved {
+        Some(resolved) => resolved
+            .roles
+            .keys()
+            .map(|r| RoleId(*r))
+            .collect::<Vec<_>>(),
+        None => roles_to_add
+            .iter()
+            .filter_map(parse_role)
+            .filter(|r| server_roles.iter().any(|s| s.id == *r))
+            .collect::<Vec<_>>(),
+    };
+    if !check_manageable(&ctx, "Rankbinds Addition Failed", &given_roles).await? {
+        return Ok(());
+    }
+
+    // Ranges are stored as a single bind unless the template or roles have to be picked per rank
+    if let CreateType::Multiple(min_rank, max_rank) = rank_ids {
+        let per_rank = template.eq_ignore_ascii_case("auto")
+            || roles_to_add.iter().any(|r| r.eq_ignore_ascii_case("auto"));
+        if !per_rank {
+            let template_str = match template.as_str() {
+                "disable" => "{discord-name}".into(),
+                "N/A" => "{roblox-username}".into(),
+                _ => {
+                    if Template::has_slug(template.as_str()) {
+                        template.clone()
+                    } else {
+                        format!("{template} {{roblox-username}}")
+                    }
+                }
+            };
+
+            let bind = Rangebind {
+                bind_id: BindId::default(),
+                group_id,
+                discord_roles: given_roles.iter().copied().unique().collect::<Vec<_>>(),
+                removed_roles: Vec::new(),
+                active_from: None,
+                active_until: None,
+                fallback_templates: Vec::new(),
+                min_rank,
+                max_rank,
+                priority,
+                template: Template(template_str),
+            };
+            return rangebinds_new(ctx, bind, &rankbinds).await;
+        }
+    }
+
     let mut added = Vec::new();
     let mut modified = Vec::new();
 
@@ -130,35 +185,27 @@ pub async fn rankbinds_new(ctx: CommandContext, args: NewRankbind) -> CommandRes
             }
         };
 
-        let mut roles = Vec::new();
-        for role_to_add in &roles_to_add {
-            if role_to_add.eq_ignore_ascii_case("auto") {
-                let role = match server_roles
-                    .iter()
-                    .find(|r| r.name.eq_ignore_ascii_case(&roblox_rank.name))
-                {
-                    Some(r) => r.id,
-                    None => {
-                        let new_role = ctx
-                            .bot
-                            .http
-                            .create_role(ctx.guild_id.unwrap().0)
-                            .name(&roblox_rank.name)
-                            .exec()
-                            .await?
-                            .model()
-                            .await?;
-                        RoleId(new_role.id)
-                    }
-                };
-                roles.push(role);
-            } else if let Some(resolved) = &ctx.resolved {
-                roles.extend(resolved.roles.iter().map(|r| RoleId(*r.0)));
-            } else if let Some(role_id) = parse_role(role_to_add) {
-                if server_roles.iter().any(|r| r.id == role_id) {
-                    roles.push(role_id);
+        let mut roles = given_roles.clone();
+        if roles_to_add.iter().any(|r| r.eq_ignore_ascii_case("auto")) {
+            let role = match server_roles
+                .iter()
+                .find(|r| r.name.eq_ignore_ascii_case(&roblox_rank.name))
+            {
+                Some(r) => r.id,
+                None => {
+                    let new_role = ctx
+                        .bot
+                        .http
+                        .create_role(ctx.guild_id.unwrap().0)
+                        .name(&roblox_rank.name)
+                        .exec()
+                        .await?
+                        .model()
+                        .await?;
+                    RoleId(new_role.id)
                 }
-            }
+            };
+            roles.push(role);
         }
 
         let rank_id = i64::from(roblox_rank.rank);
@@ -170,6 +217,10 @@ pub async fn rankbinds_new(ctx: CommandContext, args: NewRankbind) -> CommandRes
             roblox_rank_id: roblox_rank.id.0 as i64,
             priority,
             discord_roles: roles.into_iter().unique().collect::<Vec<_>>(),
+            removed_roles: Vec::new(),
+            active_from: None,
+            active_until: None,
+            fallback_templates: Vec::new(),
             template: Template(template_str),
         };
 
@@ -275,6 +326,140 @@ pub async fn rankbinds_new(ctx: CommandContext, args: NewRankbind) -> CommandRes
     Ok(())
 }
 
+/// Creates the range bind, replacing the range bind spanning the same ranks & any rankbinds
+/// within the range. Range binds that only partly overlap it are left for the user to remove
+async fn rangebinds_new(
+    ctx: CommandContext,
+    bind: Rangebind,
+    rankbinds: &[Rankbind],
+) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let rangebinds = ctx
+        .bot
+        .database
+        .query::<Rangebind>(
+            "SELECT * FROM binds WHERE guild_id = $1 AND bind_type = $2 AND group_id = $3",
+            &[&(guild_id), &BindType::Range, &bind.group_id],
+        )
+        .await?;
+    let (existing, overlapping): (Vec<_>, Vec<_>) = rangebinds
+        .iter()
+        .filter(|r| r.overlaps(&bind))
+        .partition(|r| r.min_rank == bind.min_rank && r.max_rank == bind.max_rank);
+    if !overlapping.is_empty() {
+        let ranges = overlapping
+            .iter()
+            .map(|r| format!("`{}-{}`", r.min_rank, r.max_rank))
+            .join(", ");
+        let embed = EmbedBuilder::new()
+            .default_data()
+            .color(Color::Red as u32)
+            .title("Rankbinds Addition Failed")
+            .description(format!(
+                "The ranks {}-{} overlap the range binds {ranges}. Delete or modify those binds first",
+                bind.min_rank, bind.max_rank
+            ))
+            .build()?;
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
+
+    let replaced = rankbinds
+        .iter()
+        .filter(|r| r.group_id == bind.group_id && bind.contains(r.group_rank_id))
+        .collect::<Vec<_>>();
+
+    let mut database = ctx.bot.database.get().await?;
+    let transaction = database.transaction().await?;
+
+    if !replaced.is_empty() {
+        let stmt = transaction
+            .prepare_cached(&format!(
+                "DELETE FROM binds WHERE bind_id IN ({})",
+                dynamic_args(replaced.len())
+            ))
+            .await?;
+        transaction
+            .execute_raw(&stmt, replaced.iter().map(|r| r.bind_id))
+            .await?;
+    }
+
+    let action = if let Some(existing) = existing.first() {
+        let stmt = transaction.prepare_cached("UPDATE binds SET priority = $1, template = $2, discord_roles = $3 WHERE bind_id = $4").await?;
+        transaction
+            .execute(
+                &stmt,
+                &[
+                    &bind.priority,
+                    &bind.template,
+                    &bind.discord_roles,
+                    &existing.bind_id,
+                ],
+            )
+            .await?;
+        "Modified"
+    } else {
+        let stmt = transaction.prepare_cached("INSERT INTO binds(bind_type, guild_id, group_id, min_rank, max_rank, template, priority, discord_roles) VALUES($1, $2, $3, $4, $5, $6, $7, $8)").await?;
+        transaction
+            .execute(
+                &stmt,
+                &[
+                    &BindType::Range,
+                    &(guild_id),
+                    &bind.group_id,
+                    &bind.min_rank,
+                    &bind.max_rank,
+                    &bind.template,
+                    &bind.priority,
+                    &bind.discord_roles,
+                ],
+            )
+            .await?;
+        "Added"
+    };
+
+    transaction.commit().await?;
+
+    let name = format!("Ranks: {}-{}", bind.min_rank, bind.max_rank);
+    let desc = format!(
+        "Template: {}\nPriority: {}\n Roles: {}",
+        bind.template,
+        bind.priority,
+        bind.discord_roles
+            .iter()
+            .map(|r| format!("<@&{r}>"))
+            .join("")
+    );
+    let mut embed = EmbedBuilder::new()
+        .default_data()
+        .title("Binds Addition Sucessful")
+        .color(Color::DarkGreen as u32)
+        .description(format!(
+            "{action} a range bind and replaced {} rankbinds",
+            replaced.len()
+        ))
+        .field(EmbedFieldBuilder::new(name, desc).inline().build());
+    if !replaced.is_empty() {
+        let ranks = replaced
+            .iter()
+            .map(|r| format!("`{}`", r.group_rank_id))
+            .join(", ");
+        embed = embed.field(EmbedFieldBuilder::new(
+            "Replaced Rankbinds",
+            ranks
+                .chars()
+                .take(EMBED_FIELD_DESCRIPTION_LIMIT)
+                .collect::<String>(),
+        ));
+    }
+    let embed = embed.build()?;
+    ctx.respond().embeds(&[embed])?.exec().await?;
+
+    log_rangebind(&ctx, bind).await;
+
+    Ok(())
+}
+
 #[derive(Debug, Eq, PartialEq)]
 #[repr(i8)]
 pub enum CreateType {
@@ -327,6 +512,36 @@ pub async fn log_rankbind(ctx: &CommandContext, bind: Rankbind) {
     ctx.log_guild(ctx.guild_id.unwrap(), log_embed).await;
 }
 
+impl Display for CreateType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            CreateType::Single(id) => write!(f, "{id}"),
+            CreateType::Multiple(min, max) => write!(f, "{min}-{max}"),
+        }
+    }
+}
+
+pub async fn log_rangebind(ctx: &CommandContext, bind: Rangebind) {
+    let name = format!("Group Id: {}", bind.group_id);
+    let roles_str = bind
+        .discord_roles
+        .iter()
+        .map(|r| format!("<@&{r}>"))
+        .join(" ");
+    let desc = format!(
+        "Rank Ids: {}-{}\nTemplate: `{}`\nPriority: {}\nDiscord Roles: {}",
+        bind.min_rank, bind.max_rank, bind.template, bind.priority, roles_str
+    );
+    let log_embed = EmbedBuilder::new()
+        .default_data()
+        .title(format!("Action by {}", ctx.author.name))
+        .description("Range Bind Addition")
+        .field(EmbedFieldBuilder::new(name, desc))
+        .build()
+        .unwrap();
+    ctx.log_guild(ctx.guild_id.unwrap(), log_embed).await;
+}
+
 fn extract_ids(rank_str: &str) -> Option<(i64, i64)> {
     let splits = rank_str.split('-').collect_vec();
     if splits.len() == 2 {
@@ -4,6 +4,7 @@ mod functional;
 mod log;
 mod misc;
 mod nickname_bypass;
+mod templates;
 mod trainer;
 mod update;
 mod verify;
@@ -19,6 +20,7 @@ use misc::{blacklist_action, settings_prefix, toggle_ad, toggle_commands};
 use nickname_bypass::{
     nickname_bypass_add, nickname_bypass_remove, nickname_bypass_set, nickname_bypass_view,
 };
+use templates::{default_template, template_str, unverified_template};
 use trainer::{trainer_add, trainer_remove, trainer_set, trainer_view};
 use update::update_on_join;
 use verify::{
@@ -251,6 +253,18 @@ pub fn settings_config(cmds: &mut Vec<Command>) {
         .description("Command to toggle the auto detection setting of the server")
         .handler(toggle_ad);
 
+    let default_template_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["default-template"])
+        .description("Command to set the template for members no bind gives a nickname to")
+        .handler(default_template);
+
+    let unverified_template_cmd = Command::builder()
+        .level(RoLevel::Admin)
+        .names(&["unverified-template"])
+        .description("Command to set the template for members who haven't verified")
+        .handler(unverified_template);
+
     let settings_cmd = Command::builder()
         .level(RoLevel::Admin)
         .names(&["settings", "setting"])
@@ -270,6 +284,8 @@ pub fn settings_config(cmds: &mut Vec<Command>) {
         .sub_command(log_channel_cmd)
         .sub_command(functional_cmd)
         .sub_command(toggle_ad_cmd)
+        .sub_command(default_template_cmd)
+        .sub_command(unverified_template_cmd)
         .handler(settings_view);
     cmds.push(settings_cmd);
 }
@@ -305,6 +321,20 @@ pub async fn settings_view(ctx: CommandContext) -> CommandResult {
         .field(EmbedFieldBuilder::new("Update On Join", guild.update_on_join.to_string()).inline())
         .field(EmbedFieldBuilder::new("Verification Role", verification_roles).inline())
         .field(EmbedFieldBuilder::new("Verified Role", verified_roles).inline())
+        .field(
+            EmbedFieldBuilder::new(
+                "Default Template",
+                template_str(guild.default_template.as_ref()),
+            )
+            .inline(),
+        )
+        .field(
+            EmbedFieldBuilder::new(
+                "Unverified Template",
+                template_str(guild.unverified_template.as_ref()),
+            )
+            .inline(),
+        )
         .build()
         .unwrap();
 
@@ -0,0 +1,178 @@
+use rowifi_framework::prelude::*;
+use rowifi_models::bind::Template;
+
+#[derive(FromArgs)]
+pub struct DefaultTemplateArguments {
+    #[arg(
+        help = "The template for members no bind gives a nickname to. Use `none` to go back to their Roblox username",
+        rest
+    )]
+    pub template: Option<String>,
+}
+
+pub async fn default_template(
+    ctx: CommandContext,
+    args: DefaultTemplateArguments,
+) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let guild = ctx.bot.database.get_guild(guild_id).await?;
+
+    let Some(template) = args.template else {
+        let content = match &guild.default_template {
+            Some(t) => format!("The default template of this server is `{t}`"),
+            None => "This server does not have a default template set up. Members no bind gives a nickname to get their Roblox username".into(),
+        };
+        ctx.respond().content(&content)?.exec().await?;
+        return Ok(());
+    };
+
+    let template = match template.as_str() {
+        "disable" => Some(Template("{discord-name}".into())),
+        "N/A" => Some(Template("{roblox-username}".into())),
+        _ if template.eq_ignore_ascii_case("none") => None,
+        _ => {
+            if let Some(slug) = Template::invalid_slug(&template) {
+                let embed = EmbedBuilder::new()
+                    .default_data()
+                    .color(Color::Red as u32)
+                    .title("Settings Modification Failed")
+                    .description(format!("`{slug}` is not a valid slug"))
+                    .build()
+                    .unwrap();
+                ctx.respond().embeds(&[embed])?.exec().await?;
+                return Ok(());
+            }
+            if Template::has_slug(&template) {
+                Some(Template(template))
+            } else {
+                Some(Template(format!("{template} {{roblox-username}}")))
+            }
+        }
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE guilds SET default_template = $1 WHERE guild_id = $2",
+            &[&template, &guild.guild_id],
+        )
+        .await?;
+
+    let desc = match &template {
+        Some(t) => format!("The default template has been set to `{t}`"),
+        None => "The default template has been removed".into(),
+    };
+    let embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::DarkGreen as u32)
+        .title("Settings Modification Successful")
+        .description(desc)
+        .build()
+        .unwrap();
+    ctx.respond().embeds(&[embed])?.exec().await?;
+
+    let log_embed = EmbedBuilder::new()
+        .default_data()
+        .title(format!("Action by {}", ctx.author.name))
+        .description(format!(
+            "Settings Modification: Default Template - {} -> {}",
+            template_str(guild.default_template.as_ref()),
+            template_str(template.as_ref())
+        ))
+        .build()
+        .unwrap();
+    ctx.log_guild(guild_id, log_embed).await;
+    Ok(())
+}
+
+#[derive(FromArgs)]
+pub struct UnverifiedTemplateArguments {
+    #[arg(
+        help = "The template for members who haven't verified. Use `none` to leave their nickname as is",
+        rest
+    )]
+    pub template: Option<String>,
+}
+
+pub async fn unverified_template(
+    ctx: CommandContext,
+    args: UnverifiedTemplateArguments,
+) -> CommandResult {
+    let guild_id = ctx.guild_id.unwrap();
+    let guild = ctx.bot.database.get_guild(guild_id).await?;
+
+    let Some(template) = args.template else {
+        let content = match &guild.unverified_template {
+            Some(t) => format!("The unverified template of this server is `{t}`"),
+            None => "This server does not have an unverified template set up".into(),
+        };
+        ctx.respond().content(&content)?.exec().await?;
+        return Ok(());
+    };
+
+    let template = if template.eq_ignore_ascii_case("none") || template == "disable" {
+        None
+    } else {
+        let error = if let Some(slug) = Template::invalid_slug(&template) {
+            Some(format!("`{slug}` is not a valid slug"))
+        } else if Template::has_roblox_slug(&template) {
+            Some("Members who haven't verified have no Roblox account to fill in. Only `{discord-name}` and `{discord-id}` may be used".into())
+        } else {
+            None
+        };
+        if let Some(error) = error {
+            let embed = EmbedBuilder::new()
+                .default_data()
+                .color(Color::Red as u32)
+                .title("Settings Modification Failed")
+                .description(error)
+                .build()
+                .unwrap();
+            ctx.respond().embeds(&[embed])?.exec().await?;
+            return Ok(());
+        }
+        if Template::has_slug(&template) {
+            Some(Template(template))
+        } else {
+            Some(Template(format!("{template} {{discord-name}}")))
+        }
+    };
+
+    ctx.bot
+        .database
+        .execute(
+            "UPDATE guilds SET unverified_template = $1 WHERE guild_id = $2",
+            &[&template, &guild.guild_id],
+        )
+        .await?;
+
+    let desc = match &template {
+        Some(t) => format!("The unverified template has been set to `{t}`"),
+        None => "The unverified template has been removed".into(),
+    };
+    let embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::DarkGreen as u32)
+        .title("Settings Modification Successful")
+        .description(desc)
+        .build()
+        .unwrap();
+    ctx.respond().embeds(&[embed])?.exec().await?;
+
+    let log_embed = EmbedBuilder::new()
+        .default_data()
+        .title(format!("Action by {}", ctx.author.name))
+        .description(format!(
+            "Settings Modification: Unverified Template - {} -> {}",
+            template_str(guild.unverified_template.as_ref()),
+            template_str(template.as_ref())
+        ))
+        .build()
+        .unwrap();
+    ctx.log_guild(guild_id, log_embed).await;
+    Ok(())
+}
+
+pub fn template_str(template: Option<&Template>) -> String {
+    template.map_or_else(|| "None".into(), |t| format!("`{t}`"))
+}
@@ -96,7 +96,15 @@ pub async fn handle_update_button(
                         .exec()
                         .await?;
 
-                    let embed = update_func(ctx, UpdateArguments { user_id: None }, false).await?;
+                    let embed = update_func(
+                        ctx,
+                        UpdateArguments {
+                            user_id: None,
+                            dry_run: None,
+                        },
+                        false,
+                    )
+                    .await?;
                     ctx.bot
                         .http
                         .interaction(ctx.bot.application_id)
@@ -1,23 +1,25 @@
 use itertools::Itertools;
 use rowifi_framework::prelude::*;
-use rowifi_models::{
-    bind::Bind,
-    discord::channel::embed::Embed,
-    id::{RoleId, UserId},
-};
+use rowifi_models::{bind::Bind, discord::channel::embed::Embed, id::UserId};
 use std::error::Error;
 use twilight_http::error::{Error as DiscordHttpError, ErrorType as DiscordErrorType};
 
-use crate::utils::{UpdateUser, UpdateUserResult};
+use crate::utils::{resolve_macros, DryRun, UpdatePlan, UpdateUser, UpdateUserResult};
 
 #[derive(Debug, FromArgs, Clone)]
 pub struct UpdateArguments {
     #[arg(help = "The user to be updated")]
     pub user_id: Option<UserId>,
+    #[arg(help = "Pass `--dry-run` to preview the changes without updating the user")]
+    pub dry_run: Option<DryRun>,
 }
 
 pub async fn update(ctx: CommandContext, args: UpdateArguments) -> Result<(), RoError> {
     let embed = update_func(&ctx, args.clone(), false).await?;
+    if args.dry_run.is_some() {
+        ctx.respond().embeds(&[embed])?.exec().await?;
+        return Ok(());
+    }
     let message = ctx
         .respond()
         .embeds(&[embed])?
@@ -177,12 +179,13 @@ pub async fn update_func(
         }
     };
 
-    let guild = ctx.bot.database.get_guild(guild_id).await?;
-    let binds = ctx
+    let mut guild = ctx.bot.database.get_guild(guild_id).await?;
+    let mut binds = ctx
         .bot
         .database
         .query::<Bind>("SELECT * FROM binds WHERE guild_id = $1", &[&(guild_id)])
         .await?;
+    resolve_macros(&ctx.bot.database, &mut guild, &mut binds).await?;
     let all_roles = binds
         .iter()
         .flat_map(|b| b.discord_roles())
@@ -201,9 +204,16 @@ pub async fn update_func(
         all_roles: &all_roles,
     };
 
-    let (added_roles, removed_roles, disc_nick): (Vec<RoleId>, Vec<RoleId>, String) =
+    if args.dry_run.is_some() {
+        let plan = update_user.plan().await?;
+        return Ok(dry_run_embed(&plan));
+    }
+
+    let (added_roles, removed_roles, denied_roles, unmanageable_roles, disc_nick, nick_fallback) =
         match update_user.execute().await {
-            UpdateUserResult::Success(a, r, n) => (a, r, n),
+            UpdateUserResult::Success(added, removed, denied, unmanageable, nick, fallback) => {
+                (added, removed, denied, unmanageable, nick, fallback)
+            }
             UpdateUserResult::Error(e) => {
                 #[allow(clippy::redundant_closure_for_method_calls)]
                 if let Some(source) = e
@@ -222,10 +232,7 @@ pub async fn update_func(
                                 .color(Color::Red as u32)
                                 .title("Update Failed")
                                 .description(
-                                    "There was an error in updating the user. Possible causes:
-                            1. The user has a role higher than or equal to mine
-                            2. I am trying to add/remove a binded role that is above my highest role
-                            3. Either the verification & verified role are above my highest role",
+                                    "There was an error in updating the user. The user may have a role higher than or equal to mine",
                                 )
                                 .build()
                                 .unwrap();
@@ -240,8 +247,7 @@ pub async fn update_func(
                     .default_data()
                     .title("Update Failed")
                     .description(format!(
-                        "User was found on the server blacklist. Reason: {}",
-                        reason
+                        "User was found on the server blacklist. Reason: {reason}"
                     ))
                     .build()
                     .unwrap();
@@ -268,24 +274,19 @@ pub async fn update_func(
                 }
                 return Ok(embed);
             }
-            UpdateUserResult::InvalidNickname(nickname) => {
-                let embed = EmbedBuilder::new()
-                    .default_data()
-                    .title("Update Failed")
-                    .description(format!(
-                        "The supposed nickname {} is greater than 32 characters.",
-                        nickname
-                    ))
-                    .build()
-                    .unwrap();
-                return Ok(embed);
-            }
         };
     let end = chrono::Utc::now().timestamp_millis();
     let embed = EmbedBuilder::new()
         .default_data()
         .title("Update")
-        .update_log(&added_roles, &removed_roles, &disc_nick)
+        .update_log(
+            &added_roles,
+            &removed_roles,
+            &denied_roles,
+            &unmanageable_roles,
+            &disc_nick,
+            nick_fallback.as_ref(),
+        )
         .color(Color::DarkGreen as u32)
         .footer(EmbedFooterBuilder::new(format!(
             "RoWifi | Executed in {} ms",
@@ -297,10 +298,45 @@ pub async fn update_func(
     let log_embed = EmbedBuilder::new()
         .default_data()
         .title("Update")
-        .update_log(&added_roles, &removed_roles, &disc_nick)
+        .update_log(
+            &added_roles,
+            &removed_roles,
+            &denied_roles,
+            &unmanageable_roles,
+            &disc_nick,
+            nick_fallback.as_ref(),
+        )
         .build()
         .unwrap();
     ctx.log_guild(guild_id, log_embed).await;
 
     Ok(embed)
 }
+
+fn dry_run_embed(plan: &UpdatePlan) -> Embed {
+    let embed = EmbedBuilder::new()
+        .default_data()
+        .title("Update (Dry Run)")
+        .footer(EmbedFooterBuilder::new("RoWifi | Nothing was changed"));
+    if let Some(blacklist) = plan.blacklist {
+        return embed
+            .color(Color::Red as u32)
+            .description(format!(
+                "User would be found on the server blacklist. Reason: {}",
+                blacklist.reason
+            ))
+            .build()
+            .unwrap();
+    }
+    embed
+        .update_log(
+            &plan.added_roles,
+            &plan.removed_roles,
+            &plan.denied_roles,
+            &plan.unmanageable_roles,
+            &plan.nickname,
+            plan.nick_fallback.as_ref(),
+        )
+        .build()
+        .unwrap()
+}
@@ -22,9 +22,9 @@ use axum::{
     Extension, Json, Router, Server,
 };
 use commands::{
-    analytics_config, assetbinds_config, backup_config, blacklists_config, custombinds_config,
-    events_config, group_config, groupbinds_config, premium_config, rankbinds_config,
-    settings_config, user_config,
+    analytics_config, assetbinds_config, backup_config, binds_config, blacklists_config,
+    custombinds_config, events_config, group_config, groupbinds_config, macros_config,
+    premium_config, rankbinds_config, settings_config, user_config,
 };
 use deadpool_redis::{Manager as RedisManager, Pool as RedisPool, Runtime};
 use patreon::Client as PatreonClient;
@@ -223,12 +223,14 @@ async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
     .configure(analytics_config)
     .configure(assetbinds_config)
     .configure(backup_config)
+    .configure(binds_config)
     .configure(blacklists_config)
     .configure(custombinds_config)
     .configure(events_config)
     .configure(group_config)
     // .configure(api_config)
     .configure(groupbinds_config)
+    .configure(macros_config)
     .configure(settings_config)
     .configure(premium_config);
 
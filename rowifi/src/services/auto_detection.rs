@@ -1,3 +1,4 @@
+use chrono::{DateTime, Duration as CDuration, Utc};
 use itertools::Itertools;
 use rowifi_cache::CachedGuild;
 use rowifi_database::postgres::Row;
@@ -6,32 +7,43 @@ use rowifi_models::{
     bind::Bind,
     discord::gateway::{event::Event, payload::outgoing::RequestGuildMembers},
     guild::{GuildType, RoGuild},
-    id::{GuildId, RoleId},
+    id::{GuildId, RoleId, UserId},
     roblox::id::UserId as RobloxUserId,
     user::RoGuildUser,
 };
 use std::{collections::HashSet, env, error::Error, sync::atomic::Ordering};
 use tokio::time::{interval, sleep, timeout, Duration};
 
-use crate::utils::{UpdateUser, UpdateUserResult};
+use crate::utils::{resolve_macros, UpdateUser, UpdateUserResult};
 
 pub async fn auto_detection(ctx: BotContext) {
     tracing::info!("Auto Detection starting");
-    let mut interval = interval(Duration::from_secs(3 * 3600));
+    let period = Duration::from_hours(3);
+    let mut interval = interval(period);
     let chunk_size = if let Ok(chunk_size) = env::var("CHUNK_SIZE") {
         chunk_size.parse::<usize>().unwrap_or(5)
     } else {
         5
     };
+    // The start of the last cycle that went through every guild. Binds that expired after it
+    // still need their roles taken away from unlinked members
+    let mut last_cycle = Utc::now() - CDuration::from_std(period).unwrap();
     loop {
         interval.tick().await;
-        if let Err(err) = execute(&ctx, chunk_size).await {
-            tracing::error!(err = ?err, "Error in auto detection");
+        let cycle = Utc::now();
+        match execute(&ctx, chunk_size, last_cycle, cycle).await {
+            Ok(()) => last_cycle = cycle,
+            Err(err) => tracing::error!(err = ?err, "Error in auto detection"),
         }
     }
 }
 
-async fn execute(ctx: &BotContext, chunk_size: usize) -> Result<(), Box<dyn Error>> {
+async fn execute(
+    ctx: &BotContext,
+    chunk_size: usize,
+    last_cycle: DateTime<Utc>,
+    cycle: DateTime<Utc>,
+) -> Result<(), Box<dyn Error>> {
     let servers = ctx
         .cache
         .guilds()
@@ -41,20 +53,21 @@ async fn execute(ctx: &BotContext, chunk_size: usize) -> Result<(), Box<dyn Erro
 
     let mut guilds = ctx.database.query::<RoGuild>("SELECT * FROM guilds WHERE guild_id = ANY($1) AND (kind = $2 OR kind = $3) AND auto_detection = true", &[&servers, &GuildType::Alpha, &GuildType::Beta]).await?;
     guilds.sort_by_key(|g| g.guild_id);
-    for guild in guilds {
+    for mut guild in guilds {
         let start = chrono::Utc::now().timestamp_millis();
         let guild_id = guild.guild_id;
         let server = match ctx.cache.guild(guild_id) {
             Some(g) => g,
             None => continue,
         };
-        let binds = ctx
+        let mut binds = ctx
             .database
             .query::<Bind>(
                 "SELECT * FROM binds WHERE guild_id = $1",
                 &[&guild.guild_id],
             )
             .await?;
+        resolve_macros(&ctx.database, &mut guild, &mut binds).await?;
         let mut members = ctx
             .cache
             .members(guild_id)
@@ -136,6 +149,20 @@ async fn execute(ctx: &BotContext, chunk_size: usize) -> Result<(), Box<dyn Erro
                 );
             }
         }
+        let linked = users.iter().map(|u| u.discord_id).collect::<HashSet<_>>();
+        let count =
+            remove_expired_roles(ctx, &server, &guild, &binds, &linked, last_cycle, cycle).await;
+        if count > 0 {
+            let log_embed = EmbedBuilder::new()
+                .default_data()
+                .title("Auto Detection: Expired Binds")
+                .description(format!(
+                    "Removed the roles of expired binds from {count} unlinked members"
+                ))
+                .build()
+                .unwrap();
+            ctx.log_guild(guild_id, log_embed).await;
+        }
         let end = chrono::Utc::now().timestamp_millis();
         tracing::info!(time = end-start, server_name = ?server.name, "Time to complete auto detection");
         ctx.log_premium(&format!("{} - {}", server.name, end - start))
@@ -145,6 +172,73 @@ async fn execute(ctx: &BotContext, chunk_size: usize) -> Result<(), Box<dyn Erro
     Ok(())
 }
 
+/// Members who are linked get the roles of expired binds removed when they're updated. Everyone else
+/// holding a role that only expired binds give out has it removed here, once, in the first cycle
+/// after the bind expired. Roles given out by hand after that are left alone
+async fn remove_expired_roles(
+    ctx: &BotContext,
+    server: &CachedGuild,
+    guild: &RoGuild,
+    binds: &[Bind],
+    linked: &HashSet<UserId>,
+    last_cycle: DateTime<Utc>,
+    cycle: DateTime<Utc>,
+) -> usize {
+    let now = Utc::now();
+    let active_roles = binds
+        .iter()
+        .filter(|b| b.is_active(now))
+        .flat_map(|b| b.discord_roles())
+        .collect::<HashSet<_>>();
+    let manageable_roles = ctx.cache.manageable_roles(server.id);
+    let expired_roles = binds
+        .iter()
+        .filter(|b| matches!(b.active_until(), Some(until) if last_cycle < until && until <= cycle))
+        .flat_map(|b| b.discord_roles())
+        .filter(|r| {
+            !active_roles.contains(r)
+                && !guild.verified_roles.contains(r)
+                && manageable_roles.contains(r)
+        })
+        .collect::<HashSet<_>>();
+    if expired_roles.is_empty() {
+        return 0;
+    }
+
+    let mut count = 0;
+    for member_id in ctx.cache.members(server.id) {
+        if linked.contains(&member_id) {
+            continue;
+        }
+        let Some(member) = ctx.cache.member(server.id, member_id) else {
+            continue;
+        };
+        if !member.roles.iter().any(|r| expired_roles.contains(r))
+            || ctx.has_bypass_role(server, &member)
+        {
+            continue;
+        }
+        let roles = member
+            .roles
+            .iter()
+            .filter(|r| !expired_roles.contains(r))
+            .map(|r| r.0)
+            .collect::<Vec<_>>();
+        if let Err(err) = ctx
+            .http
+            .update_guild_member(server.id.0, member.user.id)
+            .roles(&roles)
+            .exec()
+            .await
+        {
+            tracing::error!(err = ?err, member = ?member.user.id, "Error in removing roles of expired binds");
+            continue;
+        }
+        count += 1;
+    }
+    count
+}
+
 #[allow(clippy::too_many_arguments)]
 pub async fn execute_chunk(
     user_chunk: &[RoGuildUser],
@@ -188,12 +282,27 @@ pub async fn execute_chunk(
             };
 
             let res = update_user.execute().await;
-            if let UpdateUserResult::Success(added_roles, removed_roles, disc_nick) = res {
+            if let UpdateUserResult::Success(
+                added_roles,
+                removed_roles,
+                denied_roles,
+                unmanageable_roles,
+                disc_nick,
+                nick_fallback,
+            ) = res
+            {
                 if !added_roles.is_empty() || !removed_roles.is_empty() {
                     let log_embed = EmbedBuilder::new()
                         .default_data()
                         .title(format!("{}: {}", log, name))
-                        .update_log(&added_roles, &removed_roles, &disc_nick)
+                        .update_log(
+                            &added_roles,
+                            &removed_roles,
+                            &denied_roles,
+                            &unmanageable_roles,
+                            &disc_nick,
+                            nick_fallback.as_ref(),
+                        )
                         .build()
                         .unwrap();
                     ctx.log_guild(server.id, log_embed).await;
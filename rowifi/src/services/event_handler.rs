@@ -20,8 +20,8 @@ use tower::Service;
 use twilight_gateway::Event;
 
 use crate::{
-    services::auto_detection,
-    utils::{UpdateUser, UpdateUserResult},
+    services::{auto_detection, update_jobs},
+    utils::{resolve_macros, UpdateUser, UpdateUserResult},
 };
 
 use super::activity;
@@ -135,6 +135,11 @@ impl Service<(u64, Event)> for EventHandler {
                             tokio::time::sleep(Duration::from_secs(3 * 60)).await;
                             activity(context_ac).await;
                         });
+                        let context_uj = eh.bot.clone();
+                        tokio::spawn(async move {
+                            tokio::time::sleep(Duration::from_mins(1)).await;
+                            update_jobs::resume_jobs(context_uj).await;
+                        });
                     }
                     let guild_ids = ready
                         .guilds
@@ -176,7 +181,7 @@ impl Service<(u64, Event)> for EventHandler {
                         Some(m) => m,
                         None => return Ok(()),
                     };
-                    let guild = eh.bot.database.get_guild(guild_id).await?;
+                    let mut guild = eh.bot.database.get_guild(guild_id).await?;
                     if !guild.update_on_join {
                         return Ok(());
                     }
@@ -188,19 +193,26 @@ impl Service<(u64, Event)> for EventHandler {
                                     eh.bot.http.add_guild_member_role(m.guild_id, user_id.0, role.id.0).exec().await?;
                                 }
                             }
+                            if let Some(template) = &guild.unverified_template {
+                                if !eh.bot.has_nickname_bypass(&server, &member) {
+                                    let nickname = template.unverified_nickname(user_id, &member.user.name);
+                                    eh.bot.http.update_guild_member(m.guild_id, user_id.0).nick(Some(&nickname)).unwrap().exec().await?;
+                                }
+                            }
                             return Ok(());
                         },
                     };
 
                     let guild_roles = eh.bot.cache.roles(guild_id);
 
-                    let binds = eh.bot
+                    let mut binds = eh.bot
                         .database
                         .query::<Bind>(
                             "SELECT * FROM binds WHERE guild_id = $1",
                             &[&guild.guild_id],
                         )
                         .await?;
+                    resolve_macros(&eh.bot.database, &mut guild, &mut binds).await?;
                     let all_roles = binds
                         .iter()
                         .flat_map(|b| b.discord_roles())
@@ -218,9 +230,9 @@ impl Service<(u64, Event)> for EventHandler {
                         bypass_roblox_cache: false,
                         all_roles: &all_roles,
                     };
-                    let (added_roles, removed_roles, disc_nick) = match update_user.execute().await
+                    let (added_roles, removed_roles, denied_roles, unmanageable_roles, disc_nick, nick_fallback) = match update_user.execute().await
                     {
-                        UpdateUserResult::Success(a, r, n) => (a, r, n),
+                        UpdateUserResult::Success(added, removed, denied, unmanageable, nick, fallback) => (added, removed, denied, unmanageable, nick, fallback),
                         UpdateUserResult::Blacklist(reason) => {
                             if let Ok(channel) = eh.bot.http.create_private_channel(m.user.id).exec().await?.model().await {
                                 let _ = eh.bot
@@ -236,13 +248,12 @@ impl Service<(u64, Event)> for EventHandler {
                             }
                             return Ok(());
                         },
-                        UpdateUserResult::InvalidNickname(_) => return Ok(()),
                         UpdateUserResult::Error(err) => return Err(err)
                     };
                     let log_embed = EmbedBuilder::new()
                         .default_data()
                         .title("Update On Join")
-                        .update_log(&added_roles, &removed_roles, &disc_nick)
+                        .update_log(&added_roles, &removed_roles, &denied_roles, &unmanageable_roles, &disc_nick, nick_fallback.as_ref())
                         .build()
                         .unwrap();
                     eh.bot.log_guild(guild_id, log_embed).await;
@@ -1,6 +1,7 @@
 pub mod activity;
 pub mod auto_detection;
 pub mod event_handler;
+pub mod update_jobs;
 
 pub use activity::activity;
 // pub use auto_detection::auto_detection;
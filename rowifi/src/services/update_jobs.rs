@@ -0,0 +1,284 @@
+use itertools::Itertools;
+use rowifi_cache::CachedGuild;
+use rowifi_database::postgres::Row;
+use rowifi_framework::{constants::EMBED_FIELD_DESCRIPTION_LIMIT, context::BotContext, prelude::*};
+use rowifi_models::{
+    bind::Bind,
+    discord::gateway::payload::outgoing::RequestGuildMembers,
+    id::{ChannelId, GuildId},
+    job::{JobStatus, UpdateJob},
+    roblox::id::UserId as RobloxUserId,
+    user::RoGuildUser,
+};
+use std::{
+    env,
+    sync::{atomic::Ordering, Arc},
+};
+use tokio::time::{sleep, timeout};
+use twilight_gateway::Event;
+
+use crate::{
+    services::auto_detection::{execute_chunk, mass_update_user},
+    utils::resolve_macros,
+};
+
+/// Resumes the mass updates that were running when the cluster went down. Only the jobs of the
+/// servers on the shards of this cluster are picked up
+pub async fn resume_jobs(ctx: BotContext) {
+    let jobs = match ctx
+        .database
+        .query::<UpdateJob>(
+            "SELECT * FROM update_jobs WHERE status = $1",
+            &[&JobStatus::Running],
+        )
+        .await
+    {
+        Ok(j) => j,
+        Err(err) => {
+            tracing::error!(err = ?err, "Error in fetching the mass update jobs");
+            return;
+        }
+    };
+
+    let first_shard = ctx.cluster_id * ctx.shards_per_cluster;
+    let shards = first_shard..first_shard + ctx.shards_per_cluster;
+    for job in jobs {
+        let shard_id = (job.guild_id.get() >> 22) % ctx.total_shards;
+        if shards.contains(&shard_id) {
+            tracing::info!(job_id = job.job_id, "Resuming mass update job");
+            tokio::spawn(run_job(ctx.clone(), job));
+        }
+    }
+}
+
+/// Runs the job to the end. A job that can't finish is marked as failed, since a job left running
+/// would keep the server from starting another one
+pub async fn run_job(ctx: BotContext, job: UpdateJob) {
+    let job_id = job.job_id;
+    let channel_id = job.channel_id;
+    let failure = match cached_guild(&ctx, job.guild_id).await {
+        Some(server) => match execute_job(&ctx, &server, job).await {
+            Ok(()) => return,
+            Err(err) => {
+                tracing::error!(err = ?err, job_id, "Error in mass update job");
+                err.to_string()
+            }
+        },
+        None => "The server is unavailable".into(),
+    };
+    fail_job(&ctx, job_id, channel_id, &failure).await;
+}
+
+/// Waits for the server to show up in the cache. A job resumed after a restart may start before
+/// the shard has received the server
+async fn cached_guild(ctx: &BotContext, guild_id: GuildId) -> Option<Arc<CachedGuild>> {
+    for _ in 0..10 {
+        if let Some(server) = ctx.cache.guild(guild_id) {
+            return Some(server);
+        }
+        sleep(Duration::from_secs(30)).await;
+    }
+    ctx.cache.guild(guild_id)
+}
+
+async fn fail_job(ctx: &BotContext, job_id: i64, channel_id: ChannelId, failure: &str) {
+    let failure = failure
+        .chars()
+        .take(EMBED_FIELD_DESCRIPTION_LIMIT)
+        .collect::<String>();
+    let failed = ctx
+        .database
+        .query_opt::<Row>(
+            "UPDATE update_jobs SET status = $1, failure = $2, updated_at = NOW() WHERE job_id = $3 AND status = $4 RETURNING job_id",
+            &[&JobStatus::Failed, &failure, &job_id, &JobStatus::Running],
+        )
+        .await;
+    match failed {
+        Ok(Some(_)) => {
+            let _ = ctx
+                .http
+                .create_message(channel_id.0)
+                .content("Updating all members stopped because of an error. Use `update-all status` to see why and `update-all` to start again")
+                .unwrap()
+                .exec()
+                .await;
+        }
+        // The job was cancelled in the meantime
+        Ok(None) => {}
+        Err(err) => {
+            tracing::error!(err = ?err, job_id, "Error in marking the mass update job as failed");
+        }
+    }
+}
+
+async fn execute_job(
+    ctx: &BotContext,
+    server: &CachedGuild,
+    job: UpdateJob,
+) -> Result<(), RoError> {
+    let guild_id = job.guild_id;
+    let mut guild = ctx.database.get_guild(guild_id).await?;
+    let mut binds = ctx
+        .database
+        .query::<Bind>("SELECT * FROM binds WHERE guild_id = $1", &[&(guild_id)])
+        .await?;
+    resolve_macros(&ctx.database, &mut guild, &mut binds).await?;
+
+    if !request_members(ctx, server).await {
+        tracing::warn!(
+            job_id = job.job_id,
+            "Could not request the members of the server"
+        );
+    }
+    let users = linked_users(ctx, guild_id, job.last_member_id).await?;
+    ctx.database
+        .execute(
+            "UPDATE update_jobs SET total = $1 WHERE job_id = $2",
+            &[&(job.processed + users.len() as i64), &job.job_id],
+        )
+        .await?;
+
+    let guild_roles = ctx.cache.roles(guild_id);
+    let all_roles = binds
+        .iter()
+        .flat_map(|b| b.discord_roles())
+        .unique()
+        .collect::<Vec<_>>();
+    let chunk_size = if let Ok(chunk_size) = env::var("CHUNK_SIZE") {
+        chunk_size.parse::<usize>().unwrap_or(5)
+    } else {
+        5
+    };
+
+    for user_chunk in users.chunks(100) {
+        let user_ids = user_chunk
+            .iter()
+            .map(|u| RobloxUserId(u.roblox_id as u64))
+            .collect::<Vec<_>>();
+        if let Err(err) = ctx.roblox.get_users(&user_ids).await {
+            tracing::error!(err = ?err);
+        }
+        for user_sec_chunk in user_chunk.chunks(chunk_size) {
+            let (_, ()) = tokio::join!(
+                execute_chunk(
+                    user_sec_chunk,
+                    ctx,
+                    server,
+                    &guild,
+                    &guild_roles,
+                    false,
+                    job.role_filter,
+                    &binds,
+                    &all_roles
+                ),
+                sleep(Duration::from_secs(1))
+            );
+
+            // The job only moves forward while it's running, so a cancelled job stops here
+            let last_member_id = user_sec_chunk
+                .last()
+                .map(|u| u.discord_id.get() as i64)
+                .unwrap_or_default();
+            let progress = ctx
+                .database
+                .query_opt::<Row>(
+                    "UPDATE update_jobs SET last_member_id = $1, processed = processed + $2, updated_at = NOW() WHERE job_id = $3 AND status = $4 RETURNING job_id",
+                    &[
+                        &last_member_id,
+                        &(user_sec_chunk.len() as i64),
+                        &job.job_id,
+                        &JobStatus::Running,
+                    ],
+                )
+                .await?;
+            if progress.is_none() {
+                tracing::info!(job_id = job.job_id, "Mass update job cancelled");
+                return Ok(());
+            }
+        }
+    }
+
+    ctx.database
+        .execute(
+            "UPDATE update_jobs SET status = $1, updated_at = NOW() WHERE job_id = $2",
+            &[&JobStatus::Completed, &job.job_id],
+        )
+        .await?;
+    let _ = ctx
+        .http
+        .create_message(job.channel_id.0)
+        .content("Finished updating all members")
+        .unwrap()
+        .exec()
+        .await;
+    Ok(())
+}
+
+/// Requests the members of the server from the gateway when less than half of them are cached.
+/// Returns false if the request could not be sent
+pub async fn request_members(ctx: &BotContext, server: &CachedGuild) -> bool {
+    let guild_id = server.id;
+    let members = ctx.cache.members(guild_id).len() as i64;
+    if members >= server.member_count.load(Ordering::SeqCst) / 2 {
+        return true;
+    }
+
+    let req = RequestGuildMembers::builder(server.id.0).query("", None);
+    let shard_id = (guild_id.get() >> 22) % ctx.total_shards;
+    let fut = timeout(
+        Duration::from_mins(1),
+        ctx.standby.wait_for_event(move |event: &Event| {
+            if let Event::MemberChunk(mc) = event {
+                if mc.guild_id == guild_id.0 && mc.chunk_index == mc.chunk_count - 1 {
+                    return true;
+                }
+            }
+            false
+        }),
+    );
+    if ctx.cluster.command(shard_id, &req).await.is_err() {
+        return false;
+    }
+    let _ = fut.await;
+    true
+}
+
+/// The linked users among the cached members of the server whose Discord ids come after the
+/// given id, in ascending order of their ids
+pub async fn linked_users(
+    ctx: &BotContext,
+    guild_id: GuildId,
+    after: i64,
+) -> Result<Vec<RoGuildUser>, RoError> {
+    let members = ctx
+        .cache
+        .members(guild_id)
+        .into_iter()
+        .map(|m| m.0.get() as i64)
+        .filter(|m| *m > after)
+        .collect::<Vec<_>>();
+
+    let rows = ctx
+        .database
+        .query::<Row>(
+            r"
+            SELECT users.discord_id, l.roblox_id, users.default_roblox_id FROM
+            (SELECT * FROM linked_users WHERE guild_id = $1) AS l
+            RIGHT JOIN users
+            ON users.discord_id = l.discord_id
+            WHERE users.discord_id = ANY($2)
+        ",
+            &[&guild_id, &members],
+        )
+        .await?;
+    let mut users = Vec::new();
+    for row in rows {
+        match mass_update_user(&row, guild_id) {
+            Ok(u) => users.push(u),
+            Err(err) => tracing::error!("error in deserializing user: {}", err),
+        }
+    }
+    users.sort_by_key(|u| u.discord_id);
+    tracing::trace!(users = ?users);
+    Ok(users)
+}
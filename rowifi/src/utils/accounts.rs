@@ -0,0 +1,34 @@
+use roblox::Client as RobloxClient;
+use rowifi_database::Database;
+use rowifi_framework::error::RoError;
+use rowifi_models::{roblox::id::UserId as RobloxUserId, user::RoGuildUser};
+use std::collections::HashMap;
+
+/// Fetches the group ranks of every Roblox account linked to the user. `ranks` are the ranks of
+/// the account linked to the server, which are reused instead of being fetched again
+pub async fn fetch_account_ranks(
+    database: &Database,
+    roblox: &RobloxClient,
+    user: &RoGuildUser,
+    ranks: &HashMap<i64, i64>,
+) -> Result<HashMap<i64, HashMap<i64, i64>>, RoError> {
+    let mut accounts = HashMap::new();
+    accounts.insert(user.roblox_id, ranks.clone());
+
+    let Some(linked) = database.get_user(user.discord_id.get() as i64).await? else {
+        return Ok(accounts);
+    };
+    for roblox_id in std::iter::once(linked.default_roblox_id).chain(linked.alts) {
+        if accounts.contains_key(&roblox_id) {
+            continue;
+        }
+        let account_ranks = roblox
+            .get_user_roles(RobloxUserId(roblox_id as u64))
+            .await?
+            .iter()
+            .map(|r| (r.group.id.0 as i64, i64::from(r.role.rank)))
+            .collect::<HashMap<_, _>>();
+        accounts.insert(roblox_id, account_ranks);
+    }
+    Ok(accounts)
+}
@@ -0,0 +1,70 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use rowifi_framework::prelude::*;
+
+/// A point in time of the active window of a bind. `None` leaves that end of the window open
+pub struct ActiveTime(pub Option<DateTime<Utc>>);
+
+impl FromArg for ActiveTime {
+    type Error = ParseError;
+
+    fn from_arg(arg: &str) -> Result<Self, Self::Error> {
+        if arg.eq_ignore_ascii_case("none") || arg.eq_ignore_ascii_case("N/A") {
+            return Ok(ActiveTime(None));
+        }
+        let now = Utc::now();
+        if arg.eq_ignore_ascii_case("now") {
+            return Ok(ActiveTime(Some(now)));
+        }
+        if let Ok(time) = DateTime::parse_from_rfc3339(arg) {
+            return Ok(ActiveTime(Some(time.with_timezone(&Utc))));
+        }
+        if let Ok(time) = NaiveDateTime::parse_from_str(arg, "%Y-%m-%dT%H:%M") {
+            return Ok(ActiveTime(Some(Utc.from_utc_datetime(&time))));
+        }
+        if let Ok(date) = NaiveDate::parse_from_str(arg, "%Y-%m-%d") {
+            if let Some(time) = date.and_hms_opt(0, 0, 0) {
+                return Ok(ActiveTime(Some(Utc.from_utc_datetime(&time))));
+            }
+        }
+
+        let mut arg = arg.to_string();
+        if let Some(unit) = arg.pop() {
+            if let Ok(num) = arg.parse::<i64>() {
+                match unit {
+                    'h' => return Ok(ActiveTime(Some(now + Duration::hours(num)))),
+                    'd' => return Ok(ActiveTime(Some(now + Duration::days(num)))),
+                    'w' => return Ok(ActiveTime(Some(now + Duration::weeks(num)))),
+                    _ => {}
+                }
+            }
+        }
+        Err(ParseError(
+            "a UTC date such as `2026-10-20` or `2026-10-20T18:00`, a time from now such as `12h` `7d` `2w`, `now` or `none`",
+        ))
+    }
+
+    fn from_interaction(option: &CommandDataOption) -> Result<Self, Self::Error> {
+        let arg = match &option.value {
+            CommandOptionValue::Integer(value) => value.to_string(),
+            CommandOptionValue::String(value) => value.clone(),
+            _ => unreachable!("ActiveTime unreached"),
+        };
+        Self::from_arg(&arg)
+    }
+}
+
+/// Describes the active window of a bind for the bind views. Binds without a window apply always
+#[must_use]
+pub fn active_window(
+    active_from: Option<DateTime<Utc>>,
+    active_until: Option<DateTime<Utc>>,
+) -> String {
+    match (active_from, active_until) {
+        (None, None) => "Always".into(),
+        (Some(from), None) => format!("From <t:{}:f>", from.timestamp()),
+        (None, Some(until)) => format!("Until <t:{}:f>", until.timestamp()),
+        (Some(from), Some(until)) => {
+            format!("<t:{}:f> - <t:{}:f>", from.timestamp(), until.timestamp())
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use roblox::{error::Error as RobloxError, Client as RobloxClient};
+use rowifi_models::{
+    bind::AssetType,
+    roblox::id::{AssetId as RobloxAssetId, UserId as RobloxUserId},
+};
+use std::collections::HashSet;
+
+/// Fetches which of the given assets are in the inventory of the user
+pub async fn fetch_owned_assets(
+    roblox: &RobloxClient,
+    user_id: RobloxUserId,
+    assets: impl IntoIterator<Item = (AssetType, i64)>,
+) -> Result<HashSet<(AssetType, i64)>, RobloxError> {
+    let assets = assets.into_iter().collect::<HashSet<_>>();
+    let mut owned_assets = HashSet::new();
+    for (asset_type, asset_id) in assets {
+        let asset = roblox
+            .get_asset(
+                user_id,
+                RobloxAssetId(asset_id as u64),
+                &asset_type.to_string(),
+            )
+            .await?;
+        if asset.is_some() {
+            owned_assets.insert((asset_type, asset_id));
+        }
+    }
+    Ok(owned_assets)
+}
@@ -0,0 +1,280 @@
+use itertools::Itertools;
+use rowifi_cache::{CachedGuild, CachedMember};
+use rowifi_framework::{context::BotContext, prelude::*};
+use rowifi_models::{
+    bind::Bind, discord::channel::embed::Embed, guild::RoGuild, id::RoleId, user::RoGuildUser,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use super::{UpdatePlan, UpdateUser};
+
+/// The roles listed per field of the dry run summary
+const SUMMARY_ROLES: usize = 20;
+
+/// The `--dry-run` flag of the update commands
+#[derive(Clone, Debug)]
+pub struct DryRun;
+
+impl FromArg for DryRun {
+    type Error = ParseError;
+
+    fn from_arg(arg: &str) -> Result<Self, Self::Error> {
+        if arg.eq_ignore_ascii_case("--dry-run") || arg.eq_ignore_ascii_case("dry-run") {
+            Ok(DryRun)
+        } else {
+            Err(ParseError("`--dry-run`"))
+        }
+    }
+
+    fn from_interaction(option: &CommandDataOption) -> Result<Self, Self::Error> {
+        match &option.value {
+            CommandOptionValue::Boolean(true) => Ok(DryRun),
+            CommandOptionValue::String(value) => Self::from_arg(value),
+            _ => Err(ParseError("`--dry-run`")),
+        }
+    }
+}
+
+/// The planned changes of a dry run over many members
+pub struct DryRunReport {
+    pub planned: usize,
+    pub changed: usize,
+    pub nicknames: usize,
+    pub blacklisted: usize,
+    pub failed: usize,
+    /// The number of members gaining & losing each role
+    roles: HashMap<RoleId, (usize, usize)>,
+    csv: String,
+}
+
+impl DryRunReport {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            planned: 0,
+            changed: 0,
+            nicknames: 0,
+            blacklisted: 0,
+            failed: 0,
+            roles: HashMap::new(),
+            csv: "discord_id,username,roblox_id,added_roles,removed_roles,old_nickname,new_nickname,note\n".into(),
+        }
+    }
+
+    /// Records the plan of a member. Only members who would be changed make it into the CSV
+    pub fn add(&mut self, member: &CachedMember, user: &RoGuildUser, plan: &UpdatePlan) {
+        self.planned += 1;
+        let original_nick = member.nick.as_deref().unwrap_or(&member.user.name);
+
+        if let Some(blacklist) = plan.blacklist {
+            self.blacklisted += 1;
+            self.push_row(
+                member,
+                user,
+                &[],
+                &[],
+                original_nick,
+                original_nick,
+                &format!("Blacklisted: {}", blacklist.reason),
+            );
+            return;
+        }
+        if plan.added_roles.is_empty()
+            && plan.removed_roles.is_empty()
+            && plan.unmanageable_roles.is_empty()
+            && plan.nick_fallback.is_none()
+            && !plan.nick_changed
+        {
+            return;
+        }
+
+        self.changed += 1;
+        if plan.nick_changed {
+            self.nicknames += 1;
+        }
+        for role in &plan.added_roles {
+            self.roles.entry(*role).or_default().0 += 1;
+        }
+        for role in &plan.removed_roles {
+            self.roles.entry(*role).or_default().1 += 1;
+        }
+        let mut notes = Vec::new();
+        if !plan.unmanageable_roles.is_empty() {
+            notes.push(format!(
+                "Skipped roles above the bot: {}",
+                plan.unmanageable_roles
+                    .iter()
+                    .map(|r| r.0.to_string())
+                    .join(" ")
+            ));
+        }
+        if let Some(fallback) = &plan.nick_fallback {
+            notes.push(fallback.to_string());
+        }
+        let note = notes.join("; ");
+        self.push_row(
+            member,
+            user,
+            &plan.added_roles,
+            &plan.removed_roles,
+            original_nick,
+            &plan.nickname,
+            &note,
+        );
+    }
+
+    /// Records a member whose plan could not be made
+    pub fn add_error(&mut self, member: &CachedMember, user: &RoGuildUser, err: &RoError) {
+        self.planned += 1;
+        self.failed += 1;
+        let original_nick = member.nick.as_deref().unwrap_or(&member.user.name);
+        self.push_row(
+            member,
+            user,
+            &[],
+            &[],
+            original_nick,
+            original_nick,
+            &format!("Error: {err}"),
+        );
+    }
+
+    pub fn embed(&self, title: &str) -> Result<Embed, EmbedError> {
+        let description = format!(
+            "Nothing was changed. Out of {} members, {} would be updated, {} of them with a new nickname.\n{} would be blacklisted & {} could not be planned.",
+            self.planned, self.changed, self.nicknames, self.blacklisted, self.failed
+        );
+        let mut embed = EmbedBuilder::new()
+            .default_data()
+            .title(title)
+            .description(description);
+
+        let gained = self
+            .roles
+            .iter()
+            .filter(|(_, (gained, _))| *gained > 0)
+            .sorted_by_key(|(_, (gained, _))| std::cmp::Reverse(*gained))
+            .map(|(role, (gained, _))| (*role, *gained))
+            .collect::<Vec<_>>();
+        let lost = self
+            .roles
+            .iter()
+            .filter(|(_, (_, lost))| *lost > 0)
+            .sorted_by_key(|(_, (_, lost))| std::cmp::Reverse(*lost))
+            .map(|(role, (_, lost))| (*role, *lost))
+            .collect::<Vec<_>>();
+        embed = embed
+            .field(EmbedFieldBuilder::new(
+                "Roles Gained",
+                roles_summary(&gained),
+            ))
+            .field(EmbedFieldBuilder::new("Roles Lost", roles_summary(&lost)));
+        embed.build()
+    }
+
+    #[must_use]
+    pub fn csv(&self) -> &str {
+        &self.csv
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn push_row(
+        &mut self,
+        member: &CachedMember,
+        user: &RoGuildUser,
+        added_roles: &[RoleId],
+        removed_roles: &[RoleId],
+        old_nick: &str,
+        new_nick: &str,
+        note: &str,
+    ) {
+        let roles_str = |roles: &[RoleId]| roles.iter().map(|r| r.0.to_string()).join(" ");
+        let row = [
+            member.user.id.to_string(),
+            csv_field(&member.user.name),
+            user.roblox_id.to_string(),
+            roles_str(added_roles),
+            roles_str(removed_roles),
+            csv_field(old_nick),
+            csv_field(new_nick),
+            csv_field(note),
+        ];
+        self.csv.push_str(&row.join(","));
+        self.csv.push('\n');
+    }
+}
+
+impl Default for DryRunReport {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Plans the update of every member in the chunk for a dry run of `update-all` & `update-role`
+#[allow(clippy::too_many_arguments)]
+pub async fn plan_chunk(
+    user_chunk: &[RoGuildUser],
+    ctx: &BotContext,
+    server: &CachedGuild,
+    guild: &RoGuild,
+    guild_roles: &HashSet<RoleId>,
+    role_filter: Option<RoleId>,
+    binds: &[Bind],
+    all_roles: &[&RoleId],
+    report: &mut DryRunReport,
+) {
+    for user in user_chunk {
+        if let Some(member) = ctx.cache.member(server.id, user.discord_id) {
+            if let Some(role_filter) = role_filter {
+                if !member.roles.contains(&role_filter) {
+                    continue;
+                }
+            }
+            if ctx.has_bypass_role(server, &member) {
+                continue;
+            }
+
+            let update_user = UpdateUser {
+                ctx,
+                member: &member,
+                user,
+                server,
+                guild,
+                binds,
+                guild_roles,
+                bypass_roblox_cache: false,
+                all_roles,
+            };
+            match update_user.plan().await {
+                Ok(plan) => report.add(&member, user, &plan),
+                Err(err) => report.add_error(&member, user, &err),
+            }
+        }
+    }
+}
+
+fn roles_summary(roles: &[(RoleId, usize)]) -> String {
+    if roles.is_empty() {
+        return "None".into();
+    }
+    let mut summary = String::new();
+    for (role, count) in roles.iter().take(SUMMARY_ROLES) {
+        let _ = writeln!(summary, "- <@&{}>: {count}", role.0);
+    }
+    if roles.len() > SUMMARY_ROLES {
+        let _ = write!(summary, "...and {} more", roles.len() - SUMMARY_ROLES);
+    }
+    summary
+}
+
+/// Quotes a value for the CSV when it holds a comma, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
@@ -0,0 +1,28 @@
+use itertools::Itertools;
+use rowifi_models::bind::Template;
+
+/// Parses the fallback templates of a bind, given in the order they are tried and separated by `|`.
+/// `none` clears them
+pub fn parse_fallbacks(arg: &str) -> Vec<Template> {
+    if arg.trim().eq_ignore_ascii_case("none") {
+        return Vec::new();
+    }
+    arg.split('|')
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+        .map(|t| match t {
+            "disable" => Template("{discord-name}".into()),
+            "N/A" => Template("{roblox-username}".into()),
+            _ => Template(t.to_string()),
+        })
+        .collect()
+}
+
+/// Lists the fallback templates of a bind in the order they are tried
+pub fn fallbacks_str(templates: &[Template]) -> String {
+    if templates.is_empty() {
+        "None".into()
+    } else {
+        templates.iter().map(|t| format!("`{t}`")).join(" | ")
+    }
+}
@@ -0,0 +1,34 @@
+use rowifi_database::{error::DatabaseError, Database};
+use rowifi_models::{bind::Bind, blacklist::BlacklistData, guild::RoGuild};
+
+/// Resolves the macros called by the custombinds & custom blacklists of the guild so they can be
+/// evaluated. Code that calls a macro which no longer compiles is left unresolved & never matches
+pub async fn resolve_macros(
+    database: &Database,
+    guild: &mut RoGuild,
+    binds: &mut [Bind],
+) -> Result<(), DatabaseError> {
+    let calls_macros = binds
+        .iter()
+        .any(|b| matches!(b, Bind::Custom(c) if c.command.calls_macros()))
+        || guild
+            .blacklists
+            .iter()
+            .any(|b| matches!(&b.data, BlacklistData::Custom(c) if c.calls_macros()));
+    if !calls_macros {
+        return Ok(());
+    }
+
+    let macros = database.get_macros(guild.guild_id).await?;
+    for bind in binds.iter_mut() {
+        if let Bind::Custom(c) = bind {
+            let _ = c.command.resolve(&macros);
+        }
+    }
+    for blacklist in &mut guild.blacklists {
+        if let BlacklistData::Custom(command) = &mut blacklist.data {
+            let _ = command.resolve(&macros);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use itertools::Itertools;
+use rowifi_framework::prelude::*;
+use rowifi_models::id::RoleId;
+
+/// Refuses the command when the bot can't assign some of the roles of a bind, since every update
+/// would have to skip them. Returns whether all of the roles can be assigned
+pub async fn check_manageable(
+    ctx: &CommandContext,
+    title: &str,
+    roles: &[RoleId],
+) -> Result<bool, RoError> {
+    let manageable = ctx.bot.cache.manageable_roles(ctx.guild_id.unwrap());
+    let unmanageable = roles
+        .iter()
+        .filter(|r| !manageable.contains(r))
+        .unique()
+        .map(|r| format!("<@&{r}>"))
+        .join(" ");
+    if unmanageable.is_empty() {
+        return Ok(true);
+    }
+
+    let embed = EmbedBuilder::new()
+        .default_data()
+        .color(Color::Red as u32)
+        .title(title)
+        .description(format!(
+            "I am unable to assign {unmanageable}. Please make sure I have the `Manage Roles` permission & my highest role is above these roles"
+        ))
+        .build()
+        .unwrap();
+    ctx.respond().embeds(&[embed])?.exec().await?;
+    Ok(false)
+}
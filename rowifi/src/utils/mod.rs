@@ -1,3 +1,19 @@
+mod accounts;
+mod active;
+mod assets;
+mod dry_run;
+mod fallbacks;
+mod macros;
+mod manageable;
+mod update_plan;
 mod update_user;
 
+pub use accounts::*;
+pub use active::*;
+pub use assets::*;
+pub use dry_run::*;
+pub use fallbacks::*;
+pub use macros::*;
+pub use manageable::*;
+pub use update_plan::*;
 pub use update_user::*;
@@ -0,0 +1,700 @@
+use chrono::{DateTime, Utc};
+use itertools::Itertools;
+use rowifi_cache::CachedMember;
+use rowifi_models::{
+    bind::{Bind, NicknameFallback},
+    blacklist::Blacklist,
+    guild::RoGuild,
+    id::RoleId,
+    roblox::{group::GroupUserRole, user::PartialUser as RobloxUser},
+    rolang::RoCommandUser,
+};
+use std::collections::HashSet;
+
+/// Everything needed to decide on the changes to a member. The Roblox data is fetched ahead of
+/// time by [`UpdateUser`](super::UpdateUser)
+pub struct PlanInput<'u, 'd> {
+    pub member: &'u CachedMember,
+    pub guild: &'u RoGuild,
+    pub binds: &'u [Bind],
+    /// The roles that currently exist in the server
+    pub guild_roles: &'u HashSet<RoleId>,
+    /// Every role given out by a bind of the server
+    pub all_roles: &'u [&'u RoleId],
+    /// The roles the bot is able to assign in the server
+    pub manageable_roles: &'d HashSet<RoleId>,
+    pub roblox_user: &'d RobloxUser,
+    /// The ranks of the member in every group they're in, for the group slugs of templates
+    pub group_roles: &'d [GroupUserRole],
+    pub command_user: &'d RoCommandUser<'d>,
+    pub nick_bypass: bool,
+    pub now: DateTime<Utc>,
+}
+
+/// The changes to be made to a member. Built by [`plan_update`] & applied by
+/// [`UpdateUser::apply`](super::UpdateUser::apply)
+#[derive(Default)]
+pub struct UpdatePlan<'u> {
+    /// The blacklist the member matched. Nothing else is planned when the member is blacklisted
+    pub blacklist: Option<&'u Blacklist>,
+    pub added_roles: Vec<RoleId>,
+    pub removed_roles: Vec<RoleId>,
+    /// The roles held back by the removed roles of matched binds
+    pub denied_roles: Vec<RoleId>,
+    /// The roles that should have been added or removed but sit above the highest role of the bot
+    pub unmanageable_roles: Vec<RoleId>,
+    pub nickname: String,
+    pub nick_changed: bool,
+    /// How the nickname was picked when the template made it too long
+    pub nick_fallback: Option<NicknameFallback>,
+}
+
+/// Decides on the roles & the nickname of a member without touching Discord
+#[must_use]
+pub fn plan_update<'u>(input: &PlanInput<'u, '_>) -> UpdatePlan<'u> {
+    let member = input.member;
+    let guild = input.guild;
+    let command_user = input.command_user;
+
+    if let Some(blacklist) = guild
+        .blacklists
+        .iter()
+        .find(|b| b.evaluate(command_user).unwrap_or(false))
+    {
+        return UpdatePlan {
+            blacklist: Some(blacklist),
+            ..UpdatePlan::default()
+        };
+    }
+
+    let mut added_roles = Vec::<RoleId>::new();
+    let mut removed_roles = Vec::<RoleId>::new();
+
+    for verification_role in &guild.verification_roles {
+        if input.guild_roles.contains(verification_role) && member.roles.contains(verification_role)
+        {
+            removed_roles.push(*verification_role);
+        }
+    }
+
+    for verified_role in &guild.verified_roles {
+        if input.guild_roles.contains(verified_role) && !member.roles.contains(verified_role) {
+            added_roles.push(*verified_role);
+        }
+    }
+
+    let mut nick_bind: Option<&Bind> = None;
+    let mut roles_to_add = Vec::new();
+    let mut roles_to_remove = Vec::new();
+
+    for bind in input.binds {
+        // Binds outside their active window are treated like binds the member doesn't match, so
+        // the roles of expired binds get removed
+        if !bind.is_active(input.now) {
+            continue;
+        }
+        let matched = match bind {
+            Bind::Rank(r) => match command_user.ranks.get(&r.group_id) {
+                Some(rank_id) => *rank_id == r.group_rank_id,
+                None => r.group_rank_id == 0,
+            },
+            Bind::Range(r) => r.contains(
+                command_user
+                    .ranks
+                    .get(&r.group_id)
+                    .copied()
+                    .unwrap_or_default(),
+            ),
+            Bind::Group(g) => command_user.ranks.contains_key(&g.group_id),
+            Bind::Custom(c) => c.command.evaluate(command_user).unwrap_or(false),
+            Bind::Asset(a) => command_user
+                .owned_assets
+                .contains(&(a.asset_type, a.asset_id)),
+        };
+        if !matched {
+            continue;
+        }
+
+        match nick_bind {
+            Some(highest) if highest.priority() >= bind.priority() => {}
+            _ => nick_bind = Some(bind),
+        }
+        roles_to_add.extend(bind.discord_roles().iter().copied());
+        roles_to_remove.extend(bind.removed_roles().iter().copied());
+    }
+
+    // The removed roles of matched binds take precedence over the roles granted by any bind
+    let denied_roles = roles_to_remove
+        .into_iter()
+        .unique()
+        .filter(|r| {
+            input.guild_roles.contains(r)
+                && (roles_to_add.contains(r) || added_roles.contains(r) || member.roles.contains(r))
+        })
+        .collect::<Vec<_>>();
+    roles_to_add.retain(|r| !denied_roles.contains(r));
+    added_roles.retain(|r| !denied_roles.contains(r));
+
+    for bind_role in input.all_roles {
+        if input.guild_roles.contains(bind_role) {
+            if roles_to_add.contains(bind_role) {
+                if !member.roles.contains(bind_role) {
+                    added_roles.push(**bind_role);
+                }
+            } else if member.roles.contains(bind_role) {
+                removed_roles.push(**bind_role);
+            }
+        }
+    }
+
+    for denied_role in &denied_roles {
+        if member.roles.contains(denied_role) && !removed_roles.contains(denied_role) {
+            removed_roles.push(*denied_role);
+        }
+    }
+
+    // Changing a role the bot can't manage fails the whole request, so those are only reported
+    let unmanageable_roles = added_roles
+        .iter()
+        .chain(removed_roles.iter())
+        .filter(|r| !input.manageable_roles.contains(r))
+        .copied()
+        .collect::<Vec<_>>();
+    added_roles.retain(|r| input.manageable_roles.contains(r));
+    removed_roles.retain(|r| input.manageable_roles.contains(r));
+
+    let original_nick = member
+        .nick
+        .as_ref()
+        .map_or_else(|| member.user.name.as_str(), String::as_str);
+    let (nickname, nick_fallback) = if input.nick_bypass {
+        (original_nick.to_string(), None)
+    } else if let Some(nick_bind) = nick_bind {
+        nick_bind.fitting_nickname(
+            input.roblox_user,
+            command_user.user,
+            &member.user.name,
+            input.group_roles,
+        )
+    } else if let Some(default_template) = &guild.default_template {
+        default_template.fitting_nickname(
+            &[],
+            input.roblox_user,
+            command_user.user,
+            &member.user.name,
+            input.group_roles,
+        )
+    } else {
+        (input.roblox_user.name.clone(), None)
+    };
+    // A nickname that can't be made to fit is left as is, so the roles still get updated
+    let nickname = match nick_fallback {
+        Some(NicknameFallback::TooLong(_)) => original_nick.to_string(),
+        _ => nickname,
+    };
+    let nick_changed = nickname != original_nick;
+
+    UpdatePlan {
+        blacklist: None,
+        added_roles,
+        removed_roles,
+        denied_roles,
+        unmanageable_roles,
+        nickname,
+        nick_changed,
+        nick_fallback,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+    use rowifi_models::{
+        bind::{AssetType, Assetbind, Custombind, Groupbind, Rangebind, Rankbind, Template},
+        blacklist::BlacklistData,
+        discord::{id::Id, user::User},
+        id::{BindId, GuildId, UserId},
+        roblox::id::UserId as RobloxUserId,
+        rolang::{Macros, RoCommand},
+        user::RoGuildUser,
+    };
+    use std::{
+        collections::{HashMap, HashSet},
+        sync::Arc,
+    };
+
+    use super::*;
+
+    const GROUP: i64 = 1000;
+    const ROBLOX_ID: i64 = 3;
+
+    /// The fields every kind of bind has
+    struct Common {
+        roles: Vec<RoleId>,
+        removed: Vec<RoleId>,
+        priority: i32,
+        template: String,
+        fallbacks: Vec<Template>,
+        active_from: Option<DateTime<Utc>>,
+        active_until: Option<DateTime<Utc>>,
+    }
+
+    fn grants(ids: &[u64]) -> Common {
+        Common {
+            roles: roles(ids),
+            removed: Vec::new(),
+            priority: 0,
+            template: "{roblox-username}".into(),
+            fallbacks: Vec::new(),
+            active_from: None,
+            active_until: None,
+        }
+    }
+
+    fn roles(ids: &[u64]) -> Vec<RoleId> {
+        ids.iter().map(|id| RoleId::new(*id)).collect()
+    }
+
+    fn rank(group_rank_id: i64, c: Common) -> Bind {
+        Bind::Rank(Rankbind {
+            bind_id: BindId::default(),
+            group_id: GROUP,
+            discord_roles: c.roles,
+            removed_roles: c.removed,
+            group_rank_id,
+            roblox_rank_id: 0,
+            priority: c.priority,
+            template: Template(c.template),
+            fallback_templates: c.fallbacks,
+            active_from: c.active_from,
+            active_until: c.active_until,
+        })
+    }
+
+    fn range(min_rank: i64, max_rank: i64, c: Common) -> Bind {
+        Bind::Range(Rangebind {
+            bind_id: BindId::default(),
+            group_id: GROUP,
+            discord_roles: c.roles,
+            removed_roles: c.removed,
+            min_rank,
+            max_rank,
+            priority: c.priority,
+            template: Template(c.template),
+            fallback_templates: c.fallbacks,
+            active_from: c.active_from,
+            active_until: c.active_until,
+        })
+    }
+
+    fn group(group_id: i64, c: Common) -> Bind {
+        Bind::Group(Groupbind {
+            bind_id: BindId::default(),
+            group_id,
+            discord_roles: c.roles,
+            removed_roles: c.removed,
+            priority: c.priority,
+            template: Template(c.template),
+            fallback_templates: c.fallbacks,
+            active_from: c.active_from,
+            active_until: c.active_until,
+        })
+    }
+
+    fn custom(code: &str, c: Common) -> Bind {
+        Bind::Custom(Custombind {
+            bind_id: BindId::default(),
+            custom_bind_id: 1,
+            discord_roles: c.roles,
+            removed_roles: c.removed,
+            code: code.into(),
+            priority: c.priority,
+            template: Template(c.template),
+            fallback_templates: c.fallbacks,
+            active_from: c.active_from,
+            active_until: c.active_until,
+            command: RoCommand::new(code, &Macros::default()).unwrap(),
+        })
+    }
+
+    fn asset(asset_type: AssetType, asset_id: i64, c: Common) -> Bind {
+        Bind::Asset(Assetbind {
+            bind_id: BindId::default(),
+            asset_id,
+            asset_type,
+            discord_roles: c.roles,
+            removed_roles: c.removed,
+            priority: c.priority,
+            template: Template(c.template),
+            fallback_templates: c.fallbacks,
+            active_from: c.active_from,
+            active_until: c.active_until,
+        })
+    }
+
+    /// A member of a server in which the bot can manage roles 1 to 20
+    struct Fixture {
+        guild: RoGuild,
+        binds: Vec<Bind>,
+        member: CachedMember,
+        guild_roles: HashSet<RoleId>,
+        manageable_roles: HashSet<RoleId>,
+        ranks: HashMap<i64, i64>,
+        owned_assets: HashSet<(AssetType, i64)>,
+        roblox_name: String,
+        nick_bypass: bool,
+        now: DateTime<Utc>,
+    }
+
+    /// The parts of a plan the tests look at, with the roles sorted
+    #[derive(Debug, PartialEq)]
+    struct Outcome {
+        blacklist: Option<i64>,
+        added: Vec<RoleId>,
+        removed: Vec<RoleId>,
+        denied: Vec<RoleId>,
+        unmanageable: Vec<RoleId>,
+        nickname: String,
+        nick_changed: bool,
+        nick_fallback: Option<NicknameFallback>,
+    }
+
+    impl Fixture {
+        fn new(binds: Vec<Bind>) -> Self {
+            let user = User {
+                accent_color: None,
+                avatar: None,
+                banner: None,
+                bot: false,
+                discriminator: 1,
+                email: None,
+                flags: None,
+                id: Id::new(2),
+                locale: None,
+                mfa_enabled: None,
+                name: "discorduser".into(),
+                premium_type: None,
+                public_flags: None,
+                system: None,
+                verified: None,
+            };
+            let all = (1..=20).map(RoleId::new).collect::<HashSet<_>>();
+            Self {
+                guild: RoGuild::new(GuildId::new(1)),
+                binds,
+                member: CachedMember {
+                    roles: Vec::new(),
+                    nick: None,
+                    user: Arc::new(user),
+                    pending: false,
+                },
+                guild_roles: all.clone(),
+                manageable_roles: all,
+                ranks: HashMap::new(),
+                owned_assets: HashSet::new(),
+                roblox_name: "builderman".into(),
+                nick_bypass: false,
+                now: Utc::now(),
+            }
+        }
+
+        fn plan(&self) -> Outcome {
+            let user = RoGuildUser {
+                guild_id: self.guild.guild_id,
+                discord_id: UserId::new(2),
+                roblox_id: ROBLOX_ID,
+            };
+            let roblox_user = RobloxUser {
+                id: RobloxUserId(ROBLOX_ID as u64),
+                name: self.roblox_name.clone(),
+                display_name: None,
+            };
+            let accounts = HashMap::new();
+            let command_user = RoCommandUser {
+                user: &user,
+                roles: &self.member.roles,
+                ranks: &self.ranks,
+                username: &roblox_user.name,
+                owned_assets: &self.owned_assets,
+                profile: None,
+                accounts: &accounts,
+            };
+            let all_roles = self
+                .binds
+                .iter()
+                .flat_map(|b| b.discord_roles())
+                .unique()
+                .collect::<Vec<_>>();
+            let plan = plan_update(&PlanInput {
+                member: &self.member,
+                guild: &self.guild,
+                binds: &self.binds,
+                guild_roles: &self.guild_roles,
+                all_roles: &all_roles,
+                manageable_roles: &self.manageable_roles,
+                roblox_user: &roblox_user,
+                group_roles: &[],
+                command_user: &command_user,
+                nick_bypass: self.nick_bypass,
+                now: self.now,
+            });
+            let sorted = |roles: Vec<RoleId>| roles.into_iter().sorted().collect::<Vec<_>>();
+            Outcome {
+                blacklist: plan.blacklist.map(|b| b.blacklist_id),
+                added: sorted(plan.added_roles),
+                removed: sorted(plan.removed_roles),
+                denied: sorted(plan.denied_roles),
+                unmanageable: sorted(plan.unmanageable_roles),
+                nickname: plan.nickname,
+                nick_changed: plan.nick_changed,
+                nick_fallback: plan.nick_fallback,
+            }
+        }
+    }
+
+    #[test]
+    fn blacklisted_members_get_nothing_else() {
+        let mut fixture = Fixture::new(vec![group(GROUP, grants(&[1]))]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.guild.blacklists = vec![
+            Blacklist {
+                blacklist_id: 1,
+                reason: "Other user".into(),
+                data: BlacklistData::User(ROBLOX_ID + 1),
+            },
+            Blacklist {
+                blacklist_id: 2,
+                reason: "Banned group".into(),
+                data: BlacklistData::Group(GROUP),
+            },
+        ];
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.blacklist, Some(2));
+        assert!(outcome.added.is_empty());
+        assert!(outcome.removed.is_empty());
+    }
+
+    #[test]
+    fn failing_custom_blacklists_do_not_match() {
+        let mut fixture = Fixture::new(vec![group(GROUP, grants(&[1]))]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.guild.blacklists = vec![Blacklist {
+            blacklist_id: 1,
+            reason: "Broken".into(),
+            data: BlacklistData::Custom(
+                RoCommand::new("GetRank(1000) / 0 == 1", &Macros::default()).unwrap(),
+            ),
+        }];
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.blacklist, None);
+        assert_eq!(outcome.added, roles(&[1]));
+    }
+
+    #[test]
+    fn rank_range_and_group_binds_match_on_rank() {
+        let mut fixture = Fixture::new(vec![
+            rank(10, grants(&[1])),
+            rank(20, grants(&[2])),
+            range(5, 15, grants(&[3])),
+            range(20, 30, grants(&[4])),
+            group(GROUP, grants(&[5])),
+            group(2000, grants(&[6])),
+        ]);
+        fixture.ranks.insert(GROUP, 10);
+        assert_eq!(fixture.plan().added, roles(&[1, 3, 5]));
+
+        // Members who aren't in the group count as rank 0
+        fixture.ranks.clear();
+        fixture.binds = vec![rank(0, grants(&[1])), range(0, 5, grants(&[2]))];
+        assert_eq!(fixture.plan().added, roles(&[1, 2]));
+    }
+
+    #[test]
+    fn custom_and_asset_binds_match() {
+        let mut fixture = Fixture::new(vec![
+            custom("HasRank(1000, 10)", grants(&[1])),
+            custom("IsInGroup(2000)", grants(&[2])),
+            asset(AssetType::Badge, 55, grants(&[3])),
+            asset(AssetType::Gamepass, 55, grants(&[4])),
+        ]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.owned_assets.insert((AssetType::Badge, 55));
+        assert_eq!(fixture.plan().added, roles(&[1, 3]));
+    }
+
+    #[test]
+    fn unmatched_bind_roles_and_verification_roles_are_removed() {
+        let mut fixture = Fixture::new(vec![rank(10, grants(&[1])), rank(20, grants(&[2]))]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.guild.verification_roles = roles(&[9]);
+        fixture.guild.verified_roles = roles(&[10]);
+        fixture.member.roles = roles(&[2, 9, 15]);
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.added, roles(&[1, 10]));
+        assert_eq!(outcome.removed, roles(&[2, 9]));
+    }
+
+    #[test]
+    fn removed_roles_take_precedence() {
+        let mut fixture = Fixture::new(vec![
+            rank(10, grants(&[1, 2])),
+            group(
+                GROUP,
+                Common {
+                    removed: roles(&[2, 4, 10, 11]),
+                    ..grants(&[3])
+                },
+            ),
+        ]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.guild.verified_roles = roles(&[10]);
+        fixture.member.roles = roles(&[4]);
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.added, roles(&[1, 3]));
+        assert_eq!(outcome.removed, roles(&[4]));
+        // Role 11 isn't held or granted, so there's nothing to deny
+        assert_eq!(outcome.denied, roles(&[2, 4, 10]));
+    }
+
+    #[test]
+    fn a_bind_that_gives_and_removes_a_role_removes_it() {
+        let mut fixture = Fixture::new(vec![rank(
+            10,
+            Common {
+                removed: roles(&[2, 3]),
+                ..grants(&[1, 2])
+            },
+        )]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.member.roles = roles(&[2, 3]);
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.added, roles(&[1]));
+        assert_eq!(outcome.removed, roles(&[2, 3]));
+        assert_eq!(outcome.denied, roles(&[2, 3]));
+    }
+
+    #[test]
+    fn unmanageable_roles_are_skipped() {
+        let mut fixture = Fixture::new(vec![rank(10, grants(&[1, 3])), rank(20, grants(&[2]))]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.member.roles = roles(&[2]);
+        fixture.manageable_roles.remove(&RoleId::new(1));
+        fixture.manageable_roles.remove(&RoleId::new(2));
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.added, roles(&[3]));
+        assert!(outcome.removed.is_empty());
+        assert_eq!(outcome.unmanageable, roles(&[1, 2]));
+    }
+
+    #[test]
+    fn highest_priority_picks_the_nickname_and_ties_go_to_the_first_bind() {
+        let bind = |priority, template: &str| Common {
+            priority,
+            template: template.into(),
+            ..grants(&[])
+        };
+        let mut fixture = Fixture::new(vec![
+            rank(10, bind(1, "low")),
+            group(GROUP, bind(5, "first")),
+            custom("IsInGroup(1000)", bind(5, "second")),
+            range(20, 30, bind(10, "unmatched")),
+        ]);
+        fixture.ranks.insert(GROUP, 10);
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.nickname, "first");
+        assert!(outcome.nick_changed);
+    }
+
+    #[test]
+    fn nickname_falls_back_when_the_template_is_too_long() {
+        let long = "x".repeat(40);
+        let template = |template: &str, fallbacks: &[&str]| Common {
+            template: template.into(),
+            fallbacks: fallbacks.iter().map(|f| Template((*f).into())).collect(),
+            ..grants(&[])
+        };
+        let mut fixture = Fixture::new(Vec::new());
+        fixture.ranks.insert(GROUP, 10);
+        fixture.roblox_name = "a_very_long_roblox_username".into();
+
+        fixture.binds = vec![group(GROUP, template("[HR] {roblox-username}", &[]))];
+        let outcome = fixture.plan();
+        assert_eq!(outcome.nickname, "[HR] a_very_long_roblox_username");
+        assert_eq!(outcome.nick_fallback, None);
+
+        fixture.binds = vec![group(
+            GROUP,
+            template(
+                "[High Rank] {roblox-username}",
+                &["[High] {roblox-username}", "[HR] {roblox-username}"],
+            ),
+        )];
+        let outcome = fixture.plan();
+        assert_eq!(outcome.nickname, "[HR] a_very_long_roblox_username");
+        assert_eq!(outcome.nick_fallback, Some(NicknameFallback::Template(1)));
+
+        // The last template has its username cut short when no template fits as it is
+        fixture.binds = vec![group(
+            GROUP,
+            template(
+                "[High Rank] {roblox-username}",
+                &["[High] {roblox-username}"],
+            ),
+        )];
+        let outcome = fixture.plan();
+        assert_eq!(outcome.nickname, "[High] a_very_long_roblox_userna");
+        assert_eq!(outcome.nick_fallback, Some(NicknameFallback::Truncated));
+
+        // Without a username to shorten the nickname is left as it was
+        fixture.member.nick = Some("old".into());
+        fixture.binds = vec![group(GROUP, template(&long, &[]))];
+        let outcome = fixture.plan();
+        assert_eq!(outcome.nickname, "old");
+        assert!(!outcome.nick_changed);
+        assert_eq!(outcome.nick_fallback, Some(NicknameFallback::TooLong(long)));
+    }
+
+    #[test]
+    fn nickname_without_a_bind_uses_the_default_template() {
+        let mut fixture = Fixture::new(Vec::new());
+        assert_eq!(fixture.plan().nickname, "builderman");
+
+        fixture.guild.default_template =
+            Some(Template("{roblox-username} | {discord-name}".into()));
+        assert_eq!(fixture.plan().nickname, "builderman | discorduser");
+
+        fixture.nick_bypass = true;
+        let outcome = fixture.plan();
+        assert_eq!(outcome.nickname, "discorduser");
+        assert!(!outcome.nick_changed);
+    }
+
+    #[test]
+    fn expired_and_upcoming_binds_are_skipped() {
+        let now = Utc::now();
+        let window = |from: Option<i64>, until: Option<i64>, ids| Common {
+            active_from: from.map(|h| now + Duration::hours(h)),
+            active_until: until.map(|h| now + Duration::hours(h)),
+            ..grants(ids)
+        };
+        let mut fixture = Fixture::new(vec![
+            rank(10, window(None, Some(-1), &[1])),
+            rank(10, window(Some(1), None, &[2])),
+            rank(10, window(Some(-1), Some(1), &[3])),
+        ]);
+        fixture.ranks.insert(GROUP, 10);
+        fixture.member.roles = roles(&[1]);
+        fixture.now = now;
+
+        let outcome = fixture.plan();
+        assert_eq!(outcome.added, roles(&[3]));
+        assert_eq!(outcome.removed, roles(&[1]));
+    }
+}
@@ -2,15 +2,18 @@ use itertools::Itertools;
 use rowifi_cache::{CachedGuild, CachedMember};
 use rowifi_framework::{context::BotContext, error::RoError};
 use rowifi_models::{
-    bind::Bind,
+    bind::{Bind, NicknameFallback},
+    blacklist::BlacklistData,
     guild::{BlacklistActionType, RoGuild},
     id::RoleId,
-    roblox::id::{AssetId as RobloxAssetId, UserId as RobloxUserId},
+    roblox::id::UserId as RobloxUserId,
     rolang::RoCommandUser,
     user::RoGuildUser,
 };
 use std::collections::{HashMap, HashSet};
 
+use super::{fetch_account_ranks, fetch_owned_assets, plan_update, PlanInput, UpdatePlan};
+
 pub struct UpdateUser<'u> {
     pub ctx: &'u BotContext,
     pub member: &'u CachedMember,
@@ -24,208 +27,145 @@ pub struct UpdateUser<'u> {
 }
 
 pub enum UpdateUserResult {
-    Success(Vec<RoleId>, Vec<RoleId>, String),
+    /// The roles added, the roles removed, the roles denied by the removed roles of matched binds,
+    /// the roles that could not be changed since the bot can't manage them, the nickname & how it
+    /// was picked when the template of the bind made it too long
+    Success(
+        Vec<RoleId>,
+        Vec<RoleId>,
+        Vec<RoleId>,
+        Vec<RoleId>,
+        String,
+        Option<NicknameFallback>,
+    ),
     Blacklist(String),
-    InvalidNickname(String),
     Error(RoError),
 }
 
-impl UpdateUser<'_> {
+impl<'u> UpdateUser<'u> {
     pub async fn execute(self) -> UpdateUserResult {
-        let mut added_roles = Vec::<RoleId>::new();
-        let mut removed_roles = Vec::<RoleId>::new();
-
-        for verification_role in &self.guild.verification_roles {
-            if self.guild_roles.get(verification_role).is_some()
-                && self.member.roles.contains(verification_role)
-            {
-                removed_roles.push(*verification_role);
-            }
-        }
-
-        for verified_role in &self.guild.verified_roles {
-            if self.guild_roles.get(verified_role).is_some()
-                && !self.member.roles.contains(verified_role)
-            {
-                added_roles.push(*verified_role);
-            }
+        match self.plan().await {
+            Ok(plan) => self.apply(plan).await,
+            Err(err) => UpdateUserResult::Error(err),
         }
+    }
 
+    /// Fetches the Roblox data of the member & decides on the changes to make without touching
+    /// Discord
+    pub async fn plan(&self) -> Result<UpdatePlan<'u>, RoError> {
         let user_id = RobloxUserId(self.user.roblox_id as u64);
-        let user_roles = match self.ctx.roblox.get_user_roles(user_id).await {
-            Ok(user_roles) => user_roles
-                .iter()
-                .map(|r| (r.group.id.0 as i64, i64::from(r.role.rank)))
-                .collect::<HashMap<_, _>>(),
-            Err(e) => return UpdateUserResult::Error(e.into()),
-        };
+        let group_roles = self.ctx.roblox.get_user_roles(user_id).await?;
+        let user_roles = group_roles
+            .iter()
+            .map(|r| (r.group.id.0 as i64, i64::from(r.role.rank)))
+            .collect::<HashMap<_, _>>();
 
-        let roblox_user = match self
+        let roblox_user = self
             .ctx
             .roblox
             .get_user(user_id, self.bypass_roblox_cache)
-            .await
-        {
-            Ok(r) => r,
-            Err(err) => return UpdateUserResult::Error(err.into()),
+            .await?;
+
+        let mut assets = Vec::new();
+        let mut uses_profile = false;
+        let mut uses_accounts = false;
+        let now = chrono::Utc::now();
+        for bind in self.binds.iter().filter(|b| b.is_active(now)) {
+            match bind {
+                Bind::Asset(a) => assets.push((a.asset_type, a.asset_id)),
+                Bind::Custom(c) => {
+                    assets.extend(c.command.assets());
+                    uses_profile |= c.command.uses_profile();
+                    uses_accounts |= c.command.uses_accounts();
+                }
+                _ => {}
+            }
+        }
+        for blacklist in &self.guild.blacklists {
+            if let BlacklistData::Custom(cmd) = &blacklist.data {
+                assets.extend(cmd.assets());
+                uses_profile |= cmd.uses_profile();
+                uses_accounts |= cmd.uses_accounts();
+            }
+        }
+        let owned_assets = fetch_owned_assets(&self.ctx.roblox, user_id, assets).await?;
+        let profile = if uses_profile {
+            Some(self.ctx.roblox.get_user_profile(user_id).await?)
+        } else {
+            None
         };
+        let accounts = if uses_accounts {
+            fetch_account_ranks(&self.ctx.database, &self.ctx.roblox, self.user, &user_roles)
+                .await?
+        } else {
+            HashMap::new()
+        };
+
         let command_user = RoCommandUser {
             user: self.user,
             roles: &self.member.roles,
             ranks: &user_roles,
             username: &roblox_user.name,
+            owned_assets: &owned_assets,
+            profile: profile.as_ref(),
+            accounts: &accounts,
         };
 
-        if !self.guild.blacklists.is_empty() {
-            let success = self
-                .guild
-                .blacklists
-                .iter()
-                .find(|b| b.evaluate(&command_user).unwrap());
-            if let Some(success) = success {
-                match self.guild.blacklist_action {
-                    BlacklistActionType::None => {}
-                    BlacklistActionType::Kick => {
-                        let _ = self
-                            .ctx
-                            .http
-                            .remove_guild_member(self.server.id.0, self.member.user.id)
-                            .exec()
-                            .await;
-                    }
-                    BlacklistActionType::Ban => {
-                        let _ = self
-                            .ctx
-                            .http
-                            .create_ban(self.server.id.0, self.member.user.id)
-                            .exec()
-                            .await;
-                    }
-                };
-                return UpdateUserResult::Blacklist(success.reason.clone());
-            }
-        }
-
-        let mut nick_bind: Option<&Bind> = None;
-        let mut roles_to_add = Vec::new();
+        let manageable_roles = self.ctx.cache.manageable_roles(self.server.id);
+        let plan = plan_update(&PlanInput {
+            member: self.member,
+            guild: self.guild,
+            binds: self.binds,
+            guild_roles: self.guild_roles,
+            all_roles: self.all_roles,
+            manageable_roles: &manageable_roles,
+            roblox_user: &roblox_user,
+            group_roles: &group_roles,
+            command_user: &command_user,
+            nick_bypass: self.ctx.has_nickname_bypass(self.server, self.member),
+            now,
+        });
+        Ok(plan)
+    }
 
-        for bind in self.binds {
-            match bind {
-                Bind::Rank(r) => {
-                    let to_add = match user_roles.get(&r.group_id) {
-                        Some(rank_id) => *rank_id == r.group_rank_id as i64,
-                        None => r.group_rank_id == 0,
-                    };
-                    if to_add {
-                        if let Some(highest) = nick_bind {
-                            if highest.priority() < r.priority {
-                                nick_bind = Some(bind);
-                            }
-                        } else {
-                            nick_bind = Some(bind);
-                        }
-                        roles_to_add.extend(r.discord_roles.iter().copied());
-                    }
-                }
-                Bind::Group(g) => {
-                    if user_roles.contains_key(&g.group_id) {
-                        if let Some(highest) = nick_bind {
-                            if highest.priority() < g.priority {
-                                nick_bind = Some(bind);
-                            }
-                        } else {
-                            nick_bind = Some(bind);
-                        }
-                        roles_to_add.extend(g.discord_roles.iter().copied());
-                    }
-                }
-                Bind::Custom(c) => {
-                    if c.command.evaluate(&command_user).unwrap() {
-                        if let Some(highest) = nick_bind {
-                            if highest.priority() < c.priority {
-                                nick_bind = Some(bind);
-                            }
-                        } else {
-                            nick_bind = Some(bind);
-                        }
-                        roles_to_add.extend(c.discord_roles.iter().copied());
-                    }
-                }
-                Bind::Asset(a) => {
-                    match self
+    /// Carries out the blacklist action or the role & nickname changes of the plan
+    pub async fn apply(&self, plan: UpdatePlan<'_>) -> UpdateUserResult {
+        if let Some(blacklist) = plan.blacklist {
+            match self.guild.blacklist_action {
+                BlacklistActionType::None => {}
+                BlacklistActionType::Kick => {
+                    let _ = self
                         .ctx
-                        .roblox
-                        .get_asset(
-                            user_id,
-                            RobloxAssetId(a.asset_id as u64),
-                            &a.asset_type.to_string(),
-                        )
-                        .await
-                    {
-                        Ok(Some(_)) => {
-                            if let Some(highest) = nick_bind {
-                                if highest.priority() < a.priority {
-                                    nick_bind = Some(bind);
-                                }
-                            } else {
-                                nick_bind = Some(bind);
-                            }
-                            roles_to_add.extend(a.discord_roles.iter().copied());
-                        }
-                        Ok(None) => {}
-                        Err(err) => return UpdateUserResult::Error(err.into()),
-                    }
+                        .http
+                        .remove_guild_member(self.server.id.0, self.member.user.id)
+                        .exec()
+                        .await;
                 }
-            }
-        }
-
-        for bind_role in self.all_roles {
-            if self.guild_roles.get(bind_role).is_some() {
-                if roles_to_add.contains(bind_role) {
-                    if !self.member.roles.contains(bind_role) {
-                        added_roles.push(**bind_role);
-                    }
-                } else if self.member.roles.contains(bind_role) {
-                    removed_roles.push(**bind_role);
+                BlacklistActionType::Ban => {
+                    let _ = self
+                        .ctx
+                        .http
+                        .create_ban(self.server.id.0, self.member.user.id)
+                        .exec()
+                        .await;
                 }
             }
+            return UpdateUserResult::Blacklist(blacklist.reason.clone());
         }
 
-        let original_nick = self
-            .member
-            .nick
-            .as_ref()
-            .map_or_else(|| self.member.user.name.as_str(), String::as_str);
-        let nick_bypass = self.ctx.has_nickname_bypass(self.server, self.member);
-        let nickname = if nick_bypass {
-            original_nick.to_string()
-        } else {
-            nick_bind.map_or_else(
-                || roblox_user.name.to_string(),
-                |nick_bind| nick_bind.nickname(&roblox_user, self.user, &self.member.user.name),
-            )
-        };
-
-        if nickname.len() > 32 {
-            return UpdateUserResult::InvalidNickname(nickname);
-        }
-
-        let update = self
-            .ctx
-            .http
-            .update_guild_member(self.server.id.0, self.member.user.id);
-        let role_changes = !added_roles.is_empty() || !removed_roles.is_empty();
-        let mut roles = self.member.roles.clone();
-        roles.extend_from_slice(&added_roles);
-        roles.retain(|r| !removed_roles.contains(r));
-        let roles = roles.into_iter().unique().map(|r| r.0).collect::<Vec<_>>();
-
-        let nick_changes = nickname != original_nick;
-
-        if role_changes || nick_changes {
-            if let Err(err) = update
+        let role_changes = !plan.added_roles.is_empty() || !plan.removed_roles.is_empty();
+        if role_changes || plan.nick_changed {
+            let mut roles = self.member.roles.clone();
+            roles.extend_from_slice(&plan.added_roles);
+            roles.retain(|r| !plan.removed_roles.contains(r));
+            let roles = roles.into_iter().unique().map(|r| r.0).collect::<Vec<_>>();
+
+            if let Err(err) = self
+                .ctx
+                .http
+                .update_guild_member(self.server.id.0, self.member.user.id)
                 .roles(&roles)
-                .nick(Some(&nickname))
+                .nick(Some(&plan.nickname))
                 .unwrap()
                 .exec()
                 .await
@@ -234,6 +174,13 @@ impl UpdateUser<'_> {
             }
         }
 
-        UpdateUserResult::Success(added_roles, removed_roles, nickname)
+        UpdateUserResult::Success(
+            plan.added_roles,
+            plan.removed_roles,
+            plan.denied_roles,
+            plan.unmanageable_roles,
+            plan.nickname,
+            plan.nick_fallback,
+        )
     }
 }